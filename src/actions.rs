@@ -0,0 +1,792 @@
+// Named player actions and the keys, mouse buttons and gamepad buttons bound to them. Gameplay
+// reads `ActionState` instead of the raw input, so rebinding an action (in the settings menu, or
+// in `bindings.toml`) moves it everywhere at once.
+//
+// Walking is done by the FPS controller, which only reads the keyboard and mouse: the movement
+// actions are handed to it as keys, and their gamepad bindings only steer vehicles. On foot, the
+// gamepad's left stick walks and its right stick looks around, added on top of whatever the
+// controller read itself.
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_fps_controller::controller::{
+    fps_controller_input, fps_controller_look, FpsController, FpsControllerInput,
+};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::config::{load_config, save_config};
+use crate::settings::Settings;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActionBindings::load())
+            .init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_systems(
+                PreUpdate,
+                (capture_rebinding, update_action_state)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(
+                PreUpdate,
+                apply_gamepad_sticks
+                    .after(fps_controller_input)
+                    .before(fps_controller_look),
+            )
+            .add_systems(Update, apply_movement_bindings);
+    }
+}
+
+pub const BINDINGS_PATH: &str = "bindings.toml";
+
+// For controller keys that shouldn't do anything.
+const UNUSED_KEY: KeyCode = KeyCode::F35;
+
+// Stick travel that's ignored, so a worn stick doesn't drift.
+const STICK_DEADZONE: f32 = 0.15;
+// Radians per second with the right stick all the way over, at a sensitivity of 1.0.
+const STICK_TURN_RATE: f32 = 2.5;
+// Keeps the view just short of straight up or down, as the controller does.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+// Written in the bindings file in snake case.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Interact,
+    Scan,
+    Throw,
+    SpawnPackage,
+    Pause,
+    BuildMode,
+    Lights,
+    UseTool,
+    NextTool,
+    PreviousTool,
+    #[serde(rename = "slot_1")]
+    Slot1,
+    #[serde(rename = "slot_2")]
+    Slot2,
+    #[serde(rename = "slot_3")]
+    Slot3,
+    #[serde(rename = "slot_4")]
+    Slot4,
+    #[serde(rename = "slot_5")]
+    Slot5,
+    #[serde(rename = "slot_6")]
+    Slot6,
+    #[serde(rename = "slot_7")]
+    Slot7,
+    #[serde(rename = "slot_8")]
+    Slot8,
+    #[serde(rename = "slot_9")]
+    Slot9,
+    Modifier,
+    TrackPackage,
+    LoadTruck,
+    Consolidate,
+    Clear,
+    Hold,
+    ReturnToSender,
+    LiftUp,
+    LiftDown,
+    StaffPanel,
+    BuyRobot,
+    Dashboard,
+    ExportMetrics,
+    Heatmap,
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+    Crouch,
+}
+
+impl Action {
+    pub const ALL: [Action; 40] = [
+        Action::Interact,
+        Action::Scan,
+        Action::Throw,
+        Action::SpawnPackage,
+        Action::Pause,
+        Action::BuildMode,
+        Action::Lights,
+        Action::UseTool,
+        Action::NextTool,
+        Action::PreviousTool,
+        Action::Slot1,
+        Action::Slot2,
+        Action::Slot3,
+        Action::Slot4,
+        Action::Slot5,
+        Action::Slot6,
+        Action::Slot7,
+        Action::Slot8,
+        Action::Slot9,
+        Action::Modifier,
+        Action::TrackPackage,
+        Action::LoadTruck,
+        Action::Consolidate,
+        Action::Clear,
+        Action::Hold,
+        Action::ReturnToSender,
+        Action::LiftUp,
+        Action::LiftDown,
+        Action::StaffPanel,
+        Action::BuyRobot,
+        Action::Dashboard,
+        Action::ExportMetrics,
+        Action::Heatmap,
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Sprint,
+        Action::Crouch,
+    ];
+
+    // Numbered slots, for the hotbar and the staff panel's lists.
+    pub const SLOTS: [Action; 9] = [
+        Action::Slot1,
+        Action::Slot2,
+        Action::Slot3,
+        Action::Slot4,
+        Action::Slot5,
+        Action::Slot6,
+        Action::Slot7,
+        Action::Slot8,
+        Action::Slot9,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Interact => "Interact",
+            Action::Scan => "Scan",
+            Action::Throw => "Throw",
+            Action::SpawnPackage => "Spawn package",
+            Action::Pause => "Pause",
+            Action::BuildMode => "Build mode",
+            Action::Lights => "Lights",
+            Action::UseTool => "Use tool",
+            Action::NextTool => "Next tool",
+            Action::PreviousTool => "Previous tool",
+            Action::Slot1 => "Slot 1",
+            Action::Slot2 => "Slot 2",
+            Action::Slot3 => "Slot 3",
+            Action::Slot4 => "Slot 4",
+            Action::Slot5 => "Slot 5",
+            Action::Slot6 => "Slot 6",
+            Action::Slot7 => "Slot 7",
+            Action::Slot8 => "Slot 8",
+            Action::Slot9 => "Slot 9",
+            Action::Modifier => "Modifier",
+            Action::TrackPackage => "Track package",
+            Action::LoadTruck => "Load truck",
+            Action::Consolidate => "Consolidate",
+            Action::Clear => "Clear / correct",
+            Action::Hold => "Hold",
+            Action::ReturnToSender => "Return to sender",
+            Action::LiftUp => "Lift up / hitch",
+            Action::LiftDown => "Lift down / unhitch",
+            Action::StaffPanel => "Staff panel",
+            Action::BuyRobot => "Buy robot",
+            Action::Dashboard => "Dashboard",
+            Action::ExportMetrics => "Export metrics",
+            Action::Heatmap => "Heatmap",
+            Action::MoveForward => "Move forward",
+            Action::MoveBack => "Move back",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::Sprint => "Sprint",
+            Action::Crouch => "Crouch",
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Gamepad, Key, Mouse};
+        use GamepadButtonType as Pad;
+
+        match self {
+            Action::Interact => vec![Key(KeyCode::KeyE), Gamepad(Pad::South)],
+            Action::Scan => vec![Key(KeyCode::KeyF), Gamepad(Pad::West)],
+            Action::Throw => vec![Mouse(MouseButton::Left), Gamepad(Pad::RightTrigger2)],
+            Action::SpawnPackage => vec![Key(KeyCode::KeyG), Gamepad(Pad::North)],
+            Action::Pause => vec![Key(KeyCode::Escape), Gamepad(Pad::Start)],
+            Action::BuildMode => vec![Key(KeyCode::KeyB), Gamepad(Pad::Select)],
+            Action::Lights => vec![Key(KeyCode::KeyJ), Gamepad(Pad::RightThumb)],
+            Action::UseTool => vec![Mouse(MouseButton::Right), Gamepad(Pad::LeftTrigger2)],
+            Action::NextTool => vec![Key(KeyCode::Tab), Gamepad(Pad::RightTrigger)],
+            Action::PreviousTool => vec![Gamepad(Pad::LeftTrigger)],
+            Action::Slot1 => vec![Key(KeyCode::Digit1)],
+            Action::Slot2 => vec![Key(KeyCode::Digit2)],
+            Action::Slot3 => vec![Key(KeyCode::Digit3)],
+            Action::Slot4 => vec![Key(KeyCode::Digit4)],
+            Action::Slot5 => vec![Key(KeyCode::Digit5)],
+            Action::Slot6 => vec![Key(KeyCode::Digit6)],
+            Action::Slot7 => vec![Key(KeyCode::Digit7)],
+            Action::Slot8 => vec![Key(KeyCode::Digit8)],
+            Action::Slot9 => vec![Key(KeyCode::Digit9)],
+            Action::Modifier => vec![Key(KeyCode::AltLeft), Key(KeyCode::AltRight)],
+            Action::TrackPackage => vec![Key(KeyCode::KeyT)],
+            Action::LoadTruck => vec![Key(KeyCode::KeyL)],
+            Action::Consolidate => vec![Key(KeyCode::KeyK)],
+            Action::Clear => vec![Key(KeyCode::KeyC)],
+            Action::Hold => vec![Key(KeyCode::KeyH)],
+            Action::ReturnToSender => vec![Key(KeyCode::KeyR)],
+            Action::LiftUp => vec![Key(KeyCode::KeyQ), Gamepad(Pad::East)],
+            Action::LiftDown => vec![Key(KeyCode::KeyZ), Gamepad(Pad::LeftThumb)],
+            Action::StaffPanel => vec![Key(KeyCode::KeyO)],
+            Action::BuyRobot => vec![Key(KeyCode::KeyU)],
+            Action::Dashboard => vec![Key(KeyCode::KeyM)],
+            Action::ExportMetrics => vec![Key(KeyCode::KeyX)],
+            Action::Heatmap => vec![Key(KeyCode::KeyN)],
+            Action::MoveForward => vec![Key(KeyCode::KeyW), Gamepad(Pad::DPadUp)],
+            Action::MoveBack => vec![Key(KeyCode::KeyS), Gamepad(Pad::DPadDown)],
+            Action::MoveLeft => vec![Key(KeyCode::KeyA), Gamepad(Pad::DPadLeft)],
+            Action::MoveRight => vec![Key(KeyCode::KeyD), Gamepad(Pad::DPadRight)],
+            Action::Jump => vec![Key(KeyCode::Space)],
+            Action::Sprint => vec![Key(KeyCode::ShiftLeft)],
+            Action::Crouch => vec![Key(KeyCode::ControlLeft)],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    // On any connected gamepad.
+    Gamepad(GamepadButtonType),
+}
+
+// Keys that can be bound, by the names used in the bindings file.
+const KEYS: [(&str, KeyCode); 62] = [
+    ("KeyA", KeyCode::KeyA),
+    ("KeyB", KeyCode::KeyB),
+    ("KeyC", KeyCode::KeyC),
+    ("KeyD", KeyCode::KeyD),
+    ("KeyE", KeyCode::KeyE),
+    ("KeyF", KeyCode::KeyF),
+    ("KeyG", KeyCode::KeyG),
+    ("KeyH", KeyCode::KeyH),
+    ("KeyI", KeyCode::KeyI),
+    ("KeyJ", KeyCode::KeyJ),
+    ("KeyK", KeyCode::KeyK),
+    ("KeyL", KeyCode::KeyL),
+    ("KeyM", KeyCode::KeyM),
+    ("KeyN", KeyCode::KeyN),
+    ("KeyO", KeyCode::KeyO),
+    ("KeyP", KeyCode::KeyP),
+    ("KeyQ", KeyCode::KeyQ),
+    ("KeyR", KeyCode::KeyR),
+    ("KeyS", KeyCode::KeyS),
+    ("KeyT", KeyCode::KeyT),
+    ("KeyU", KeyCode::KeyU),
+    ("KeyV", KeyCode::KeyV),
+    ("KeyW", KeyCode::KeyW),
+    ("KeyX", KeyCode::KeyX),
+    ("KeyY", KeyCode::KeyY),
+    ("KeyZ", KeyCode::KeyZ),
+    ("Digit0", KeyCode::Digit0),
+    ("Digit1", KeyCode::Digit1),
+    ("Digit2", KeyCode::Digit2),
+    ("Digit3", KeyCode::Digit3),
+    ("Digit4", KeyCode::Digit4),
+    ("Digit5", KeyCode::Digit5),
+    ("Digit6", KeyCode::Digit6),
+    ("Digit7", KeyCode::Digit7),
+    ("Digit8", KeyCode::Digit8),
+    ("Digit9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("ShiftLeft", KeyCode::ShiftLeft),
+    ("ShiftRight", KeyCode::ShiftRight),
+    ("ControlLeft", KeyCode::ControlLeft),
+    ("ControlRight", KeyCode::ControlRight),
+    ("AltLeft", KeyCode::AltLeft),
+    ("ArrowUp", KeyCode::ArrowUp),
+    ("ArrowDown", KeyCode::ArrowDown),
+    ("ArrowLeft", KeyCode::ArrowLeft),
+    ("ArrowRight", KeyCode::ArrowRight),
+];
+
+const MOUSE_BUTTONS: [(&str, MouseButton); 3] = [
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+const GAMEPAD_BUTTONS: [(&str, GamepadButtonType); 18] = [
+    ("GamepadSouth", GamepadButtonType::South),
+    ("GamepadEast", GamepadButtonType::East),
+    ("GamepadNorth", GamepadButtonType::North),
+    ("GamepadWest", GamepadButtonType::West),
+    ("GamepadLeftBumper", GamepadButtonType::LeftTrigger),
+    ("GamepadLeftTrigger", GamepadButtonType::LeftTrigger2),
+    ("GamepadRightBumper", GamepadButtonType::RightTrigger),
+    ("GamepadRightTrigger", GamepadButtonType::RightTrigger2),
+    ("GamepadSelect", GamepadButtonType::Select),
+    ("GamepadStart", GamepadButtonType::Start),
+    ("GamepadLeftStick", GamepadButtonType::LeftThumb),
+    ("GamepadRightStick", GamepadButtonType::RightThumb),
+    ("GamepadDPadUp", GamepadButtonType::DPadUp),
+    ("GamepadDPadDown", GamepadButtonType::DPadDown),
+    ("GamepadDPadLeft", GamepadButtonType::DPadLeft),
+    ("GamepadDPadRight", GamepadButtonType::DPadRight),
+    ("GamepadMode", GamepadButtonType::Mode),
+    ("GamepadC", GamepadButtonType::C),
+];
+
+fn name_of<T: PartialEq + Copy>(table: &[(&'static str, T)], value: T) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(_, candidate)| *candidate == value)
+        .map(|(name, _)| *name)
+}
+
+fn value_of<T: Copy>(table: &[(&'static str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|(_, value)| *value)
+}
+
+impl Binding {
+    pub fn from_name(name: &str) -> Option<Binding> {
+        value_of(&KEYS, name)
+            .map(Binding::Key)
+            .or_else(|| value_of(&MOUSE_BUTTONS, name).map(Binding::Mouse))
+            .or_else(|| value_of(&GAMEPAD_BUTTONS, name).map(Binding::Gamepad))
+    }
+
+    // `None` for buttons that can't be written to the bindings file.
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            Binding::Key(key) => name_of(&KEYS, key),
+            Binding::Mouse(button) => name_of(&MOUSE_BUTTONS, button),
+            Binding::Gamepad(button) => name_of(&GAMEPAD_BUTTONS, button),
+        }
+    }
+
+    // Short enough for a prompt: "E", "1", "MouseLeft", "GamepadSouth".
+    pub fn label(&self) -> String {
+        let Some(name) = self.name() else {
+            return "?".to_string();
+        };
+        name.strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(name)
+            .to_string()
+    }
+
+    fn is_gamepad(&self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+}
+
+// Written in the bindings file by name.
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = self
+            .name()
+            .ok_or_else(|| ser::Error::custom(format!("{:?} has no name", self)))?;
+        serializer.serialize_str(name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Binding::from_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown button `{}`", name)))
+    }
+}
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "BindingsFile", into = "BindingsFile")]
+pub struct ActionBindings {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+// The bindings file: a `[bindings]` table with a list of buttons for each action, in the order
+// the actions are declared. Actions the file leaves out keep their default bindings, minus any
+// button the file gives to something else.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct BindingsFile {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl From<BindingsFile> for ActionBindings {
+    fn from(file: BindingsFile) -> Self {
+        let mut bindings = ActionBindings::default();
+        for (action, buttons) in &file.bindings {
+            for binding in buttons {
+                bindings.take_from_others(*action, *binding);
+            }
+        }
+        bindings.bindings.extend(file.bindings);
+        bindings.reject_conflicts();
+        bindings
+    }
+}
+
+impl From<ActionBindings> for BindingsFile {
+    fn from(bindings: ActionBindings) -> Self {
+        BindingsFile {
+            bindings: bindings.bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Default for ActionBindings {
+    fn default() -> Self {
+        ActionBindings {
+            bindings: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl ActionBindings {
+    pub fn load() -> Self {
+        load_config(BINDINGS_PATH).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        save_config(BINDINGS_PATH, self);
+    }
+
+    pub fn of(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // The first binding, for prompts.
+    pub fn label(&self, action: Action) -> String {
+        self.of(action)
+            .first()
+            .map_or("unbound".to_string(), Binding::label)
+    }
+
+    // Every binding, for the settings menu.
+    pub fn describe(&self, action: Action) -> String {
+        let labels: Vec<String> = self.of(action).iter().map(Binding::label).collect();
+        if labels.is_empty() {
+            "unbound".to_string()
+        } else {
+            labels.join(" / ")
+        }
+    }
+
+    // Replaces the action's keyboard and mouse binding, or its gamepad binding, so rebinding one
+    // doesn't take away the other.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.take_from_others(action, binding);
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|existing| existing.is_gamepad() != binding.is_gamepad());
+        bindings.insert(0, binding);
+    }
+
+    // A button only does one thing, so binding it to an action takes it off any other.
+    fn take_from_others(&mut self, action: Action, binding: Binding) {
+        for (other, bindings) in self.bindings.iter_mut() {
+            if *other != action {
+                bindings.retain(|existing| *existing != binding);
+            }
+        }
+    }
+
+    // When the file binds one button to several actions, the first of them keeps it.
+    fn reject_conflicts(&mut self) {
+        let mut taken: HashMap<Binding, Action> = HashMap::new();
+        for action in Action::ALL {
+            let Some(bindings) = self.bindings.get_mut(&action) else {
+                continue;
+            };
+            bindings.retain(|binding| match taken.get(binding) {
+                Some(owner) => {
+                    warn!(
+                        "{}: {} is already bound to {}, ignoring it for {}",
+                        BINDINGS_PATH,
+                        binding.label(),
+                        owner.label(),
+                        action.label()
+                    );
+                    false
+                }
+                None => {
+                    taken.insert(*binding, action);
+                    true
+                }
+            });
+        }
+    }
+
+    // The first keyboard binding, for the FPS controller.
+    fn key(&self, action: Action) -> Option<KeyCode> {
+        self.of(action).iter().find_map(|binding| match binding {
+            Binding::Key(key) => Some(*key),
+            _ => None,
+        })
+    }
+}
+
+// What the bound actions are doing this frame.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: Vec<Action>,
+    just_pressed: Vec<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    // Index of the numbered slot picked this frame.
+    pub fn just_pressed_slot(&self) -> Option<usize> {
+        Action::SLOTS
+            .iter()
+            .position(|action| self.just_pressed(*action))
+    }
+
+    // 1.0, -1.0 or 0.0 when both or neither are held.
+    pub fn axis(&self, positive: Action, negative: Action) -> f32 {
+        self.pressed(positive) as i8 as f32 - self.pressed(negative) as i8 as f32
+    }
+}
+
+// The action waiting for its next button press in the settings menu.
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding(pub Option<Action>);
+
+#[derive(Clone, Copy)]
+struct RawInput<'a> {
+    keys: &'a ButtonInput<KeyCode>,
+    mouse: &'a ButtonInput<MouseButton>,
+    gamepad_buttons: &'a ButtonInput<GamepadButton>,
+    gamepads: &'a Gamepads,
+}
+
+impl RawInput<'_> {
+    fn is_down(&self, binding: Binding, just_pressed: bool) -> bool {
+        match binding {
+            Binding::Key(key) if just_pressed => self.keys.just_pressed(key),
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::Mouse(button) if just_pressed => self.mouse.just_pressed(button),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+            Binding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                let button = GamepadButton::new(gamepad, button_type);
+                if just_pressed {
+                    self.gamepad_buttons.just_pressed(button)
+                } else {
+                    self.gamepad_buttons.pressed(button)
+                }
+            }),
+        }
+    }
+
+    // The first bindable button pressed this frame.
+    fn first_pressed(&self) -> Option<Binding> {
+        let key = self
+            .keys
+            .get_just_pressed()
+            .map(|key| Binding::Key(*key))
+            .find(|binding| binding.name().is_some());
+        let mouse = || {
+            self.mouse
+                .get_just_pressed()
+                .map(|button| Binding::Mouse(*button))
+                .find(|binding| binding.name().is_some())
+        };
+        let gamepad = || {
+            self.gamepad_buttons
+                .get_just_pressed()
+                .map(|button| Binding::Gamepad(button.button_type))
+                .find(|binding| binding.name().is_some())
+        };
+        key.or_else(mouse).or_else(gamepad)
+    }
+}
+
+// While an action is being rebound, the next press goes to it (or Escape cancels) and no actions
+// fire, so the press doesn't also do whatever it was bound to before.
+fn capture_rebinding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<ActionBindings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+
+    let input = RawInput {
+        keys: &keys,
+        mouse: &mouse,
+        gamepad_buttons: &gamepad_buttons,
+        gamepads: &gamepads,
+    };
+    if let Some(binding) = input.first_pressed() {
+        bindings.rebind(action, binding);
+        rebinding.0 = None;
+        info!("{} bound to {}", action.label(), binding.label());
+    }
+}
+
+fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    bindings: Res<ActionBindings>,
+    rebinding: Res<Rebinding>,
+    mut state: ResMut<ActionState>,
+) {
+    state.pressed.clear();
+    state.just_pressed.clear();
+    // Also covers the frame the rebinding finished on.
+    if rebinding.0.is_some() || rebinding.is_changed() {
+        return;
+    }
+
+    let input = RawInput {
+        keys: &keys,
+        mouse: &mouse,
+        gamepad_buttons: &gamepad_buttons,
+        gamepads: &gamepads,
+    };
+    for action in Action::ALL {
+        let bound = bindings.of(action);
+        if bound.iter().any(|binding| input.is_down(*binding, false)) {
+            state.pressed.push(action);
+        }
+        if bound.iter().any(|binding| input.is_down(*binding, true)) {
+            state.just_pressed.push(action);
+        }
+    }
+}
+
+// Also runs for controllers spawned after the bindings last changed.
+fn apply_movement_bindings(
+    bindings: Res<ActionBindings>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    for mut controller in controller_query.iter_mut() {
+        if !bindings.is_changed() && !controller.is_added() {
+            continue;
+        }
+
+        let controller = controller.as_mut();
+        let keys = [
+            (Action::MoveForward, &mut controller.key_forward),
+            (Action::MoveBack, &mut controller.key_back),
+            (Action::MoveLeft, &mut controller.key_left),
+            (Action::MoveRight, &mut controller.key_right),
+            (Action::Jump, &mut controller.key_jump),
+            (Action::Sprint, &mut controller.key_sprint),
+            (Action::Crouch, &mut controller.key_crouch),
+        ];
+        for (action, key) in keys {
+            if let Some(bound) = bindings.key(action) {
+                *key = bound;
+            }
+        }
+
+        // The controller's noclip mode defaults to F, with E and Q to fly up and down, which would
+        // fire alongside Scan and Interact. Nothing in the game flies, so park them on a key no
+        // keyboard has.
+        controller.key_fly = UNUSED_KEY;
+        controller.key_up = UNUSED_KEY;
+        controller.key_down = UNUSED_KEY;
+    }
+}
+
+// Past the deadzone the stick is rescaled to start from zero, so there's no jump at its edge.
+fn read_stick(
+    axes: &Axis<GamepadAxis>,
+    gamepad: Gamepad,
+    x: GamepadAxisType,
+    y: GamepadAxisType,
+) -> Vec2 {
+    let stick = Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+        axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0),
+    );
+    let length = stick.length();
+    if length <= STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+    stick / length * ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0)
+}
+
+fn apply_gamepad_sticks(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut controller_query: Query<(&FpsController, &mut FpsControllerInput)>,
+) {
+    let (mut walk, mut look) = (Vec2::ZERO, Vec2::ZERO);
+    for gamepad in gamepads.iter() {
+        use GamepadAxisType::{LeftStickX, LeftStickY, RightStickX, RightStickY};
+        walk += read_stick(&axes, gamepad, LeftStickX, LeftStickY);
+        look += read_stick(&axes, gamepad, RightStickX, RightStickY);
+    }
+    if walk == Vec2::ZERO && look == Vec2::ZERO {
+        return;
+    }
+
+    let turn = STICK_TURN_RATE * settings.controls.stick_sensitivity * time.delta_seconds();
+    for (controller, mut input) in controller_query.iter_mut() {
+        if !controller.enable_input {
+            continue;
+        }
+
+        // The controller's movement is x to the right and z forward.
+        if walk != Vec2::ZERO {
+            let movement = (input.movement.xz() + walk).clamp_length_max(1.0);
+            input.movement.x = movement.x;
+            input.movement.z = movement.y;
+        }
+        input.yaw -= look.x * turn;
+        input.pitch = (input.pitch + look.y * turn).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+}
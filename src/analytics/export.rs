@@ -0,0 +1,203 @@
+// Writes every shift so far to a CSV and a JSON file under `metrics/`, named after the time of the
+// export so runs on different layouts don't overwrite each other.
+
+use bevy::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::analytics::metrics::{Equipment, ShiftMetrics, ShiftMetricsLog, Stage, SHIFT_HOURS};
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportMetrics>()
+            .add_systems(Update, export_metrics);
+    }
+}
+
+const EXPORT_DIRECTORY: &str = "metrics";
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ExportMetrics;
+
+// Missing values are left empty.
+fn optional(value: Option<f32>) -> String {
+    value.map_or(String::new(), |value| format!("{:.4}", value))
+}
+
+fn csv_header() -> String {
+    let mut columns: Vec<String> = [
+        "shift",
+        "started_at",
+        "hours",
+        "packages_received",
+        "packages_sorted",
+        "sort_accuracy",
+        "packages_shipped",
+        "packages_per_hour",
+        "average_dwell_hours",
+        "max_dwell_hours",
+        "late_packages",
+        "trucks_departed",
+        "late_departures",
+        "bottleneck",
+    ]
+    .iter()
+    .map(|column| column.to_string())
+    .collect();
+
+    for hour in 0..SHIFT_HOURS {
+        columns.push(format!("shipped_hour_{}", hour + 1));
+    }
+    for equipment in Equipment::ALL {
+        columns.push(format!(
+            "utilization_{}",
+            equipment.label().replace(' ', "_")
+        ));
+    }
+    for stage in Stage::ALL {
+        columns.push(format!("backlog_{}", stage.label()));
+    }
+
+    columns.join(",")
+}
+
+fn csv_row(metrics: &ShiftMetrics) -> String {
+    let mut values = vec![
+        metrics.shift.to_string(),
+        format!("{:.4}", metrics.started_at),
+        format!("{:.4}", metrics.hours_elapsed),
+        metrics.packages_received.to_string(),
+        metrics.packages_sorted.to_string(),
+        optional(metrics.sort_accuracy()),
+        metrics.packages_shipped.to_string(),
+        format!("{:.4}", metrics.packages_per_hour()),
+        optional(metrics.average_dwell_hours()),
+        format!("{:.4}", metrics.max_dwell_hours),
+        metrics.late_packages.to_string(),
+        metrics.trucks_departed.to_string(),
+        metrics.late_departures.to_string(),
+        metrics
+            .bottleneck()
+            .map_or(String::new(), |stage| stage.label().to_string()),
+    ];
+
+    values.extend(metrics.shipped_by_hour.iter().map(u32::to_string));
+    values.extend(
+        Equipment::ALL
+            .iter()
+            .map(|equipment| optional(metrics.utilization_of(*equipment))),
+    );
+    values.extend(
+        Stage::ALL
+            .iter()
+            .map(|stage| format!("{:.4}", metrics.average_backlog(*stage))),
+    );
+
+    values.join(",")
+}
+
+pub fn to_csv(shifts: &[ShiftMetrics]) -> String {
+    let mut csv = csv_header();
+    csv.push('\n');
+    for metrics in shifts {
+        csv.push_str(&csv_row(metrics));
+        csv.push('\n');
+    }
+    csv
+}
+
+// One shift as it's written to JSON, with the figures worked out from the raw counts. Missing
+// values are written as null.
+#[derive(Serialize)]
+struct ShiftSnapshot {
+    shift: u32,
+    started_at: f32,
+    hours: f32,
+    packages_received: u32,
+    packages_sorted: u32,
+    sort_accuracy: Option<f32>,
+    packages_shipped: u32,
+    packages_per_hour: f32,
+    average_dwell_hours: Option<f32>,
+    max_dwell_hours: f32,
+    late_packages: u32,
+    trucks_departed: u32,
+    late_departures: u32,
+    bottleneck: Option<&'static str>,
+    shipped_by_hour: Vec<u32>,
+    utilization: BTreeMap<&'static str, Option<f32>>,
+    average_backlog: BTreeMap<&'static str, f32>,
+}
+
+impl ShiftSnapshot {
+    fn new(metrics: &ShiftMetrics) -> Self {
+        ShiftSnapshot {
+            shift: metrics.shift,
+            started_at: metrics.started_at,
+            hours: metrics.hours_elapsed,
+            packages_received: metrics.packages_received,
+            packages_sorted: metrics.packages_sorted,
+            sort_accuracy: metrics.sort_accuracy(),
+            packages_shipped: metrics.packages_shipped,
+            packages_per_hour: metrics.packages_per_hour(),
+            average_dwell_hours: metrics.average_dwell_hours(),
+            max_dwell_hours: metrics.max_dwell_hours,
+            late_packages: metrics.late_packages,
+            trucks_departed: metrics.trucks_departed,
+            late_departures: metrics.late_departures,
+            bottleneck: metrics.bottleneck().map(|stage| stage.label()),
+            shipped_by_hour: metrics.shipped_by_hour.to_vec(),
+            utilization: Equipment::ALL
+                .iter()
+                .map(|equipment| (equipment.label(), metrics.utilization_of(*equipment)))
+                .collect(),
+            average_backlog: Stage::ALL
+                .iter()
+                .map(|stage| (stage.label(), metrics.average_backlog(*stage)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MetricsExport {
+    shifts: Vec<ShiftSnapshot>,
+}
+
+pub fn to_json(shifts: &[ShiftMetrics]) -> serde_json::Result<String> {
+    let export = MetricsExport {
+        shifts: shifts.iter().map(ShiftSnapshot::new).collect(),
+    };
+    serde_json::to_string_pretty(&export)
+}
+
+fn write_exports(shifts: &[ShiftMetrics]) -> std::io::Result<PathBuf> {
+    let directory = PathBuf::from(EXPORT_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let base = directory.join(format!("shifts-{}", stamp));
+
+    fs::write(base.with_extension("csv"), to_csv(shifts))?;
+    fs::write(base.with_extension("json"), to_json(shifts)?)?;
+    Ok(base)
+}
+
+fn export_metrics(mut requests: EventReader<ExportMetrics>, log: Res<ShiftMetricsLog>) {
+    // Several requests in one frame would only write the same files again.
+    if requests.read().count() == 0 {
+        return;
+    }
+
+    match write_exports(&log.shifts) {
+        Ok(base) => info!("Exported shift metrics to {}.csv/.json", base.display()),
+        Err(error) => warn!("Couldn't export shift metrics: {}", error),
+    }
+}
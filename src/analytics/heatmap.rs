@@ -0,0 +1,279 @@
+// Floor heatmaps, drawn over the navigation grid's cells. N cycles through the layers (where
+// packages sit, where the player walks, where sorted packages back up at the chutes) and back to
+// off; Alt+N clears them. Heat is the time something spent on a cell since the last clear, so
+// busy spots keep glowing after the crowd is gone.
+
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::utils::HashMap;
+use bevy_fps_controller::controller::LogicalPlayer;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::ChuteInput;
+use crate::levels::package_data::Package;
+use crate::logistics::sorting::SortedTo;
+use crate::navigation::grid::{NavGrid, CELL_SIZE};
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heatmaps>()
+            .add_systems(OnEnter(AssetLoaderState::Done), spawn_heatmap_overlay)
+            .add_systems(
+                Update,
+                (heatmap_controls, sample_heatmaps, draw_heatmap)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+const SAMPLE_SECONDS: f32 = 0.25;
+const REDRAW_SECONDS: f32 = 0.5;
+// Sorted packages this close to their chute count as backed up behind it.
+const CHUTE_BACKUP_RADIUS: f32 = 3.0;
+// Cells cooler than this share of the hottest one aren't drawn.
+const MIN_DRAWN_HEAT: f32 = 0.02;
+// Just above the floor, so the overlay doesn't flicker against it.
+const OVERLAY_HEIGHT: f32 = 0.03;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HeatmapLayer {
+    PackageDwell,
+    PlayerTraffic,
+    ChuteBackups,
+}
+
+impl HeatmapLayer {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeatmapLayer::PackageDwell => "package dwell",
+            HeatmapLayer::PlayerTraffic => "player traffic",
+            HeatmapLayer::ChuteBackups => "chute backups",
+        }
+    }
+
+    // The layer after this one, or `None` to turn the overlay off.
+    fn next(shown: Option<HeatmapLayer>) -> Option<HeatmapLayer> {
+        match shown {
+            None => Some(HeatmapLayer::PackageDwell),
+            Some(HeatmapLayer::PackageDwell) => Some(HeatmapLayer::PlayerTraffic),
+            Some(HeatmapLayer::PlayerTraffic) => Some(HeatmapLayer::ChuteBackups),
+            Some(HeatmapLayer::ChuteBackups) => None,
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Heatmaps {
+    pub shown: Option<HeatmapLayer>,
+    // Seconds spent on each cell, per layer.
+    heat: HashMap<HeatmapLayer, HashMap<IVec2, f32>>,
+    sample_in: f32,
+    redraw_in: f32,
+}
+
+impl Heatmaps {
+    pub fn heat(&self, layer: HeatmapLayer, cell: IVec2) -> f32 {
+        self.heat
+            .get(&layer)
+            .and_then(|cells| cells.get(&cell))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    // The hottest cell in a layer and its heat.
+    pub fn hottest(&self, layer: HeatmapLayer) -> Option<(IVec2, f32)> {
+        self.heat
+            .get(&layer)?
+            .iter()
+            .map(|(cell, heat)| (*cell, *heat))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn add(&mut self, layer: HeatmapLayer, cell: IVec2, seconds: f32) {
+        *self.heat.entry(layer).or_default().entry(cell).or_default() += seconds;
+    }
+
+    pub fn clear(&mut self) {
+        self.heat.clear();
+        self.redraw_in = 0.0;
+    }
+}
+
+#[derive(Component)]
+struct HeatmapOverlay;
+
+fn spawn_heatmap_overlay(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(heatmap_mesh(Vec::new())),
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HeatmapOverlay,
+        NotShadowCaster,
+        NotShadowReceiver,
+        Name::new("Heatmap overlay"),
+    ));
+}
+
+fn heatmap_controls(actions: Res<ActionState>, mut heatmaps: ResMut<Heatmaps>) {
+    if !actions.just_pressed(Action::Heatmap) {
+        return;
+    }
+
+    if actions.pressed(Action::Modifier) {
+        heatmaps.clear();
+        info!("Heatmaps cleared");
+        return;
+    }
+
+    heatmaps.shown = HeatmapLayer::next(heatmaps.shown);
+    heatmaps.redraw_in = 0.0;
+    match heatmaps.shown {
+        Some(layer) => info!("Heatmap: {}", layer.label()),
+        None => info!("Heatmap off"),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn sample_heatmaps(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut heatmaps: ResMut<Heatmaps>,
+    package_query: Query<(&GlobalTransform, Option<&SortedTo>), (With<Package>, Without<Parent>)>,
+    player_query: Query<&GlobalTransform, With<LogicalPlayer>>,
+    chute_query: Query<(&ChuteInput, &GlobalTransform)>,
+) {
+    heatmaps.sample_in -= time.delta_seconds();
+    if heatmaps.sample_in > 0.0 || grid.is_empty() {
+        return;
+    }
+    heatmaps.sample_in = SAMPLE_SECONDS;
+
+    for (transform, sorted_to) in package_query.iter() {
+        let position = transform.translation();
+        let Some(cell) = grid.cell_at(position) else {
+            continue;
+        };
+        heatmaps.add(HeatmapLayer::PackageDwell, cell, SAMPLE_SECONDS);
+
+        let backed_up = sorted_to.is_some_and(|sorted_to| {
+            chute_query.iter().any(|(chute, chute_transform)| {
+                chute.name == sorted_to.chute
+                    && chute_transform.translation().xz().distance(position.xz())
+                        < CHUTE_BACKUP_RADIUS
+            })
+        });
+        if backed_up {
+            heatmaps.add(HeatmapLayer::ChuteBackups, cell, SAMPLE_SECONDS);
+        }
+    }
+
+    for transform in player_query.iter() {
+        if let Some(cell) = grid.cell_at(transform.translation()) {
+            heatmaps.add(HeatmapLayer::PlayerTraffic, cell, SAMPLE_SECONDS);
+        }
+    }
+}
+
+// Blue through green and yellow to red, getting more opaque as it heats up.
+fn heat_color(heat: f32) -> [f32; 4] {
+    let heat = heat.clamp(0.0, 1.0);
+    let (r, g, b) = if heat < 0.33 {
+        let t = heat / 0.33;
+        (0.0, t, 1.0 - t)
+    } else if heat < 0.66 {
+        let t = (heat - 0.33) / 0.33;
+        (t, 1.0, 0.0)
+    } else {
+        let t = (heat - 0.66) / 0.34;
+        (1.0, 1.0 - t, 0.0)
+    };
+    [r, g, b, 0.25 + heat * 0.45]
+}
+
+// One flat quad per cell, at the cell's floor height.
+fn heatmap_mesh(cells: Vec<(Vec3, f32)>) -> Mesh {
+    let half = CELL_SIZE * 0.5;
+    let mut positions = Vec::with_capacity(cells.len() * 4);
+    let mut colors = Vec::with_capacity(cells.len() * 4);
+    let mut indices = Vec::with_capacity(cells.len() * 6);
+
+    for (center, heat) in cells {
+        let first = positions.len() as u32;
+        let y = center.y + OVERLAY_HEIGHT;
+        positions.extend([
+            [center.x - half, y, center.z - half],
+            [center.x - half, y, center.z + half],
+            [center.x + half, y, center.z + half],
+            [center.x + half, y, center.z - half],
+        ]);
+        colors.extend([heat_color(heat); 4]);
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn draw_heatmap(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut heatmaps: ResMut<Heatmaps>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut overlay_query: Query<(&Handle<Mesh>, &mut Visibility), With<HeatmapOverlay>>,
+) {
+    let Ok((mesh_handle, mut visibility)) = overlay_query.get_single_mut() else {
+        return;
+    };
+    let Some(layer) = heatmaps.shown else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    heatmaps.redraw_in -= time.delta_seconds();
+    if heatmaps.redraw_in > 0.0 {
+        return;
+    }
+    heatmaps.redraw_in = REDRAW_SECONDS;
+
+    let hottest = heatmaps.hottest(layer).map_or(0.0, |(_, heat)| heat);
+    let cells: Vec<(Vec3, f32)> = heatmaps
+        .heat
+        .get(&layer)
+        .into_iter()
+        .flatten()
+        .filter(|_| hottest > 0.0)
+        .map(|(cell, heat)| (grid.cell_center(*cell), heat / hottest))
+        .filter(|(_, heat)| *heat >= MIN_DRAWN_HEAT)
+        .collect();
+
+    if let Some(mesh) = meshes.get_mut(mesh_handle) {
+        *mesh = heatmap_mesh(cells);
+    }
+}
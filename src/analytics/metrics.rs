@@ -0,0 +1,395 @@
+// Per-shift throughput numbers, for comparing one floor layout against another. Counts come from
+// the events the rest of the game already sends; utilization and backlog are sampled every frame
+// and averaged over the shift.
+
+use bevy::prelude::*;
+
+use crate::automation::robots::{Robot, RobotTask};
+use crate::game_state::GameState;
+use crate::levels::markers::ChuteInput;
+use crate::levels::package_data::Package;
+use crate::logistics::address::AddressException;
+use crate::logistics::customs::CustomsStatus;
+use crate::logistics::outbound::{OutboundTruck, PackageDeparted, TruckDeparted};
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::sorting::{is_correct_chute, PackageSorted, SortedTo};
+use crate::logistics::tracking::{TrackingEventKind, TrackingLog};
+use crate::staff::jobs::{is_blocked, InboundCargo};
+use crate::staff::workers::Worker;
+use crate::vehicles::driving::Occupied;
+use crate::vehicles::material_handling::{Vehicle, VehicleKind};
+
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShiftMetricsLog>().add_systems(
+            Update,
+            (
+                start_shifts,
+                count_packages,
+                sample_utilization,
+                sample_backlog,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Shifts are eight hours long, the first one starting at 06:00 on day 0.
+pub const SHIFT_HOURS: usize = 8;
+const FIRST_SHIFT_STARTS_AT: f32 = 6.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Equipment {
+    Workers,
+    Robots,
+    PalletJacks,
+    Forklifts,
+    Tuggers,
+}
+
+impl Equipment {
+    pub const ALL: [Equipment; 5] = [
+        Equipment::Workers,
+        Equipment::Robots,
+        Equipment::PalletJacks,
+        Equipment::Forklifts,
+        Equipment::Tuggers,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Equipment::Workers => "workers",
+            Equipment::Robots => "robots",
+            Equipment::PalletJacks => "pallet jacks",
+            Equipment::Forklifts => "forklifts",
+            Equipment::Tuggers => "tuggers",
+        }
+    }
+
+    fn from_vehicle(kind: VehicleKind) -> Self {
+        match kind {
+            VehicleKind::PalletJack => Equipment::PalletJacks,
+            VehicleKind::Forklift => Equipment::Forklifts,
+            VehicleKind::Tugger => Equipment::Tuggers,
+        }
+    }
+}
+
+// Where packages are waiting. Whichever stage has the most of them on average is the bottleneck.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    Unloading,
+    Held,
+    Sorting,
+    Loading,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [
+        Stage::Unloading,
+        Stage::Held,
+        Stage::Sorting,
+        Stage::Loading,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stage::Unloading => "unloading",
+            Stage::Held => "held",
+            Stage::Sorting => "sorting",
+            Stage::Loading => "loading",
+        }
+    }
+}
+
+// Seconds a kind of equipment spent busy, out of the seconds each unit of it existed.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Utilization {
+    pub busy_seconds: f32,
+    pub total_seconds: f32,
+}
+
+impl Utilization {
+    pub fn rate(&self) -> Option<f32> {
+        (self.total_seconds > 0.0).then(|| self.busy_seconds / self.total_seconds)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShiftMetrics {
+    pub shift: u32,
+    // Absolute hours, see `ShiftClock::now`.
+    pub started_at: f32,
+    pub hours_elapsed: f32,
+    pub packages_received: u32,
+    pub packages_sorted: u32,
+    pub sorted_correctly: u32,
+    pub packages_shipped: u32,
+    pub late_packages: u32,
+    pub trucks_departed: u32,
+    // Trucks that left with at least one package past its sort deadline.
+    pub late_departures: u32,
+    // Arrival to departure, for packages shipped this shift.
+    pub total_dwell_hours: f32,
+    pub max_dwell_hours: f32,
+    pub shipped_by_hour: [u32; SHIFT_HOURS],
+    pub utilization: [Utilization; Equipment::ALL.len()],
+    // Packages waiting at each stage, summed over every sampled second.
+    pub backlog_package_seconds: [f32; Stage::ALL.len()],
+    pub sampled_seconds: f32,
+}
+
+impl ShiftMetrics {
+    fn new(shift: u32) -> Self {
+        ShiftMetrics {
+            shift,
+            started_at: FIRST_SHIFT_STARTS_AT + shift as f32 * SHIFT_HOURS as f32,
+            hours_elapsed: 0.0,
+            packages_received: 0,
+            packages_sorted: 0,
+            sorted_correctly: 0,
+            packages_shipped: 0,
+            late_packages: 0,
+            trucks_departed: 0,
+            late_departures: 0,
+            total_dwell_hours: 0.0,
+            max_dwell_hours: 0.0,
+            shipped_by_hour: [0; SHIFT_HOURS],
+            utilization: [Utilization::default(); Equipment::ALL.len()],
+            backlog_package_seconds: [0.0; Stage::ALL.len()],
+            sampled_seconds: 0.0,
+        }
+    }
+
+    pub fn packages_per_hour(&self) -> f32 {
+        if self.hours_elapsed > 0.0 {
+            self.packages_shipped as f32 / self.hours_elapsed
+        } else {
+            0.0
+        }
+    }
+
+    pub fn sort_accuracy(&self) -> Option<f32> {
+        (self.packages_sorted > 0)
+            .then(|| self.sorted_correctly as f32 / self.packages_sorted as f32)
+    }
+
+    pub fn average_dwell_hours(&self) -> Option<f32> {
+        (self.packages_shipped > 0).then(|| self.total_dwell_hours / self.packages_shipped as f32)
+    }
+
+    pub fn utilization_of(&self, equipment: Equipment) -> Option<f32> {
+        self.utilization[equipment.index()].rate()
+    }
+
+    pub fn average_backlog(&self, stage: Stage) -> f32 {
+        if self.sampled_seconds > 0.0 {
+            self.backlog_package_seconds[stage.index()] / self.sampled_seconds
+        } else {
+            0.0
+        }
+    }
+
+    // The stage with the longest queue on average, if anything queued at all.
+    pub fn bottleneck(&self) -> Option<Stage> {
+        Stage::ALL
+            .into_iter()
+            .map(|stage| (stage, self.average_backlog(stage)))
+            .filter(|(_, backlog)| *backlog > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(stage, _)| stage)
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ShiftMetricsLog {
+    // Oldest first. The last one is the shift in progress.
+    pub shifts: Vec<ShiftMetrics>,
+}
+
+impl ShiftMetricsLog {
+    pub fn current(&self) -> Option<&ShiftMetrics> {
+        self.shifts.last()
+    }
+
+    fn current_mut(&mut self) -> Option<&mut ShiftMetrics> {
+        self.shifts.last_mut()
+    }
+}
+
+pub fn shift_at(hours: f32) -> u32 {
+    ((hours - FIRST_SHIFT_STARTS_AT) / SHIFT_HOURS as f32)
+        .floor()
+        .max(0.0) as u32
+}
+
+fn start_shifts(clock: Res<ShiftClock>, mut log: ResMut<ShiftMetricsLog>) {
+    let now = clock.now();
+    let shift = shift_at(now);
+
+    if log.current().is_none_or(|current| current.shift != shift) {
+        log.shifts.push(ShiftMetrics::new(shift));
+    }
+    if let Some(current) = log.current_mut() {
+        current.hours_elapsed = (now - current.started_at).max(0.0);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn count_packages(
+    clock: Res<ShiftClock>,
+    tracking: Res<TrackingLog>,
+    mut log: ResMut<ShiftMetricsLog>,
+    new_packages: Query<(), Added<Package>>,
+    package_query: Query<&Package>,
+    chute_query: Query<&ChuteInput>,
+    mut sorted: EventReader<PackageSorted>,
+    mut departed_packages: EventReader<PackageDeparted>,
+    mut departed_trucks: EventReader<TruckDeparted>,
+) {
+    let now = clock.now();
+    let Some(current) = log.current_mut() else {
+        return;
+    };
+
+    current.packages_received += new_packages.iter().count() as u32;
+
+    let chutes: Vec<&str> = chute_query
+        .iter()
+        .map(|chute| chute.name.as_str())
+        .collect();
+    for event in sorted.read() {
+        let Ok(package) = package_query.get(event.package) else {
+            continue;
+        };
+        current.packages_sorted += 1;
+        if is_correct_chute(package, &event.chute, &chutes) {
+            current.sorted_correctly += 1;
+        }
+    }
+
+    for event in departed_packages.read() {
+        current.packages_shipped += 1;
+        if event.late {
+            current.late_packages += 1;
+        }
+
+        let hour = (current.hours_elapsed.floor() as usize).min(SHIFT_HOURS - 1);
+        current.shipped_by_hour[hour] += 1;
+
+        let arrived_at = tracking.history(&event.tracking_id).and_then(|history| {
+            history
+                .iter()
+                .find(|entry| entry.kind == TrackingEventKind::ArrivedAtFacility)
+                .map(|entry| entry.at)
+        });
+        if let Some(arrived_at) = arrived_at {
+            let dwell = (now - arrived_at).max(0.0);
+            current.total_dwell_hours += dwell;
+            current.max_dwell_hours = current.max_dwell_hours.max(dwell);
+        }
+    }
+
+    for event in departed_trucks.read() {
+        current.trucks_departed += 1;
+        if event.late_packages > 0 {
+            current.late_departures += 1;
+        }
+    }
+}
+
+fn sample_utilization(
+    time: Res<Time>,
+    mut log: ResMut<ShiftMetricsLog>,
+    worker_query: Query<&Worker>,
+    robot_query: Query<&Robot>,
+    vehicle_query: Query<(&Vehicle, Option<&Occupied>)>,
+) {
+    let dt = time.delta_seconds();
+    let Some(current) = log.current_mut() else {
+        return;
+    };
+
+    let workers = worker_query
+        .iter()
+        .map(|worker| (Equipment::Workers, worker.job.is_some()));
+    let robots = robot_query.iter().map(|robot| {
+        let busy = !matches!(
+            robot.task,
+            RobotTask::Idle | RobotTask::GoingToCharge { .. } | RobotTask::Charging { .. }
+        );
+        (Equipment::Robots, busy)
+    });
+    // A vehicle is in use while someone is driving it.
+    let vehicles = vehicle_query
+        .iter()
+        .map(|(vehicle, occupied)| (Equipment::from_vehicle(vehicle.kind), occupied.is_some()));
+
+    for (equipment, busy) in workers.chain(robots).chain(vehicles) {
+        let utilization = &mut current.utilization[equipment.index()];
+        utilization.total_seconds += dt;
+        if busy {
+            utilization.busy_seconds += dt;
+        }
+    }
+}
+
+type PackageProgress<'a> = (
+    Entity,
+    Option<&'a InboundCargo>,
+    Option<&'a SortedTo>,
+    Option<&'a AddressException>,
+    Option<&'a CustomsStatus>,
+    Option<&'a Parent>,
+);
+
+fn sample_backlog(
+    time: Res<Time>,
+    mut log: ResMut<ShiftMetricsLog>,
+    package_query: Query<PackageProgress, With<Package>>,
+    truck_query: Query<&OutboundTruck>,
+) {
+    let dt = time.delta_seconds();
+    let Some(current) = log.current_mut() else {
+        return;
+    };
+
+    let mut waiting = [0usize; Stage::ALL.len()];
+    for (entity, inbound, sorted_to, exception, customs, parent) in package_query.iter() {
+        // Carried or consolidated packages are being worked on, not waiting.
+        if parent.is_some()
+            || truck_query
+                .iter()
+                .any(|truck| truck.manifest.contains(&entity))
+        {
+            continue;
+        }
+
+        let stage = if sorted_to.is_some() {
+            Stage::Loading
+        } else if inbound.is_some() {
+            Stage::Unloading
+        } else if is_blocked(exception, customs) {
+            Stage::Held
+        } else {
+            Stage::Sorting
+        };
+        waiting[stage.index()] += 1;
+    }
+
+    for (total, count) in current.backlog_package_seconds.iter_mut().zip(waiting) {
+        *total += count as f32 * dt;
+    }
+    current.sampled_seconds += dt;
+}
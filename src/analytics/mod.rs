@@ -0,0 +1,19 @@
+pub mod export;
+pub mod heatmap;
+pub mod metrics;
+
+use bevy::prelude::*;
+
+use self::export::ExportPlugin;
+use self::heatmap::HeatmapPlugin;
+use self::metrics::MetricsPlugin;
+
+pub struct AnalyticsPlugin;
+
+impl Plugin for AnalyticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MetricsPlugin)
+            .add_plugins(ExportPlugin)
+            .add_plugins(HeatmapPlugin);
+    }
+}
@@ -0,0 +1,15 @@
+pub mod robots;
+pub mod traffic;
+
+use bevy::prelude::*;
+
+use self::robots::RobotsPlugin;
+use self::traffic::TrafficPlugin;
+
+pub struct AutomationPlugin;
+
+impl Plugin for AutomationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RobotsPlugin).add_plugins(TrafficPlugin);
+    }
+}
@@ -0,0 +1,493 @@
+// Autonomous mobile robots. Once the fleet is unlocked, robots pick single packages up at
+// induction stations and drive them to the floor-level drop chutes picked by the sort plan. They
+// head for a charger on their own when the battery runs low. Moving around is left to the
+// traffic controller, which decides who gets to go where.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{
+    spawn_level_markers, ChargerLocation, ChuteInput, PlayerSpawnPoint, TriggerZone,
+};
+use crate::levels::package_data::Package;
+use crate::logistics::address::AddressException;
+use crate::logistics::customs::CustomsStatus;
+use crate::logistics::economy::Wallet;
+use crate::logistics::sorting::{destination_chute, is_drop_chute, SortedTo};
+use crate::navigation::pathfinding::NavAgent;
+use crate::player::items::scanner::PackageScanned;
+use crate::staff::jobs::{is_blocked, JobQueue};
+
+pub struct RobotsPlugin;
+
+impl Plugin for RobotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RobotFleet>()
+            .add_event::<UnlockRobotFleet>()
+            .add_event::<BuyRobot>()
+            .add_systems(
+                OnEnter(AssetLoaderState::Done),
+                setup_fleet_stations.after(spawn_level_markers),
+            )
+            .add_systems(
+                Update,
+                (
+                    unlock_robot_fleet,
+                    buy_robots,
+                    dispatch_robots,
+                    run_robot_tasks,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+pub const FLEET_UNLOCK_COST: f32 = 20_000.0;
+pub const ROBOT_COST: f32 = 3_500.0;
+
+// Robots only carry one parcel at a time, and only light ones.
+const MAX_PAYLOAD: f32 = 60.0;
+const HANDLING_SECONDS: f32 = 1.5;
+const REACH: f32 = 1.2;
+const CARRY_OFFSET: Vec3 = Vec3::new(0.0, 0.9, 0.0);
+
+// Head for a charger below this, and get back to work once full.
+pub const LOW_BATTERY: f32 = 0.2;
+const CHARGE_PER_SECOND: f32 = 0.05;
+
+// Used when the level has no chargers or induction stations to park new robots at.
+const ROBOT_SPAWN_POINT: Vec3 = Vec3::new(0.0, 0.5, 0.0);
+
+#[derive(Resource, Default, Debug)]
+pub struct RobotFleet {
+    pub unlocked: bool,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UnlockRobotFleet;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BuyRobot;
+
+// Trigger zones whose name contains "induction" become induction stations.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct InductionStation;
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Charger {
+    pub occupant: Option<Entity>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RobotTask {
+    Idle,
+    Fetching {
+        package: Entity,
+    },
+    Loading {
+        package: Entity,
+        remaining: f32,
+    },
+    Delivering {
+        package: Entity,
+        drop_at: Vec3,
+    },
+    Unloading {
+        package: Entity,
+        drop_at: Vec3,
+        remaining: f32,
+    },
+    // `None` charges wherever the robot was first parked, for levels without chargers.
+    GoingToCharge {
+        charger: Option<Entity>,
+    },
+    Charging {
+        charger: Option<Entity>,
+    },
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Robot {
+    pub battery: f32,
+    pub task: RobotTask,
+    pub home: Vec3,
+    // Set by the traffic controller.
+    pub waiting_on: Option<Entity>,
+    pub waited: f32,
+    pub siding: Option<(Vec3, f32)>,
+}
+
+impl Robot {
+    fn new(home: Vec3) -> Self {
+        Robot {
+            battery: 1.0,
+            task: RobotTask::Idle,
+            home,
+            waiting_on: None,
+            waited: 0.0,
+            siding: None,
+        }
+    }
+
+    pub fn carrying(&self) -> Option<Entity> {
+        match self.task {
+            RobotTask::Delivering { package, .. } | RobotTask::Unloading { package, .. } => {
+                Some(package)
+            }
+            _ => None,
+        }
+    }
+
+    // Where the robot wants to be right now, before traffic control gets a say.
+    pub fn heading(&self, positions: &Query<&GlobalTransform, Without<Robot>>) -> Option<Vec3> {
+        match self.task {
+            RobotTask::Idle => Some(self.home),
+            RobotTask::Fetching { package } => positions
+                .get(package)
+                .ok()
+                .map(|package| package.translation()),
+            RobotTask::Delivering { drop_at, .. } => Some(drop_at),
+            RobotTask::GoingToCharge { charger } => Some(
+                charger
+                    .and_then(|charger| positions.get(charger).ok())
+                    .map_or(self.home, |charger| charger.translation()),
+            ),
+            RobotTask::Loading { .. }
+            | RobotTask::Unloading { .. }
+            | RobotTask::Charging { .. } => None,
+        }
+    }
+}
+
+fn setup_fleet_stations(
+    mut commands: Commands,
+    zones: Query<(Entity, &TriggerZone)>,
+    chargers: Query<Entity, With<ChargerLocation>>,
+) {
+    for (entity, zone) in zones.iter() {
+        if zone.name.to_lowercase().contains("induction") {
+            commands.entity(entity).insert(InductionStation);
+        }
+    }
+    for entity in chargers.iter() {
+        commands.entity(entity).insert(Charger::default());
+    }
+}
+
+fn unlock_robot_fleet(
+    mut unlocks: EventReader<UnlockRobotFleet>,
+    mut fleet: ResMut<RobotFleet>,
+    mut wallet: ResMut<Wallet>,
+) {
+    for _ in unlocks.read() {
+        if fleet.unlocked {
+            continue;
+        }
+        if wallet.balance < FLEET_UNLOCK_COST {
+            info!("Unlocking the robot fleet costs ${:.0}", FLEET_UNLOCK_COST);
+            continue;
+        }
+
+        wallet.debit(FLEET_UNLOCK_COST);
+        fleet.unlocked = true;
+        info!("Robot fleet unlocked");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn buy_robots(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut purchases: EventReader<BuyRobot>,
+    fleet: Res<RobotFleet>,
+    mut wallet: ResMut<Wallet>,
+    robot_query: Query<&Robot>,
+    charger_query: Query<&GlobalTransform, With<Charger>>,
+    induction_query: Query<&GlobalTransform, With<InductionStation>>,
+    spawn_points: Query<&Transform, With<PlayerSpawnPoint>>,
+) {
+    let mut fleet_size = robot_query.iter().count();
+
+    for _ in purchases.read() {
+        if !fleet.unlocked || wallet.balance < ROBOT_COST {
+            continue;
+        }
+        wallet.debit(ROBOT_COST);
+
+        // New robots are dropped off at the chargers in turn.
+        let chargers: Vec<Vec3> = charger_query
+            .iter()
+            .map(GlobalTransform::translation)
+            .collect();
+        let home = if chargers.is_empty() {
+            induction_query
+                .iter()
+                .next()
+                .map(GlobalTransform::translation)
+                .or_else(|| spawn_points.iter().next().map(|spawn| spawn.translation))
+                .unwrap_or(ROBOT_SPAWN_POINT)
+        } else {
+            chargers[fleet_size % chargers.len()]
+        };
+        fleet_size += 1;
+
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Cuboid::new(0.8, 0.3, 0.8)),
+                material: materials.add(Color::rgb(0.1, 0.65, 0.6)),
+                transform: Transform::from_translation(home + Vec3::Y * 0.15),
+                ..default()
+            },
+            Name::new(format!("Robot {}", fleet_size)),
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(0.4, 0.15, 0.4),
+            KinematicCharacterController::default(),
+            NavAgent::default(),
+            Robot::new(home),
+        ));
+    }
+}
+
+// Packages a robot could take: sitting in an induction station, light enough, ready to sort.
+#[allow(clippy::type_complexity)]
+fn inducted_packages<'a>(
+    rapier_context: &RapierContext,
+    queue: &JobQueue,
+    station_query: &Query<Entity, With<InductionStation>>,
+    package_query: &'a Query<
+        (
+            Entity,
+            &Package,
+            &GlobalTransform,
+            Option<&SortedTo>,
+            Option<&AddressException>,
+            Option<&CustomsStatus>,
+        ),
+        Without<Parent>,
+    >,
+) -> Vec<(Entity, &'a Package, Vec3)> {
+    package_query
+        .iter()
+        .filter(|(entity, package, _, sorted_to, exception, customs)| {
+            sorted_to.is_none()
+                && package.weight <= MAX_PAYLOAD
+                && !is_blocked(*exception, *customs)
+                && !queue.is_claimed(*entity)
+                && station_query
+                    .iter()
+                    .any(|station| rapier_context.intersection_pair(station, *entity) == Some(true))
+        })
+        .map(|(entity, package, transform, ..)| (entity, package, transform.translation()))
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn dispatch_robots(
+    rapier_context: Res<RapierContext>,
+    mut queue: ResMut<JobQueue>,
+    mut robot_query: Query<(Entity, &mut Robot, &GlobalTransform)>,
+    mut charger_query: Query<(Entity, &mut Charger, &GlobalTransform)>,
+    station_query: Query<Entity, With<InductionStation>>,
+    package_query: Query<
+        (
+            Entity,
+            &Package,
+            &GlobalTransform,
+            Option<&SortedTo>,
+            Option<&AddressException>,
+            Option<&CustomsStatus>,
+        ),
+        Without<Parent>,
+    >,
+) {
+    let mut waiting = inducted_packages(&rapier_context, &queue, &station_query, &package_query);
+
+    for (robot_entity, mut robot, transform) in robot_query.iter_mut() {
+        if robot.task != RobotTask::Idle {
+            continue;
+        }
+        let position = transform.translation();
+
+        if robot.battery < LOW_BATTERY {
+            let charger = charger_query
+                .iter_mut()
+                .filter(|(_, charger, _)| charger.occupant.is_none())
+                .min_by(|(_, _, a), (_, _, b)| {
+                    let a = a.translation().distance(position);
+                    let b = b.translation().distance(position);
+                    a.total_cmp(&b)
+                });
+
+            let charger = charger.map(|(entity, mut charger, _)| {
+                charger.occupant = Some(robot_entity);
+                entity
+            });
+            if charger.is_none() && !charger_query.is_empty() {
+                // Every charger is taken; wait for one to free up.
+                continue;
+            }
+            robot.task = RobotTask::GoingToCharge { charger };
+            continue;
+        }
+
+        // The most urgent package first, and the closest of those.
+        let next = waiting
+            .iter()
+            .enumerate()
+            .min_by(|(_, (_, a, a_position)), (_, (_, b, b_position))| {
+                a.routing_order(b).then(
+                    a_position
+                        .distance(position)
+                        .total_cmp(&b_position.distance(position)),
+                )
+            })
+            .map(|(index, _)| index);
+        let Some(index) = next else {
+            continue;
+        };
+
+        let (package, ..) = waiting.swap_remove(index);
+        if queue.claim(package) {
+            robot.task = RobotTask::Fetching { package };
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_robot_tasks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut queue: ResMut<JobQueue>,
+    mut scans: EventWriter<PackageScanned>,
+    mut robot_query: Query<(Entity, &mut Robot, &GlobalTransform)>,
+    mut charger_query: Query<&mut Charger>,
+    positions: Query<&GlobalTransform, Without<Robot>>,
+    package_query: Query<&Package>,
+    chute_query: Query<(&ChuteInput, &GlobalTransform)>,
+) {
+    let dt = time.delta_seconds();
+
+    // Floor-level drop chutes only, falling back to every chute.
+    let mut chutes: Vec<(&str, Vec3)> = chute_query
+        .iter()
+        .filter(|(chute, _)| is_drop_chute(&chute.name))
+        .map(|(chute, transform)| (chute.name.as_str(), transform.translation()))
+        .collect();
+    if chutes.is_empty() {
+        chutes = chute_query
+            .iter()
+            .map(|(chute, transform)| (chute.name.as_str(), transform.translation()))
+            .collect();
+    }
+    chutes.sort_by(|a, b| a.0.cmp(b.0));
+    let chute_names: Vec<&str> = chutes.iter().map(|(name, _)| *name).collect();
+
+    for (robot_entity, mut robot, transform) in robot_query.iter_mut() {
+        let position = transform.translation();
+        let reached = |target: Vec3| target.xz().distance(position.xz()) < REACH;
+
+        match robot.task {
+            RobotTask::Idle => {}
+            RobotTask::Fetching { package } => {
+                let Ok(package_transform) = positions.get(package) else {
+                    queue.release(package);
+                    robot.task = RobotTask::Idle;
+                    continue;
+                };
+                if reached(package_transform.translation()) {
+                    robot.task = RobotTask::Loading {
+                        package,
+                        remaining: HANDLING_SECONDS,
+                    };
+                }
+            }
+            RobotTask::Loading { package, remaining } => {
+                if remaining > dt {
+                    robot.task = RobotTask::Loading {
+                        package,
+                        remaining: remaining - dt,
+                    };
+                    continue;
+                }
+
+                let chute = package_query
+                    .get(package)
+                    .ok()
+                    .and_then(|package| destination_chute(package, &chute_names))
+                    .and_then(|chute| chutes.iter().find(|(name, _)| *name == chute));
+                let Some((_, drop_at)) = chute else {
+                    queue.release(package);
+                    robot.task = RobotTask::Idle;
+                    continue;
+                };
+
+                commands
+                    .entity(package)
+                    .remove::<RigidBody>()
+                    .set_parent(robot_entity)
+                    .insert(Transform::from_translation(CARRY_OFFSET));
+                scans.send(PackageScanned { package });
+
+                robot.task = RobotTask::Delivering {
+                    package,
+                    drop_at: *drop_at,
+                };
+            }
+            RobotTask::Delivering { package, drop_at } => {
+                if reached(drop_at) {
+                    robot.task = RobotTask::Unloading {
+                        package,
+                        drop_at,
+                        remaining: HANDLING_SECONDS,
+                    };
+                }
+            }
+            RobotTask::Unloading {
+                package,
+                drop_at,
+                remaining,
+            } => {
+                if remaining > dt {
+                    robot.task = RobotTask::Unloading {
+                        package,
+                        drop_at,
+                        remaining: remaining - dt,
+                    };
+                    continue;
+                }
+
+                // Tipped straight into the chute.
+                commands.entity(package).remove_parent().insert((
+                    Transform::from_translation(drop_at),
+                    RigidBody::Dynamic,
+                    Velocity::zero(),
+                ));
+                queue.release(package);
+                robot.task = RobotTask::Idle;
+            }
+            RobotTask::GoingToCharge { charger } => {
+                let target = charger
+                    .and_then(|charger| positions.get(charger).ok())
+                    .map_or(robot.home, |charger| charger.translation());
+                if reached(target) {
+                    robot.task = RobotTask::Charging { charger };
+                }
+            }
+            RobotTask::Charging { charger } => {
+                robot.battery = (robot.battery + CHARGE_PER_SECOND * dt).min(1.0);
+                if robot.battery >= 1.0 {
+                    if let Some(mut charger) =
+                        charger.and_then(|charger| charger_query.get_mut(charger).ok())
+                    {
+                        charger.occupant = None;
+                    }
+                    robot.task = RobotTask::Idle;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// Traffic control for the robot fleet. Robots reserve the grid cells just ahead of them before
+// moving in, and stop when someone else holds one. Who's waiting on whom forms a graph; when it
+// loops back on itself (two robots nose to nose in an aisle, say), one of them pulls aside onto a
+// free cell off the others' paths until the way is clear. The same happens to a parked robot that
+// someone has been waiting on for too long.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_rapier3d::prelude::*;
+
+use crate::automation::robots::{Robot, RobotTask};
+use crate::game_state::GameState;
+use crate::navigation::grid::{NavGrid, CELL_SIZE};
+use crate::navigation::pathfinding::NavAgent;
+
+pub struct TrafficPlugin;
+
+impl Plugin for TrafficPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrafficReservations>().add_systems(
+            Update,
+            (drive_robots, resolve_deadlocks)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+const SPEED: f32 = 2.0;
+// Flat batteries still crawl, so a robot never gets stranded in an aisle.
+const EMPTY_BATTERY_SPEED: f32 = 0.5;
+const DRAIN_PER_METER: f32 = 0.004;
+const DRAIN_PER_SECOND: f32 = 0.0005;
+const GRAVITY: f32 = 9.81;
+
+// How far ahead robots reserve, and how much room they take up.
+const LOOKAHEAD: f32 = 1.5;
+const ROBOT_RADIUS: f32 = 0.45;
+// How long a parked robot can hold someone up before it's asked to move.
+const PARKED_WAIT_LIMIT: f32 = 3.0;
+const SIDING_RINGS: i32 = 4;
+const SIDING_SECONDS: f32 = 3.0;
+
+#[derive(Resource, Default, Debug)]
+pub struct TrafficReservations {
+    cells: HashMap<IVec2, Entity>,
+}
+
+impl TrafficReservations {
+    pub fn holder(&self, cell: IVec2) -> Option<Entity> {
+        self.cells.get(&cell).copied()
+    }
+
+    // Reserves all of `cells` for `robot`, or none of them. Hands back whoever is in the way.
+    pub fn try_reserve(&mut self, robot: Entity, cells: &[IVec2]) -> Result<(), Entity> {
+        if let Some(blocker) = cells
+            .iter()
+            .filter_map(|cell| self.holder(*cell))
+            .find(|holder| *holder != robot)
+        {
+            return Err(blocker);
+        }
+
+        for cell in cells {
+            self.cells.insert(*cell, robot);
+        }
+        Ok(())
+    }
+
+    // Lets go of everything `robot` holds apart from `keep`.
+    pub fn release_except(&mut self, robot: Entity, keep: &[IVec2]) {
+        self.cells
+            .retain(|cell, holder| *holder != robot || keep.contains(cell));
+    }
+
+    // Cells held by anyone but `robot`.
+    pub fn held_by_others(&self, robot: Entity) -> impl Iterator<Item = IVec2> + '_ {
+        self.cells
+            .iter()
+            .filter(move |(_, holder)| **holder != robot)
+            .map(|(cell, _)| *cell)
+    }
+}
+
+// Robots in a loop of waits, if there is one.
+pub fn find_wait_cycle(waits: &HashMap<Entity, Entity>) -> Option<Vec<Entity>> {
+    for start in waits.keys() {
+        let mut chain = vec![*start];
+        let mut current = *start;
+
+        while let Some(next) = waits.get(&current) {
+            if let Some(position) = chain.iter().position(|robot| robot == next) {
+                return Some(chain.split_off(position));
+            }
+            chain.push(*next);
+            current = *next;
+        }
+    }
+    None
+}
+
+// Cells a robot standing at `at` covers.
+fn footprint(grid: &NavGrid, at: Vec3) -> Vec<IVec2> {
+    grid.cells_in(at.xz() - ROBOT_RADIUS, at.xz() + ROBOT_RADIUS)
+        .collect()
+}
+
+// Cells covered on the way from `from` toward `toward`, up to the lookahead distance.
+fn cells_ahead(grid: &NavGrid, from: Vec3, toward: Vec3) -> Vec<IVec2> {
+    let offset = (toward - from) * Vec3::new(1.0, 0.0, 1.0);
+    let distance = offset.length().min(LOOKAHEAD);
+    let direction = offset.normalize_or_zero();
+    let samples = (distance / (CELL_SIZE * 0.5)).ceil() as i32;
+
+    let mut cells = Vec::new();
+    for sample in 0..=samples {
+        let along = (sample as f32 * CELL_SIZE * 0.5).min(distance);
+        for cell in footprint(grid, from + direction * along) {
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+// Closest free cell that's out of everyone else's way.
+fn find_siding(grid: &NavGrid, at: Vec3, busy: &HashSet<IVec2>) -> Option<Vec3> {
+    let center = grid.cell_at(at)?;
+
+    (1..=SIDING_RINGS).find_map(|ring| {
+        (-ring..=ring)
+            .flat_map(|x| (-ring..=ring).map(move |y| IVec2::new(x, y)))
+            .filter(|offset| offset.x.abs() == ring || offset.y.abs() == ring)
+            .map(|offset| center + offset)
+            .filter(|cell| grid.is_walkable(*cell))
+            .map(|cell| grid.cell_center(cell))
+            .find(|spot| {
+                footprint(grid, *spot)
+                    .iter()
+                    .all(|cell| !busy.contains(cell))
+            })
+    })
+}
+
+pub fn drive_robots(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut reservations: ResMut<TrafficReservations>,
+    mut robot_query: Query<(
+        Entity,
+        &mut Robot,
+        &mut NavAgent,
+        &mut Transform,
+        &mut KinematicCharacterController,
+    )>,
+    positions: Query<&GlobalTransform, Without<Robot>>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut robot, mut agent, mut transform, mut controller) in robot_query.iter_mut() {
+        let fall = -Vec3::Y * GRAVITY * dt;
+        robot.battery = (robot.battery - DRAIN_PER_SECOND * dt).max(0.0);
+
+        if let Some((siding, remaining)) = robot.siding {
+            robot.siding = (remaining > dt).then_some((siding, remaining - dt));
+        }
+        agent.destination = match robot.siding {
+            Some((siding, _)) => Some(siding),
+            None => robot.heading(&positions),
+        };
+
+        let here = footprint(&grid, transform.translation);
+        let Some(waypoint) = agent.next_waypoint() else {
+            robot.waiting_on = None;
+            reservations.release_except(entity, &here);
+            let _ = reservations.try_reserve(entity, &here);
+            controller.translation = Some(fall);
+            continue;
+        };
+
+        let offset = (waypoint - transform.translation) * Vec3::new(1.0, 0.0, 1.0);
+        let distance = offset.length();
+        if distance < 0.05 {
+            controller.translation = Some(fall);
+            continue;
+        }
+
+        let ahead = cells_ahead(&grid, transform.translation, waypoint);
+        if let Err(blocker) = reservations.try_reserve(entity, &ahead) {
+            // Hold on to where we're standing, and nothing else, while we wait.
+            reservations.release_except(entity, &here);
+            robot.waiting_on = Some(blocker);
+            robot.waited += dt;
+            controller.translation = Some(fall);
+            continue;
+        }
+        reservations.release_except(entity, &ahead);
+        robot.waiting_on = None;
+        robot.waited = 0.0;
+
+        let speed = if robot.battery > 0.0 {
+            SPEED
+        } else {
+            EMPTY_BATTERY_SPEED
+        };
+        let step = (speed * dt).min(distance);
+        let direction = offset / distance;
+
+        transform.look_to(direction, Vec3::Y);
+        controller.translation = Some(direction * step + fall);
+        robot.battery = (robot.battery - DRAIN_PER_METER * step).max(0.0);
+    }
+}
+
+pub fn resolve_deadlocks(
+    grid: Res<NavGrid>,
+    reservations: Res<TrafficReservations>,
+    mut robot_query: Query<(Entity, &mut Robot, &NavAgent, &GlobalTransform)>,
+) {
+    let waits: HashMap<Entity, Entity> = robot_query
+        .iter()
+        .filter_map(|(entity, robot, _, _)| robot.waiting_on.map(|blocker| (entity, blocker)))
+        .collect();
+
+    let mut yielders: Vec<Entity> = Vec::new();
+
+    // In a loop, an empty robot gives way before a loaded one, and newer robots before older.
+    if let Some(cycle) = find_wait_cycle(&waits) {
+        let yielder = cycle.iter().copied().max_by_key(|entity| {
+            let loaded = robot_query
+                .get(*entity)
+                .is_ok_and(|(_, robot, _, _)| robot.carrying().is_some());
+            (!loaded, *entity)
+        });
+        yielders.extend(yielder);
+    }
+
+    // A parked robot in the way moves over.
+    for (_, robot, _, _) in robot_query.iter() {
+        let Some(blocker) = robot.waiting_on else {
+            continue;
+        };
+        if robot.waited < PARKED_WAIT_LIMIT {
+            continue;
+        }
+        let parked = robot_query
+            .get(blocker)
+            .is_ok_and(|(_, blocker, agent, _)| {
+                agent.destination.is_none()
+                    || matches!(blocker.task, RobotTask::Idle | RobotTask::Charging { .. })
+            });
+        if parked && !yielders.contains(&blocker) {
+            yielders.push(blocker);
+        }
+    }
+
+    for yielder in yielders {
+        // Stay off every cell someone else holds or is about to drive through.
+        let mut busy: HashSet<IVec2> = robot_query
+            .iter()
+            .filter(|(entity, ..)| *entity != yielder)
+            .flat_map(|(_, _, agent, transform)| {
+                let mut cells = footprint(&grid, transform.translation());
+                let mut from = transform.translation();
+                for waypoint in agent.path.iter().take(3) {
+                    cells.extend(cells_ahead(&grid, from, *waypoint));
+                    from = *waypoint;
+                }
+                cells
+            })
+            .collect();
+        busy.extend(reservations.held_by_others(yielder));
+
+        let Ok((_, mut robot, _, transform)) = robot_query.get_mut(yielder) else {
+            continue;
+        };
+        if robot.siding.is_some() {
+            continue;
+        }
+        if let Some(siding) = find_siding(&grid, transform.translation(), &busy) {
+            robot.siding = Some((siding, SIDING_SECONDS));
+            robot.waited = 0.0;
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Build mode, toggled with the BuildMode action (B). While it's on, the warehouse can be changed
+// instead of worked in.
+
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+
+pub struct BuildModePlugin;
+
+impl Plugin for BuildModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>().add_systems(
+            Update,
+            toggle_build_mode.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct BuildMode {
+    pub active: bool,
+}
+
+// Run condition for the things the player does while working rather than building.
+pub fn not_building(build_mode: Res<BuildMode>) -> bool {
+    !build_mode.active
+}
+
+fn toggle_build_mode(actions: Res<ActionState>, mut build_mode: ResMut<BuildMode>) {
+    if !actions.just_pressed(Action::BuildMode) {
+        return;
+    }
+
+    build_mode.active = !build_mode.active;
+    if build_mode.active {
+        info!("Build mode on");
+    } else {
+        info!("Build mode off");
+    }
+}
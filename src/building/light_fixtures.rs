@@ -0,0 +1,140 @@
+// Light fixtures. In build mode, Throw hangs one wherever the crosshair meets the level (a
+// ceiling, a wall, a rack) and Interact takes down the one under the crosshair. Fixtures join the
+// light zone they're hung in, and cost money to buy and power to run.
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::building::build_mode::BuildMode;
+use crate::game_state::GameState;
+use crate::lighting::zones::WarehouseLight;
+use crate::logistics::economy::Wallet;
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct LightFixturesPlugin;
+
+impl Plugin for LightFixturesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            hang_or_take_down_fixture.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+pub const FIXTURE_COST: f32 = 40.0;
+// How far from the camera fixtures can be hung, further than the player can reach since they go
+// up on the ceiling.
+const HANG_RANGE: f32 = 8.0;
+// Lumens, about what a warehouse high bay puts out.
+const FIXTURE_INTENSITY: f32 = 20000.0;
+const FIXTURE_RANGE: f32 = 15.0;
+const FIXTURE_SIZE: Vec3 = Vec3::new(0.6, 0.1, 0.6);
+// Keeps the lamp clear of the surface it hangs from.
+const FIXTURE_OFFSET: f32 = 0.15;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LightFixture;
+
+#[allow(clippy::too_many_arguments)]
+fn hang_or_take_down_fixture(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    build_mode: Res<BuildMode>,
+    rapier_context: Res<RapierContext>,
+    camera_query: Query<&GlobalTransform, With<PlayerInteractionSystem>>,
+    fixture_query: Query<(), With<LightFixture>>,
+    mut wallet: ResMut<Wallet>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut fixture_assets: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    let hang = actions.just_pressed(Action::Throw);
+    let take_down = actions.just_pressed(Action::Interact);
+    if !build_mode.active || (!hang && !take_down) {
+        return;
+    }
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+
+    // Packages, vehicles and the player themselves are all dynamic, so only the level and the
+    // fixtures already up are hit.
+    let filter = QueryFilter::new().exclude_sensors().exclude_dynamic();
+    let Some((entity, hit)) = rapier_context.cast_ray_and_get_normal(
+        camera.translation(),
+        camera.forward(),
+        HANG_RANGE,
+        true,
+        filter,
+    ) else {
+        return;
+    };
+
+    let on_fixture = fixture_query.contains(entity);
+    if take_down {
+        if on_fixture {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    if on_fixture {
+        return;
+    }
+    if wallet.balance < FIXTURE_COST {
+        info!("A light fixture costs ${:.0}", FIXTURE_COST);
+        return;
+    }
+
+    let (mesh, material) = fixture_assets
+        .get_or_insert_with(|| {
+            (
+                meshes.add(Cuboid::from_size(FIXTURE_SIZE)),
+                materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    emissive: Color::rgb(1.0, 0.95, 0.85),
+                    ..default()
+                }),
+            )
+        })
+        .clone();
+
+    // The lamp's underside faces away from whatever it hangs from.
+    let rotation = Quat::from_rotation_arc(Vec3::NEG_Y, hit.normal);
+    commands
+        .spawn((
+            PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_translation(hit.point + hit.normal * FIXTURE_OFFSET)
+                    .with_rotation(rotation),
+                ..default()
+            },
+            NotShadowCaster,
+            Collider::cuboid(
+                FIXTURE_SIZE.x / 2.0,
+                FIXTURE_SIZE.y / 2.0,
+                FIXTURE_SIZE.z / 2.0,
+            ),
+            LightFixture,
+            Name::new("Light fixture"),
+        ))
+        .with_children(|fixture| {
+            fixture.spawn((
+                PointLightBundle {
+                    point_light: PointLight {
+                        intensity: FIXTURE_INTENSITY,
+                        range: FIXTURE_RANGE,
+                        shadows_enabled: true,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, -FIXTURE_SIZE.y, 0.0),
+                    ..default()
+                },
+                WarehouseLight::new(FIXTURE_INTENSITY),
+            ));
+        });
+    wallet.debit(FIXTURE_COST);
+}
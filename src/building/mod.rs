@@ -0,0 +1,16 @@
+pub mod build_mode;
+pub mod light_fixtures;
+
+use bevy::prelude::*;
+
+use self::build_mode::BuildModePlugin;
+use self::light_fixtures::LightFixturesPlugin;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(BuildModePlugin)
+            .add_plugins(LightFixturesPlugin);
+    }
+}
@@ -0,0 +1,57 @@
+// Loading and saving the TOML config files. Files only need the values that differ from the
+// defaults; a file that doesn't parse is reported and left alone. Values that parse but are out
+// of range are checked by whoever loads them.
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+use std::fs;
+use std::time::SystemTime;
+
+// `None` when the file doesn't exist yet, which is normal on a first run, or doesn't parse.
+pub fn load_config<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let contents = fs::read_to_string(path).ok()?;
+    match toml::from_str(&contents) {
+        Ok(config) => Some(config),
+        Err(error) => {
+            warn!("Couldn't read {}: {}", path, error);
+            None
+        }
+    }
+}
+
+pub fn save_config<T: Serialize>(path: &str, config: &T) {
+    let contents = match toml::to_string(config) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("Couldn't save {}: {}", path, error);
+            return;
+        }
+    };
+    match fs::write(path, contents) {
+        Ok(()) => info!("Saved {}", path),
+        Err(error) => warn!("Couldn't save {}: {}", path, error),
+    }
+}
+
+pub fn modified_at(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// Puts `value` back to `default` if it's outside `min..=max`, saying which key it was.
+pub fn check_range(path: &str, key: &str, value: &mut f32, min: f32, max: f32, default: f32) {
+    if !(min..=max).contains(value) {
+        warn!(
+            "{}: {} = {} is outside {}..={}, using {}",
+            path, key, value, min, max, default
+        );
+        *value = default;
+    }
+}
+
+// For values the menus step by tenths, which would otherwise be saved as 0.699999988079071.
+pub fn serialize_rounded<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64((*value as f64 * 100.0).round() / 100.0)
+}
@@ -0,0 +1,107 @@
+// Where the game is as a whole. It starts at the main menu, loads the warehouse once a game is
+// started and drops into it. The Pause action (Escape) pauses: virtual time stops (and with it
+// the shift clock and everything else that ticks on `Time`), physics stops, and gameplay systems
+// gated on `InGame` don't run until the game is resumed.
+
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_fps_controller::controller::FpsController;
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+use crate::actions::{Action, ActionState};
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .add_systems(OnEnter(GameState::Loading), start_loading)
+            .add_systems(OnEnter(AssetLoaderState::Done), finish_loading)
+            .add_systems(OnEnter(GameState::InGame), grab_cursor)
+            .add_systems(OnEnter(GameState::Paused), pause)
+            .add_systems(OnExit(GameState::Paused), resume)
+            .add_systems(Update, pause_on_request.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(States, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Loading,
+    InGame,
+    Paused,
+}
+
+// Controllers that had input when the game was paused, so resuming doesn't hand the player back
+// control while they're sitting in a vehicle or typing in the tracking panel.
+#[derive(Component)]
+struct InputSuspended;
+
+fn start_loading(mut next_state: ResMut<NextState<AssetLoaderState>>) {
+    next_state.set(AssetLoaderState::Loading);
+}
+
+fn finish_loading(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::InGame);
+}
+
+pub fn set_cursor_grabbed(window: &mut Window, grabbed: bool) {
+    if grabbed {
+        window.cursor.grab_mode = CursorGrabMode::Locked;
+        window.cursor.visible = false;
+    } else {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+fn grab_cursor(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = window_query.get_single_mut() {
+        set_cursor_grabbed(&mut window, true);
+    }
+}
+
+fn pause_on_request(actions: Res<ActionState>, mut next_state: ResMut<NextState<GameState>>) {
+    if actions.just_pressed(Action::Pause) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn pause(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<RapierConfiguration>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut controller_query: Query<(Entity, &mut FpsController)>,
+) {
+    time.pause();
+    physics.physics_pipeline_active = false;
+
+    if let Ok(mut window) = window_query.get_single_mut() {
+        set_cursor_grabbed(&mut window, false);
+    }
+    for (entity, mut controller) in controller_query.iter_mut() {
+        if controller.enable_input {
+            controller.enable_input = false;
+            commands.entity(entity).insert(InputSuspended);
+        }
+    }
+    info!("Paused");
+}
+
+fn resume(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<RapierConfiguration>,
+    mut controller_query: Query<(Entity, &mut FpsController), With<InputSuspended>>,
+) {
+    time.unpause();
+    physics.physics_pipeline_active = true;
+
+    for (entity, mut controller) in controller_query.iter_mut() {
+        controller.enable_input = true;
+        commands.entity(entity).remove::<InputSuspended>();
+    }
+}
@@ -5,8 +5,17 @@ use bevy::gltf::Gltf;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_rapier3d::prelude::*;
+use rand::seq::IteratorRandom;
+use rand::thread_rng;
 
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::markers::{LevelMarkersPlugin, PackageSpawnPoint};
 use crate::levels::package_data::Package;
+use crate::logistics::address::{AddressOnFile, LabelDefect};
+use crate::logistics::customs::{CustomsDeclaration, CustomsStatus};
+use crate::logistics::shift::ShiftClock;
+use crate::staff::jobs::InboundCargo;
 use crate::tools::gltf::GltfToolsPlugin;
 
 pub struct AssetLoaderPlugin;
@@ -19,17 +28,20 @@ impl Plugin for AssetLoaderPlugin {
                     .load_collection::<MyAssetPack>(),
             )
             .add_systems(OnEnter(AssetLoaderState::Done), load_scene)
-            .add_systems(Update, spawn_box.run_if(in_state(AssetLoaderState::Done)))
-            .add_plugins(GltfToolsPlugin);
+            .add_systems(Update, spawn_box.run_if(in_state(GameState::InGame)))
+            .add_plugins(GltfToolsPlugin)
+            .add_plugins(LevelMarkersPlugin);
     }
 }
 
 // Deriving an enum that will track whether the GLTF is loaded.
-// The file must be loaded before we can continue.
+// The file must be loaded before we can continue. Nothing is loaded until a game is started from
+// the main menu (see `GameState`).
 
 #[derive(Default, Clone, Eq, PartialEq, Hash, States, Debug)]
 pub enum AssetLoaderState {
     #[default]
+    Idle,
     Loading,
     Done,
 }
@@ -66,32 +78,61 @@ fn load_scene(
             ..default()
         });
     }
-
-    commands.spawn(AudioBundle {
-        source: asset_pack.ambience_sound.clone(),
-        settings: PlaybackSettings::LOOP,
-    });
 }
 
+// Used when the level has no package spawn markers.
+const PACKAGE_SPAWN_POINT: Vec3 = Vec3::new(0.0, 2.5, 0.0);
+
 fn spawn_box(
     mut commands: Commands,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     asset_pack: Res<MyAssetPack>,
     assets_gltf: Res<Assets<Gltf>>,
+    spawn_points: Query<&Transform, With<PackageSpawnPoint>>,
+    clock: Res<ShiftClock>,
 ) {
     if let Some(gltf) = assets_gltf.get(&asset_pack.package) {
-        if input.pressed(KeyCode::KeyG) {
-            commands.spawn((
+        if actions.just_pressed(Action::SpawnPackage) {
+            let spawn_point = spawn_points
+                .iter()
+                .choose(&mut thread_rng())
+                .map_or(PACKAGE_SPAWN_POINT, |transform| transform.translation);
+
+            let mut package = Package::new(clock.now());
+            let international = package.is_international();
+
+            // Keep the real address before the label gets damaged, unless the recipient is
+            // someone the customer file has never heard of.
+            let label_defect = LabelDefect::roll();
+            let address_on_file = match label_defect {
+                Some(LabelDefect::UnknownRecipient) => None,
+                _ => Some(AddressOnFile::from_package(&package)),
+            };
+            if let Some(defect) = label_defect {
+                defect.apply(&mut package);
+            }
+
+            let mut package_entity = commands.spawn((
                 SceneBundle {
                     scene: gltf.named_scenes["Scene"].clone(),
-                    transform: Transform::from_xyz(0., 2.5, 0.),
+                    transform: Transform::from_translation(spawn_point),
                     ..default()
                 },
                 Collider::cuboid(0.7, 0.7, 0.7),
                 Friction::coefficient(1.2),
                 RigidBody::Dynamic,
-                Package::new(),
+                package,
+                InboundCargo {
+                    docked_at: spawn_point,
+                },
             ));
+
+            if international {
+                package_entity.insert((CustomsDeclaration::random(), CustomsStatus::Pending));
+            }
+            if let Some(address_on_file) = address_on_file {
+                package_entity.insert(address_on_file);
+            }
         }
     }
 }
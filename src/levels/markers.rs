@@ -0,0 +1,205 @@
+// Level designers mark gameplay locations by placing empties in Blender. An empty is picked up
+// either by its name (e.g. "SpawnPoint", "Dock.001", "Trigger_Inspection") or by a `marker`
+// custom property, which Blender exports as glTF extras: { "marker": "dock" }.
+
+use bevy::gltf::{Gltf, GltfNode};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::levels::asset_loader_plugin::{AssetLoaderState, MyAssetPack};
+
+pub struct LevelMarkersPlugin;
+
+impl Plugin for LevelMarkersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AssetLoaderState::Done), spawn_level_markers);
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PlayerSpawnPoint;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PackageSpawnPoint;
+
+#[derive(Component, Clone, Debug)]
+pub struct DockLocation {
+    pub name: String,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct ChuteInput {
+    pub name: String,
+}
+
+// Where a container is placed at load. The kind comes from the name, e.g. "Pallet.002".
+#[derive(Component, Clone, Debug)]
+pub struct ContainerSpawnPoint {
+    pub name: String,
+}
+
+// Where a material handling vehicle is parked at load, e.g. "Forklift.001".
+#[derive(Component, Clone, Debug)]
+pub struct VehicleSpawnPoint {
+    pub name: String,
+}
+
+// Where a fleet robot docks to charge.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ChargerLocation;
+
+// Trigger zones are sensors scaled by the empty's transform, so a cube empty drawn in Blender
+// covers exactly the volume it shows in the viewport.
+#[derive(Component, Clone, Debug)]
+pub struct TriggerZone {
+    pub name: String,
+}
+
+// The lights inside a light zone are switched together. Like trigger zones, a cube empty covers
+// the volume it shows in the viewport.
+#[derive(Component, Clone, Debug)]
+pub struct LightZone {
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerKind {
+    PlayerSpawn,
+    PackageSpawn,
+    Dock,
+    ChuteInput,
+    TriggerZone,
+    LightZone,
+    ContainerSpawn,
+    VehicleSpawn,
+    Charger,
+}
+
+// Checked in order against the lowercased tag with separators removed, so "Spawn_Point",
+// "spawn-point" and "SpawnPoint.002" all match. Longer prefixes go first where one starts with
+// another ("palletjack" before "pallet").
+const MARKER_PREFIXES: [(&str, MarkerKind); 16] = [
+    ("playerspawn", MarkerKind::PlayerSpawn),
+    ("spawnpoint", MarkerKind::PlayerSpawn),
+    ("packagespawn", MarkerKind::PackageSpawn),
+    ("boxspawn", MarkerKind::PackageSpawn),
+    ("dock", MarkerKind::Dock),
+    ("chute", MarkerKind::ChuteInput),
+    ("trigger", MarkerKind::TriggerZone),
+    ("lightzone", MarkerKind::LightZone),
+    ("palletjack", MarkerKind::VehicleSpawn),
+    ("rollcage", MarkerKind::ContainerSpawn),
+    ("gaylord", MarkerKind::ContainerSpawn),
+    ("pallet", MarkerKind::ContainerSpawn),
+    ("mailbag", MarkerKind::ContainerSpawn),
+    ("forklift", MarkerKind::VehicleSpawn),
+    ("tugger", MarkerKind::VehicleSpawn),
+    ("charger", MarkerKind::Charger),
+];
+
+// Lowercases a node name or tag and strips separators.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+impl MarkerKind {
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let normalized = normalize_tag(tag);
+
+        MARKER_PREFIXES
+            .iter()
+            .find(|(prefix, _)| normalized.starts_with(prefix))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+// Whether `entity` currently overlaps any of the given trigger zones.
+pub fn is_inside_any(
+    rapier_context: &RapierContext,
+    mut zones: impl Iterator<Item = Entity>,
+    entity: Entity,
+) -> bool {
+    zones.any(|zone| rapier_context.intersection_pair(zone, entity) == Some(true))
+}
+
+// Pulls the value of the `marker` key out of the extras JSON. The extras are a flat object of
+// custom properties, so a full JSON parser isn't needed here.
+fn marker_property(extras: &str) -> Option<&str> {
+    let (_, rest) = extras.split_once("\"marker\"")?;
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    rest.strip_prefix('"')?.split('"').next()
+}
+
+pub fn spawn_level_markers(
+    mut commands: Commands,
+    asset_pack: Res<MyAssetPack>,
+    gltf_assets: Res<Assets<Gltf>>,
+    gltf_node_assets: Res<Assets<GltfNode>>,
+) {
+    let Some(gltf) = gltf_assets.get(&asset_pack.main_scene) else {
+        return;
+    };
+
+    for (name, node) in &gltf.named_nodes {
+        let Some(node) = gltf_node_assets.get(node) else {
+            continue;
+        };
+        // Only empties are markers. A mesh that happens to be called "Dock..." is still scenery.
+        if node.mesh.is_some() {
+            continue;
+        }
+
+        let tagged_kind = node
+            .extras
+            .as_ref()
+            .and_then(|extras| marker_property(&extras.value))
+            .and_then(MarkerKind::from_tag);
+
+        let Some(kind) = tagged_kind.or_else(|| MarkerKind::from_tag(name)) else {
+            continue;
+        };
+
+        let mut marker = commands.spawn((
+            Name::new(name.clone()),
+            TransformBundle::from_transform(node.transform),
+        ));
+
+        match kind {
+            MarkerKind::PlayerSpawn => {
+                marker.insert(PlayerSpawnPoint);
+            }
+            MarkerKind::PackageSpawn => {
+                marker.insert(PackageSpawnPoint);
+            }
+            MarkerKind::Dock => {
+                marker.insert(DockLocation { name: name.clone() });
+            }
+            MarkerKind::ChuteInput => {
+                marker.insert(ChuteInput { name: name.clone() });
+            }
+            MarkerKind::ContainerSpawn => {
+                marker.insert(ContainerSpawnPoint { name: name.clone() });
+            }
+            MarkerKind::VehicleSpawn => {
+                marker.insert(VehicleSpawnPoint { name: name.clone() });
+            }
+            MarkerKind::Charger => {
+                marker.insert(ChargerLocation);
+            }
+            MarkerKind::LightZone => {
+                marker.insert(LightZone { name: name.clone() });
+            }
+            MarkerKind::TriggerZone => {
+                marker.insert((
+                    TriggerZone { name: name.clone() },
+                    Collider::cuboid(1.0, 1.0, 1.0),
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                ));
+            }
+        }
+    }
+}
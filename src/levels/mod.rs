@@ -1,2 +1,3 @@
 pub mod asset_loader_plugin;
+pub mod markers;
 pub mod package_data;
@@ -1,36 +1,210 @@
 use bevy::prelude::*;
+use rand::seq::IteratorRandom;
 use rand::{thread_rng, Rng};
+use std::cmp::Ordering;
+
+use crate::logistics::address::PostalFormat;
 // Package components to be defined here.
 
+// Packages addressed anywhere else are international and go through international services.
+pub const HOME_COUNTRY: &str = "United States of America";
+// Pounds.
+pub const MAX_PACKAGE_WEIGHT: f32 = 150.0;
+// Share of intake addressed inside the home country.
+const DOMESTIC_SHARE: f64 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ServiceLevel {
+    Overnight,
+    TwoDay,
+    Ground,
+    InternationalEconomy,
+}
+
+impl ServiceLevel {
+    pub fn random(international: bool) -> Self {
+        if international {
+            return ServiceLevel::InternationalEconomy;
+        }
+
+        match thread_rng().gen_range(0..10) {
+            0..=1 => ServiceLevel::Overnight,
+            2..=4 => ServiceLevel::TwoDay,
+            _ => ServiceLevel::Ground,
+        }
+    }
+
+    pub fn transit_days(&self) -> u32 {
+        match self {
+            ServiceLevel::Overnight => 1,
+            ServiceLevel::TwoDay => 2,
+            ServiceLevel::Ground => 5,
+            ServiceLevel::InternationalEconomy => 10,
+        }
+    }
+
+    // Lower values are routed and sorted first.
+    pub fn priority(&self) -> u8 {
+        match self {
+            ServiceLevel::Overnight => 0,
+            ServiceLevel::TwoDay => 1,
+            ServiceLevel::Ground => 2,
+            ServiceLevel::InternationalEconomy => 3,
+        }
+    }
+
+    // Hours after the start of the intake day by which the package has to be sorted to make its
+    // outbound linehaul.
+    pub fn sort_cutoff_hours(&self) -> f32 {
+        match self {
+            ServiceLevel::Overnight => 18.0,
+            ServiceLevel::TwoDay => 22.0,
+            ServiceLevel::Ground => 30.0,
+            ServiceLevel::InternationalEconomy => 42.0,
+        }
+    }
+
+    pub fn postage(&self, weight: f32) -> f32 {
+        let (base, per_pound) = match self {
+            ServiceLevel::Overnight => (25.0, 1.5),
+            ServiceLevel::TwoDay => (12.0, 0.9),
+            ServiceLevel::Ground => (6.0, 0.4),
+            ServiceLevel::InternationalEconomy => (30.0, 1.2),
+        };
+
+        base + per_pound * weight
+    }
+
+    // Only the express services carry a money-back guarantee.
+    pub fn is_guaranteed(&self) -> bool {
+        matches!(self, ServiceLevel::Overnight | ServiceLevel::TwoDay)
+    }
+}
+
 #[derive(Component, Clone, PartialEq, Debug)]
 pub struct Package {
+    pub tracking_id: String,
     pub recipient_name: String,
     pub street_address: String,
     pub country: String,
-    pub zip_code: u32,
+    pub zip_code: Option<u32>,
     pub weight: f32,
     pub hazmat: bool,
+    pub service_level: ServiceLevel,
+    pub promised_delivery_day: u32,
+    pub postage: f32,
 }
 
 impl Package {
-    pub fn new() -> Self {
+    // `now` comes from the shift clock (see `ShiftClock::now`) and anchors the promised delivery
+    // date.
+    pub fn new(now: f32) -> Self {
         let rand_num_name = thread_rng().gen_range(0..NAMES.len());
         let rand_num_street = thread_rng().gen_range(0..STREET_NAMES.len());
-        let rand_num_countries = thread_rng().gen_range(0..COUNTRIES.len());
-        let rand_num_zips = thread_rng().gen_range(0..ZIP_CODES.len());
-        let rand_weight: f32 = thread_rng().gen_range(0.0..150.0);
+        let rand_weight: f32 = thread_rng().gen_range(0.0..MAX_PACKAGE_WEIGHT);
+
+        // Most mail is domestic. Picking a country straight from the list would make nearly all
+        // of it international.
+        let country = if thread_rng().gen_bool(DOMESTIC_SHARE) {
+            HOME_COUNTRY
+        } else {
+            COUNTRIES
+                .iter()
+                .copied()
+                .filter(|country| *country != HOME_COUNTRY)
+                .choose(&mut thread_rng())
+                .unwrap_or(HOME_COUNTRY)
+        };
+        let service_level = ServiceLevel::random(country != HOME_COUNTRY);
+
+        // Domestic mail goes to the zones this facility serves; everything else just needs a
+        // code in the destination country's format.
+        let zip_code = if country == HOME_COUNTRY {
+            Some(ZIP_CODES[thread_rng().gen_range(0..ZIP_CODES.len())])
+        } else {
+            PostalFormat::for_country(country).random_code()
+        };
+
+        // Packages accepted after their service's cutoff count as the next day's intake, so an
+        // overnight package taken in the evening still gets a night on the linehaul.
+        let day = (now / 24.0).floor();
+        let mut intake_day = day as u32;
+        if now - day * 24.0 > service_level.sort_cutoff_hours() {
+            intake_day += 1;
+        }
 
         Package {
+            tracking_id: format!("{:012}", thread_rng().gen_range(0..1_000_000_000_000u64)),
             recipient_name: NAMES[rand_num_name].to_string(),
             street_address: STREET_NAMES[rand_num_street].to_string(),
-            country: COUNTRIES[rand_num_countries].to_string(),
-            zip_code: ZIP_CODES[rand_num_zips],
+            country: country.to_string(),
+            zip_code,
             weight: rand_weight,
             hazmat: false,
+            service_level,
+            promised_delivery_day: intake_day + service_level.transit_days(),
+            postage: service_level.postage(rand_weight),
+        }
+    }
+
+    // Relabeling keeps the intake day; the promised delivery day and the postage follow the new
+    // service.
+    pub fn set_service_level(&mut self, service_level: ServiceLevel) {
+        let intake_day = self
+            .promised_delivery_day
+            .saturating_sub(self.service_level.transit_days());
+
+        self.service_level = service_level;
+        self.promised_delivery_day = intake_day + service_level.transit_days();
+        self.postage = service_level.postage(self.weight);
+    }
+
+    pub fn is_international(&self) -> bool {
+        self.country != HOME_COUNTRY
+    }
+
+    // Absolute hours (see `ShiftClock::now`) by which the package must leave the sort.
+    pub fn sort_deadline(&self) -> f32 {
+        let intake_day = self
+            .promised_delivery_day
+            .saturating_sub(self.service_level.transit_days());
+
+        intake_day as f32 * 24.0 + self.service_level.sort_cutoff_hours()
+    }
+
+    // Day the package reaches the recipient if it leaves the facility at `departed_at`. Every day
+    // past its sort deadline pushes delivery back by a day.
+    pub fn estimated_delivery_day(&self, departed_at: f32) -> u32 {
+        let hours_late = departed_at - self.sort_deadline();
+        if hours_late <= 0.0 {
+            self.promised_delivery_day
+        } else {
+            self.promised_delivery_day + (hours_late / 24.0).ceil() as u32
+        }
+    }
+
+    // Orders packages for routing: higher service levels first, then the tightest deadline.
+    pub fn routing_order(&self, other: &Package) -> Ordering {
+        self.service_level
+            .priority()
+            .cmp(&other.service_level.priority())
+            .then(self.sort_deadline().total_cmp(&other.sort_deadline()))
+    }
+
+    // Amount owed back to the sender when the package arrives on `delivered_day`.
+    pub fn late_refund(&self, delivered_day: u32) -> f32 {
+        if delivered_day > self.promised_delivery_day && self.service_level.is_guaranteed() {
+            self.postage
+        } else {
+            0.0
         }
     }
 }
 
+// Set when a package gets dropped or crushed in handling.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Damaged;
+
 pub const NAMES: [&str; 104] = [
     "Alden Mireles",
     "Briar Wainwright",
@@ -1,4 +1,18 @@
+pub mod actions;
+pub mod analytics;
+pub mod automation;
+pub mod building;
+pub mod config;
+pub mod game_state;
 pub mod levels;
+pub mod lighting;
+pub mod logistics;
+pub mod navigation;
 pub mod player;
 pub mod raycasting;
+pub mod settings;
+pub mod sound;
+pub mod staff;
 pub mod tools;
+pub mod ui;
+pub mod vehicles;
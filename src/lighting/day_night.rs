@@ -0,0 +1,90 @@
+// The day/night cycle. The shift clock moves the sun across the sky, so a shift that starts at
+// 06:00 starts at dawn and one that runs late finishes in the dark. With less daylight the sun
+// dims, the ambient light and the sky darken, and the warehouse's own lights have to do the work.
+
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+use crate::lighting::sun::Sun;
+use crate::logistics::shift::ShiftClock;
+use crate::settings::Settings;
+
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Daylight>()
+            .insert_resource(ClearColor(Color::rgb_from_array(DAY_SKY)))
+            .add_systems(Update, (move_sun, apply_daylight).chain());
+    }
+}
+
+const SUNRISE_HOUR: f32 = 6.0;
+const SUNSET_HOUR: f32 = 18.0;
+// How far the sun leans south (+Z) of straight overhead at noon, in radians.
+const NOON_TILT: f32 = 0.6;
+// Daylight is at full strength once the sun is this high, as the sine of its elevation.
+const FULL_DAYLIGHT_HEIGHT: f32 = 0.25;
+// Below this much daylight the warehouse counts as dark.
+const DARK_DAYLIGHT: f32 = 0.2;
+// The share of the ambient light left in the middle of the night.
+const NIGHT_AMBIENT: f32 = 0.1;
+// The background when the sky isn't drawn (see the `atmosphere` graphics setting).
+const DAY_SKY: Vec3 = Vec3::new(0.83, 0.96, 0.96);
+const NIGHT_SKY: Vec3 = Vec3::new(0.02, 0.03, 0.06);
+
+// How much daylight there is, from 0 at night to 1 once the sun is well up.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Daylight(pub f32);
+
+impl Default for Daylight {
+    fn default() -> Self {
+        Daylight(1.0)
+    }
+}
+
+impl Daylight {
+    pub fn is_dark(&self) -> bool {
+        self.0 < DARK_DAYLIGHT
+    }
+}
+
+// Towards the sun at `hour`: rising in the east (+X), highest at noon, setting in the west and
+// under the floor all night.
+pub fn sun_direction(hour: f32) -> Vec3 {
+    let angle = (hour - SUNRISE_HOUR) / (SUNSET_HOUR - SUNRISE_HOUR) * PI;
+    Quat::from_rotation_x(NOON_TILT) * Vec3::new(angle.cos(), angle.sin(), 0.0)
+}
+
+fn move_sun(
+    clock: Res<ShiftClock>,
+    mut daylight: ResMut<Daylight>,
+    mut sun_query: Query<&mut Transform, With<Sun>>,
+) {
+    let to_sun = sun_direction(clock.hour);
+    for mut transform in sun_query.iter_mut() {
+        *transform = Transform::default().looking_to(-to_sun, Vec3::Y);
+    }
+
+    daylight.set_if_neq(Daylight((to_sun.y / FULL_DAYLIGHT_HEIGHT).clamp(0.0, 1.0)));
+}
+
+fn apply_daylight(
+    daylight: Res<Daylight>,
+    settings: Res<Settings>,
+    mut sun_query: Query<(&Sun, &mut DirectionalLight)>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let daylight = daylight.0;
+
+    // No point drawing shadows for a sun that's down.
+    for (sun, mut light) in sun_query.iter_mut() {
+        light.illuminance = sun.illuminance * daylight;
+        light.shadows_enabled = daylight > 0.0;
+    }
+
+    let ambient_share = NIGHT_AMBIENT + (1.0 - NIGHT_AMBIENT) * daylight;
+    ambient_light.brightness = settings.graphics.ambient_brightness * ambient_share;
+    clear_color.0 = Color::rgb_from_array(NIGHT_SKY.lerp(DAY_SKY, daylight));
+}
@@ -0,0 +1,96 @@
+// Lights placed in Blender are exported with the KHR_lights_punctual extension, and Bevy spawns
+// them along with the level scene. They're picked up here so they can be switched by zone and
+// paid for like the fixtures hung in build mode.
+
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+
+use crate::levels::asset_loader_plugin::{AssetLoaderState, MyAssetPack};
+use crate::lighting::zones::WarehouseLight;
+use crate::settings::graphics::apply_light_settings;
+
+pub struct LevelLightsPlugin;
+
+impl Plugin for LevelLightsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AssetLoaderState::Done), spawn_fallback_light)
+            .add_systems(Update, register_level_lights.before(apply_light_settings));
+    }
+}
+
+// The light every level had before levels could bring their own.
+const FALLBACK_LIGHT_POSITION: Vec3 = Vec3::new(0.0, 2.5, 0.0);
+const FALLBACK_LIGHT_INTENSITY: f32 = 60000.0;
+
+type NewLevelLight<'a> = (Entity, Option<&'a mut PointLight>, Option<&'a SpotLight>);
+
+// Blender's exporter doesn't say whether a light casts shadows, so level point lights do unless
+// the graphics settings turn them off.
+#[allow(clippy::type_complexity)]
+fn register_level_lights(
+    mut commands: Commands,
+    mut light_query: Query<
+        NewLevelLight,
+        (
+            Or<(Added<PointLight>, Added<SpotLight>)>,
+            Without<WarehouseLight>,
+        ),
+    >,
+) {
+    for (entity, point, spot) in light_query.iter_mut() {
+        let intensity = match (point, spot) {
+            (Some(mut point), _) => {
+                point.shadows_enabled = true;
+                point.intensity
+            }
+            (None, Some(spot)) => spot.intensity,
+            (None, None) => continue,
+        };
+        commands
+            .entity(entity)
+            .insert(WarehouseLight::new(intensity));
+    }
+}
+
+fn level_has_lights(gltf: &Gltf, scenes: &Assets<Scene>) -> bool {
+    let Some(scene) = gltf
+        .named_scenes
+        .get("Scene")
+        .and_then(|scene| scenes.get(scene))
+    else {
+        return false;
+    };
+
+    scene
+        .world
+        .iter_entities()
+        .any(|entity| entity.contains::<PointLight>() || entity.contains::<SpotLight>())
+}
+
+fn spawn_fallback_light(
+    mut commands: Commands,
+    asset_pack: Res<MyAssetPack>,
+    gltf_assets: Res<Assets<Gltf>>,
+    scenes: Res<Assets<Scene>>,
+) {
+    let Some(gltf) = gltf_assets.get(&asset_pack.main_scene) else {
+        return;
+    };
+    if level_has_lights(gltf, &scenes) {
+        return;
+    }
+
+    commands.spawn((
+        PointLightBundle {
+            transform: Transform::from_translation(FALLBACK_LIGHT_POSITION),
+            point_light: PointLight {
+                intensity: FALLBACK_LIGHT_INTENSITY,
+                shadows_enabled: true,
+                ..default()
+            },
+            ..default()
+        },
+        WarehouseLight::new(FALLBACK_LIGHT_INTENSITY),
+        Name::new("Fallback light"),
+    ));
+}
@@ -0,0 +1,22 @@
+pub mod day_night;
+pub mod level_lights;
+pub mod sun;
+pub mod zones;
+
+use bevy::prelude::*;
+
+use self::day_night::DayNightPlugin;
+use self::level_lights::LevelLightsPlugin;
+use self::sun::SunPlugin;
+use self::zones::LightZonesPlugin;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(LevelLightsPlugin)
+            .add_plugins(SunPlugin)
+            .add_plugins(DayNightPlugin)
+            .add_plugins(LightZonesPlugin);
+    }
+}
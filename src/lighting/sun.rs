@@ -0,0 +1,72 @@
+// The sun. It shines in through the warehouse's doors and windows and tells the sky where to draw
+// itself, so the two always agree. A directional light in the level sets its color and strength;
+// where it is comes from the time of day (see `day_night`).
+
+use bevy::prelude::*;
+use bevy_atmosphere::prelude::{AtmosphereMut, Nishita};
+
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+
+pub struct SunPlugin;
+
+impl Plugin for SunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AssetLoaderState::Done), spawn_sun)
+            .add_systems(Update, (adopt_level_sun, sync_sky_to_sun).chain());
+    }
+}
+
+const SUN_ILLUMINANCE: f32 = light_consts::lux::AMBIENT_DAYLIGHT;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Sun {
+    // In full daylight.
+    pub illuminance: f32,
+}
+
+fn spawn_sun(mut commands: Commands) {
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: SUN_ILLUMINANCE,
+                shadows_enabled: true,
+                ..default()
+            },
+            ..default()
+        },
+        Sun {
+            illuminance: SUN_ILLUMINANCE,
+        },
+        Name::new("Sun"),
+    ));
+}
+
+// There's only one sun, so the level's takes over the sun's settings instead of lighting the
+// warehouse a second time.
+fn adopt_level_sun(
+    mut commands: Commands,
+    level_query: Query<(Entity, &DirectionalLight), Without<Sun>>,
+    mut sun_query: Query<(&mut Sun, &mut DirectionalLight)>,
+) {
+    let Ok((mut sun, mut light)) = sun_query.get_single_mut() else {
+        return;
+    };
+
+    for (entity, level_sun) in level_query.iter() {
+        sun.illuminance = level_sun.illuminance;
+        light.color = level_sun.color;
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn sync_sky_to_sun(
+    mut atmosphere: AtmosphereMut<Nishita>,
+    sun_query: Query<&GlobalTransform, (With<Sun>, Changed<GlobalTransform>)>,
+) {
+    let Ok(transform) = sun_query.get_single() else {
+        return;
+    };
+
+    // The sky wants the direction towards the sun, which is where its light comes from.
+    atmosphere.sun_position = -transform.forward();
+}
@@ -0,0 +1,189 @@
+// Light zones are volumes marked in the level (see `LightZone`). The lights inside one are
+// switched together with the Lights action while the player stands in it, and lights outside
+// every zone are always on. Lit lights draw power, which is paid for by the hour like the staff.
+
+use bevy::prelude::*;
+use bevy_fps_controller::controller::LogicalPlayer;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::markers::LightZone;
+use crate::logistics::economy::Wallet;
+use crate::logistics::shift::ShiftClock;
+
+pub struct LightZonesPlugin;
+
+impl Plugin for LightZonesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLightZone>().add_systems(
+            Update,
+            (
+                add_light_switches,
+                assign_light_zones,
+                find_current_light_zone,
+                toggle_zone_lights,
+                switch_zone_lights,
+                pay_for_power,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// LED high bays turn about this many lumens out of every watt.
+const LUMENS_PER_WATT: f32 = 100.0;
+// Priced for the game rather than the grid: at real prices a fixture costs a few cents an hour,
+// which nobody would notice next to the wages. At this price it's about a tenth of a worker.
+const PRICE_PER_KWH: f32 = 8.0;
+
+// On every light in the warehouse, whether it came with the level or was hung in build mode.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WarehouseLight {
+    // `None` outside every light zone.
+    pub zone: Option<Entity>,
+    // What the light gives off when it's on.
+    pub intensity: f32,
+}
+
+impl WarehouseLight {
+    pub fn new(intensity: f32) -> Self {
+        WarehouseLight {
+            zone: None,
+            intensity,
+        }
+    }
+
+    pub fn power_draw_kw(&self) -> f32 {
+        self.intensity / LUMENS_PER_WATT / 1000.0
+    }
+}
+
+// On every light zone. Zones start with their lights on.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LightSwitch {
+    pub on: bool,
+}
+
+// The light zone the player is standing in.
+#[derive(Resource, Clone, Copy, PartialEq, Default, Debug)]
+pub struct CurrentLightZone(pub Option<Entity>);
+
+// Light zones are cubes two units across before the empty's transform scales them, the same as
+// trigger zones.
+pub fn zone_contains(zone: &GlobalTransform, point: Vec3) -> bool {
+    let local = zone.affine().inverse().transform_point3(point);
+    local.abs().max_element() <= 1.0
+}
+
+pub fn is_lit(light: &WarehouseLight, switch_query: &Query<&LightSwitch>) -> bool {
+    light
+        .zone
+        .and_then(|zone| switch_query.get(zone).ok())
+        .is_none_or(|switch| switch.on)
+}
+
+fn add_light_switches(mut commands: Commands, zone_query: Query<Entity, Added<LightZone>>) {
+    for zone in zone_query.iter() {
+        commands.entity(zone).insert(LightSwitch { on: true });
+    }
+}
+
+// Lights from the level only get their place in the world once the scene has spawned, so this
+// also runs whenever a light moves.
+#[allow(clippy::type_complexity)]
+fn assign_light_zones(
+    mut light_query: Query<
+        (&mut WarehouseLight, &GlobalTransform),
+        Or<(Added<WarehouseLight>, Changed<GlobalTransform>)>,
+    >,
+    zone_query: Query<(Entity, &GlobalTransform), With<LightZone>>,
+) {
+    for (mut light, transform) in light_query.iter_mut() {
+        let position = transform.translation();
+        light.zone = zone_query
+            .iter()
+            .find(|(_, zone)| zone_contains(zone, position))
+            .map(|(zone, _)| zone);
+    }
+}
+
+fn find_current_light_zone(
+    player_query: Query<&GlobalTransform, With<LogicalPlayer>>,
+    zone_query: Query<(Entity, &GlobalTransform), With<LightZone>>,
+    mut current: ResMut<CurrentLightZone>,
+) {
+    let zone = player_query.get_single().ok().and_then(|player| {
+        zone_query
+            .iter()
+            .find(|(_, zone)| zone_contains(zone, player.translation()))
+            .map(|(zone, _)| zone)
+    });
+
+    current.set_if_neq(CurrentLightZone(zone));
+}
+
+fn toggle_zone_lights(
+    actions: Res<ActionState>,
+    current: Res<CurrentLightZone>,
+    mut zone_query: Query<(&LightZone, &mut LightSwitch)>,
+) {
+    if !actions.just_pressed(Action::Lights) {
+        return;
+    }
+    let Some((zone, mut switch)) = current.0.and_then(|zone| zone_query.get_mut(zone).ok()) else {
+        return;
+    };
+
+    switch.on = !switch.on;
+    if switch.on {
+        info!("Lights on in {}", zone.name);
+    } else {
+        info!("Lights off in {}", zone.name);
+    }
+}
+
+// Switching off sets the intensity to zero rather than hiding the light, so shadow changes in the
+// graphics settings still reach lights that are off.
+fn switch_zone_lights(
+    switch_query: Query<Ref<LightSwitch>>,
+    mut light_query: Query<(
+        Ref<WarehouseLight>,
+        Option<&mut PointLight>,
+        Option<&mut SpotLight>,
+    )>,
+) {
+    for (light, point, spot) in light_query.iter_mut() {
+        let switch = light.zone.and_then(|zone| switch_query.get(zone).ok());
+        let switched = switch.as_ref().is_some_and(|switch| switch.is_changed());
+        if !light.is_changed() && !switched {
+            continue;
+        }
+
+        let on = switch.is_none_or(|switch| switch.on);
+        let intensity = if on { light.intensity } else { 0.0 };
+        if let Some(mut point) = point {
+            point.intensity = intensity;
+        }
+        if let Some(mut spot) = spot {
+            spot.intensity = intensity;
+        }
+    }
+}
+
+fn pay_for_power(
+    time: Res<Time>,
+    clock: Res<ShiftClock>,
+    light_query: Query<&WarehouseLight>,
+    switch_query: Query<&LightSwitch>,
+    mut wallet: ResMut<Wallet>,
+) {
+    let hours = time.delta_seconds() * clock.game_hours_per_second;
+    let draw_kw: f32 = light_query
+        .iter()
+        .filter(|light| is_lit(light, &switch_query))
+        .map(WarehouseLight::power_draw_kw)
+        .sum();
+
+    wallet.debit(draw_kw * PRICE_PER_KWH * hours);
+}
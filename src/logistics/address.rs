@@ -0,0 +1,370 @@
+// Address validation and the exception desk. Some labels arrive damaged or wrong; the facility's
+// label reader flags them on intake, and the player resolves them at an exception desk by
+// correcting the address from the customer file, returning the package to its sender, or
+// holding it.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{is_inside_any, spawn_level_markers, TriggerZone};
+use crate::levels::package_data::{Package, COUNTRIES, HOME_COUNTRY, NAMES, ZIP_CODES};
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct AddressPlugin;
+
+impl Plugin for AddressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AddressExceptionResolved>()
+            .add_systems(
+                OnEnter(AssetLoaderState::Done),
+                setup_exception_desks.after(spawn_level_markers),
+            )
+            .add_systems(
+                Update,
+                (flag_address_exceptions, resolve_address_exception)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Stands in for characters the label reader could not make out.
+pub const ILLEGIBLE_MARK: char = '#';
+
+// Recipients that look plausible on a label but aren't in the customer directory.
+const UNKNOWN_RECIPIENTS: [&str; 6] = [
+    "Current Resident",
+    "J. Smith",
+    "Mr. Occupant",
+    "Dr. Blank",
+    "A. N. Other",
+    "Customer",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PostalFormat {
+    NoPostalCode,
+    Digits(u32),
+}
+
+// Countries that don't follow the default five digit postal code.
+const POSTAL_FORMATS: [(&str, PostalFormat); 27] = [
+    ("Australia", PostalFormat::Digits(4)),
+    ("Austria", PostalFormat::Digits(4)),
+    ("Belgium", PostalFormat::Digits(4)),
+    ("Denmark", PostalFormat::Digits(4)),
+    ("New Zealand", PostalFormat::Digits(4)),
+    ("Norway", PostalFormat::Digits(4)),
+    ("Philippines", PostalFormat::Digits(4)),
+    ("South Africa", PostalFormat::Digits(4)),
+    ("Switzerland", PostalFormat::Digits(4)),
+    ("China", PostalFormat::Digits(6)),
+    ("India", PostalFormat::Digits(6)),
+    ("Kazakhstan", PostalFormat::Digits(6)),
+    ("Russia", PostalFormat::Digits(6)),
+    ("Singapore", PostalFormat::Digits(6)),
+    ("Chile", PostalFormat::Digits(7)),
+    ("Japan", PostalFormat::Digits(7)),
+    ("Brazil", PostalFormat::Digits(8)),
+    ("Angola", PostalFormat::NoPostalCode),
+    ("Bahamas", PostalFormat::NoPostalCode),
+    ("Fiji", PostalFormat::NoPostalCode),
+    ("Ghana", PostalFormat::NoPostalCode),
+    ("Qatar", PostalFormat::NoPostalCode),
+    ("Rwanda", PostalFormat::NoPostalCode),
+    ("Togo", PostalFormat::NoPostalCode),
+    ("Tonga", PostalFormat::NoPostalCode),
+    ("Uganda", PostalFormat::NoPostalCode),
+    ("United Arab Emirates", PostalFormat::NoPostalCode),
+];
+
+impl PostalFormat {
+    pub fn for_country(country: &str) -> Self {
+        POSTAL_FORMATS
+            .iter()
+            .find(|(name, _)| *name == country)
+            .map_or(PostalFormat::Digits(5), |(_, format)| *format)
+    }
+
+    // Leading zeros are lost in a `u32`, so only codes that are too long can be caught here.
+    pub fn accepts(&self, zip_code: u32) -> bool {
+        match self {
+            PostalFormat::NoPostalCode => false,
+            PostalFormat::Digits(digits) => zip_code < 10u32.pow(*digits),
+        }
+    }
+
+    // A valid code for a domestic or international label.
+    pub fn random_code(&self) -> Option<u32> {
+        match self {
+            PostalFormat::NoPostalCode => None,
+            PostalFormat::Digits(digits) => Some(thread_rng().gen_range(0..10u32.pow(*digits))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressIssue {
+    MissingZip,
+    ZipCountryMismatch,
+    IllegibleField,
+    UnknownRecipient,
+    UnknownCountry,
+}
+
+pub fn validate_address(package: &Package) -> Result<(), Vec<AddressIssue>> {
+    let mut issues = Vec::new();
+
+    let illegible = [
+        &package.recipient_name,
+        &package.street_address,
+        &package.country,
+    ]
+    .iter()
+    .any(|field| field.contains(ILLEGIBLE_MARK));
+
+    if illegible {
+        issues.push(AddressIssue::IllegibleField);
+    } else {
+        if !COUNTRIES.contains(&package.country.as_str()) {
+            issues.push(AddressIssue::UnknownCountry);
+        }
+        if !NAMES.contains(&package.recipient_name.as_str()) {
+            issues.push(AddressIssue::UnknownRecipient);
+        }
+    }
+
+    let format = PostalFormat::for_country(&package.country);
+    match (package.zip_code, format) {
+        (None, PostalFormat::Digits(_)) => issues.push(AddressIssue::MissingZip),
+        (None, PostalFormat::NoPostalCode) => {}
+        (Some(zip_code), _) => {
+            // Domestic mail is also checked against the zones this facility actually serves.
+            let served = package.country != HOME_COUNTRY || ZIP_CODES.contains(&zip_code);
+            if !format.accepts(zip_code) || !served {
+                issues.push(AddressIssue::ZipCountryMismatch);
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LabelDefect {
+    MissingZip,
+    ZipCountryMismatch,
+    IllegibleField,
+    UnknownRecipient,
+}
+
+impl LabelDefect {
+    // Roughly one label in ten arrives with something wrong with it.
+    pub fn roll() -> Option<Self> {
+        if !thread_rng().gen_bool(0.1) {
+            return None;
+        }
+
+        Some(match thread_rng().gen_range(0..4) {
+            0 => LabelDefect::MissingZip,
+            1 => LabelDefect::ZipCountryMismatch,
+            2 => LabelDefect::IllegibleField,
+            _ => LabelDefect::UnknownRecipient,
+        })
+    }
+
+    pub fn apply(&self, package: &mut Package) {
+        let format = PostalFormat::for_country(&package.country);
+
+        match (self, format) {
+            // A missing code is only a defect where the country uses postal codes.
+            (LabelDefect::MissingZip, PostalFormat::Digits(_)) => package.zip_code = None,
+            (LabelDefect::MissingZip | LabelDefect::ZipCountryMismatch, _) => {
+                package.zip_code = Some(mismatched_code(format));
+            }
+            (LabelDefect::IllegibleField, _) => {
+                if thread_rng().gen_bool(0.5) {
+                    smudge(&mut package.recipient_name);
+                } else {
+                    smudge(&mut package.street_address);
+                }
+            }
+            (LabelDefect::UnknownRecipient, _) => {
+                let name = UNKNOWN_RECIPIENTS[thread_rng().gen_range(0..UNKNOWN_RECIPIENTS.len())];
+                package.recipient_name = name.to_string();
+            }
+        }
+    }
+}
+
+// A code one digit longer than the destination allows, or any code at all where none is used.
+fn mismatched_code(format: PostalFormat) -> u32 {
+    match format {
+        PostalFormat::NoPostalCode => thread_rng().gen_range(10000..100000),
+        PostalFormat::Digits(digits) => {
+            let digits = (digits + 1).min(9);
+            thread_rng().gen_range(10u32.pow(digits - 1)..10u32.pow(digits))
+        }
+    }
+}
+
+fn smudge(field: &mut String) {
+    *field = field
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            // Always smudge at least the first character so the field is never fully readable.
+            if !c.is_whitespace() && (i == 0 || thread_rng().gen_bool(0.3)) {
+                ILLEGIBLE_MARK
+            } else {
+                c
+            }
+        })
+        .collect();
+}
+
+// The address as the customer file has it. Recipients nobody has heard of have no file, so their
+// packages can only be returned or held.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct AddressOnFile {
+    pub recipient_name: String,
+    pub street_address: String,
+    pub country: String,
+    pub zip_code: Option<u32>,
+}
+
+impl AddressOnFile {
+    pub fn from_package(package: &Package) -> Self {
+        AddressOnFile {
+            recipient_name: package.recipient_name.clone(),
+            street_address: package.street_address.clone(),
+            country: package.country.clone(),
+            zip_code: package.zip_code,
+        }
+    }
+
+    pub fn restore(&self, package: &mut Package) {
+        package.recipient_name = self.recipient_name.clone();
+        package.street_address = self.street_address.clone();
+        package.country = self.country.clone();
+        package.zip_code = self.zip_code;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExceptionStatus {
+    Open,
+    Held,
+    Corrected,
+    ReturnedToSender,
+}
+
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct AddressException {
+    pub issues: Vec<AddressIssue>,
+    pub status: ExceptionStatus,
+}
+
+impl AddressException {
+    // Held packages can still be corrected or returned later.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, ExceptionStatus::Open | ExceptionStatus::Held)
+    }
+}
+
+// Trigger zones whose name contains "exception" become exception desks.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ExceptionDesk;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AddressExceptionResolved {
+    pub package: Entity,
+    pub status: ExceptionStatus,
+}
+
+fn setup_exception_desks(mut commands: Commands, zones: Query<(Entity, &TriggerZone)>) {
+    for (entity, zone) in zones.iter() {
+        if zone.name.to_lowercase().contains("exception") {
+            commands.entity(entity).insert(ExceptionDesk);
+        }
+    }
+}
+
+// The label reader checks every package as it enters the facility.
+fn flag_address_exceptions(
+    mut commands: Commands,
+    package_query: Query<(Entity, &Package), Added<Package>>,
+) {
+    for (entity, package) in package_query.iter() {
+        if let Err(issues) = validate_address(package) {
+            commands.entity(entity).insert(AddressException {
+                issues,
+                status: ExceptionStatus::Open,
+            });
+        }
+    }
+}
+
+// At an exception desk: Clear (C) to correct from the customer file, Return to sender (R), or
+// Hold (H).
+fn resolve_address_exception(
+    actions: Res<ActionState>,
+    rapier_context: Res<RapierContext>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    desk_query: Query<Entity, With<ExceptionDesk>>,
+    mut package_query: Query<(
+        &mut Package,
+        &mut AddressException,
+        Option<&AddressOnFile>,
+    )>,
+    mut resolutions: EventWriter<AddressExceptionResolved>,
+) {
+    let status = if actions.just_pressed(Action::Clear) {
+        ExceptionStatus::Corrected
+    } else if actions.just_pressed(Action::ReturnToSender) {
+        ExceptionStatus::ReturnedToSender
+    } else if actions.just_pressed(Action::Hold) {
+        ExceptionStatus::Held
+    } else {
+        return;
+    };
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let Ok((mut package, mut exception, address_on_file)) = package_query.get_mut(target)
+        else {
+            continue;
+        };
+
+        if !matches!(exception.status, ExceptionStatus::Open | ExceptionStatus::Held) {
+            continue;
+        }
+        if !is_inside_any(&rapier_context, desk_query.iter(), target) {
+            continue;
+        }
+
+        if status == ExceptionStatus::Corrected {
+            let Some(address_on_file) = address_on_file else {
+                info!("No address on file for {}", package.recipient_name);
+                continue;
+            };
+            address_on_file.restore(&mut package);
+        }
+
+        exception.status = status;
+        resolutions.send(AddressExceptionResolved {
+            package: target,
+            status,
+        });
+    }
+}
@@ -0,0 +1,351 @@
+// Roll cages, gaylords, pallets and mail bags that packages are consolidated into. A consolidated
+// package becomes a child collider of the container's rigid body, so anything that moves the
+// container (the player, a forklift, a truck) moves its contents with it.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{normalize_tag, spawn_level_markers, ContainerSpawnPoint};
+use crate::levels::package_data::Package;
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::tracking::{TrackingEventKind, TrackingLog};
+use crate::navigation::grid::NavObstacle;
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct ContainersPlugin;
+
+impl Plugin for ContainersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AssetLoaderState::Done),
+            spawn_containers.after(spawn_level_markers),
+        )
+        .add_systems(
+            Update,
+            consolidate_package_action.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Packages use a 1.4m box collider (see `spawn_box`); container slots are laid out on that grid.
+const PACKAGE_SIZE: f32 = 1.4;
+const BASE_HEIGHT: f32 = 0.15;
+
+// How far a package can be from a container for the consolidate action to reach it.
+pub const CONSOLIDATE_RANGE: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContainerKind {
+    RollCage,
+    Gaylord,
+    Pallet,
+    MailBag,
+}
+
+impl ContainerKind {
+    pub fn from_marker_name(name: &str) -> Option<Self> {
+        let normalized = normalize_tag(name);
+
+        [
+            ("rollcage", ContainerKind::RollCage),
+            ("gaylord", ContainerKind::Gaylord),
+            ("pallet", ContainerKind::Pallet),
+            ("mailbag", ContainerKind::MailBag),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| normalized.starts_with(prefix))
+        .map(|(_, kind)| kind)
+    }
+
+    // Columns and rows of packages on the container's base; layers stack on top.
+    pub fn footprint(&self) -> (usize, usize) {
+        match self {
+            ContainerKind::RollCage => (1, 2),
+            ContainerKind::Gaylord => (2, 2),
+            ContainerKind::Pallet => (2, 2),
+            ContainerKind::MailBag => (1, 1),
+        }
+    }
+
+    pub fn max_packages(&self) -> usize {
+        match self {
+            ContainerKind::RollCage => 6,
+            ContainerKind::Gaylord => 4,
+            ContainerKind::Pallet => 8,
+            ContainerKind::MailBag => 2,
+        }
+    }
+
+    pub fn max_weight(&self) -> f32 {
+        match self {
+            ContainerKind::RollCage => 1000.0,
+            ContainerKind::Gaylord => 1200.0,
+            ContainerKind::Pallet => 2000.0,
+            ContainerKind::MailBag => 70.0,
+        }
+    }
+
+    // Mail bags only take small, non-hazardous parcels.
+    pub fn max_package_weight(&self) -> f32 {
+        match self {
+            ContainerKind::MailBag => 35.0,
+            _ => f32::INFINITY,
+        }
+    }
+
+    pub fn accepts_hazmat(&self) -> bool {
+        !matches!(self, ContainerKind::MailBag)
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ContainerKind::RollCage => Color::rgb(0.6, 0.6, 0.65),
+            ContainerKind::Gaylord => Color::rgb(0.55, 0.4, 0.25),
+            ContainerKind::Pallet => Color::rgb(0.75, 0.6, 0.4),
+            ContainerKind::MailBag => Color::rgb(0.3, 0.35, 0.55),
+        }
+    }
+
+    pub fn base_half_extents(&self) -> Vec3 {
+        let (columns, rows) = self.footprint();
+        Vec3::new(
+            columns as f32 * PACKAGE_SIZE * 0.5,
+            BASE_HEIGHT * 0.5,
+            rows as f32 * PACKAGE_SIZE * 0.5,
+        )
+    }
+
+    // Position of the nth package relative to the container.
+    fn slot(&self, index: usize) -> Vec3 {
+        let (columns, rows) = self.footprint();
+        let half_extents = self.base_half_extents();
+
+        let layer = index / (columns * rows);
+        let row = (index % (columns * rows)) / columns;
+        let column = index % columns;
+
+        Vec3::new(
+            (column as f32 + 0.5) * PACKAGE_SIZE - half_extents.x,
+            half_extents.y + (layer as f32 + 0.5) * PACKAGE_SIZE,
+            (row as f32 + 0.5) * PACKAGE_SIZE - half_extents.z,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContainerRejection {
+    Full,
+    Overweight,
+    PackageTooHeavy,
+    Hazmat,
+}
+
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct ShippingContainer {
+    pub kind: ContainerKind,
+    pub tracking_id: String,
+    pub label: String,
+    // Tracking IDs of the packages inside, in the order they were added.
+    pub manifest: Vec<String>,
+    pub total_weight: f32,
+    // Which package is in each slot. Taking one out leaves a gap the next one fills.
+    slots: Vec<Option<String>>,
+}
+
+impl ShippingContainer {
+    pub fn new(kind: ContainerKind, label: String) -> Self {
+        ShippingContainer {
+            kind,
+            tracking_id: format!("CT{:010}", thread_rng().gen_range(0..10_000_000_000u64)),
+            label,
+            manifest: Vec::new(),
+            total_weight: 0.0,
+            slots: Vec::new(),
+        }
+    }
+
+    pub fn check_capacity(&self, package: &Package) -> Result<(), ContainerRejection> {
+        if self.manifest.len() >= self.kind.max_packages() {
+            Err(ContainerRejection::Full)
+        } else if package.weight > self.kind.max_package_weight() {
+            Err(ContainerRejection::PackageTooHeavy)
+        } else if package.hazmat && !self.kind.accepts_hazmat() {
+            Err(ContainerRejection::Hazmat)
+        } else if self.total_weight + package.weight > self.kind.max_weight() {
+            Err(ContainerRejection::Overweight)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.manifest.len() >= self.kind.max_packages()
+    }
+
+    // Returns where the package goes, relative to the container.
+    fn add(&mut self, package: &Package) -> Vec3 {
+        self.manifest.push(package.tracking_id.clone());
+        self.total_weight += package.weight;
+
+        let index = match self.slots.iter().position(Option::is_none) {
+            Some(index) => index,
+            None => {
+                self.slots.push(None);
+                self.slots.len() - 1
+            }
+        };
+        self.slots[index] = Some(package.tracking_id.clone());
+        self.kind.slot(index)
+    }
+
+    fn remove(&mut self, package: &Package) {
+        self.manifest.retain(|id| *id != package.tracking_id);
+        self.total_weight = (self.total_weight - package.weight).max(0.0);
+        for slot in self.slots.iter_mut() {
+            if slot.as_ref() == Some(&package.tracking_id) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+// Leaves the package where it is, loose again.
+pub fn take_out(
+    commands: &mut Commands,
+    container: &mut ShippingContainer,
+    entity: Entity,
+    package: &Package,
+    clock: &ShiftClock,
+    log: &mut TrackingLog,
+) {
+    container.remove(package);
+    commands
+        .entity(entity)
+        .remove_parent_in_place()
+        .insert(RigidBody::Dynamic);
+    log.record(
+        &package.tracking_id,
+        TrackingEventKind::Deconsolidated(container.tracking_id.clone()),
+        clock.now(),
+    );
+}
+
+fn spawn_containers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    spawn_query: Query<(&ContainerSpawnPoint, &Transform)>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for (spawn_point, transform) in spawn_query.iter() {
+        let Some(kind) = ContainerKind::from_marker_name(&spawn_point.name) else {
+            continue;
+        };
+
+        let half_extents = kind.base_half_extents();
+        let container = ShippingContainer::new(kind, spawn_point.name.clone());
+        log.record(
+            &container.tracking_id,
+            TrackingEventKind::Created,
+            clock.now(),
+        );
+
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+                material: materials.add(kind.color()),
+                transform: Transform::from_translation(transform.translation)
+                    .with_rotation(transform.rotation),
+                ..default()
+            },
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+            Friction::coefficient(1.2),
+            RigidBody::Dynamic,
+            NavObstacle {
+                half_extents: half_extents.xz(),
+            },
+            container,
+        ));
+    }
+}
+
+// Consolidate (K) puts the package the player is looking at into the closest container in range,
+// or takes it back out if it's already consolidated.
+fn consolidate_package_action(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    package_query: Query<(&Package, &GlobalTransform, Option<&Parent>)>,
+    mut container_query: Query<(Entity, &mut ShippingContainer, &GlobalTransform)>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    if !actions.just_pressed(Action::Consolidate) {
+        return;
+    }
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let Ok((package, package_transform, parent)) = package_query.get(target) else {
+            continue;
+        };
+
+        if let Some(parent) = parent {
+            if let Ok((_, mut container, _)) = container_query.get_mut(parent.get()) {
+                take_out(
+                    &mut commands,
+                    &mut container,
+                    target,
+                    package,
+                    &clock,
+                    &mut log,
+                );
+                continue;
+            }
+        }
+
+        let closest_container = container_query
+            .iter_mut()
+            .map(|(entity, container, transform)| {
+                let distance = transform
+                    .translation()
+                    .distance(package_transform.translation());
+                (entity, container, distance)
+            })
+            .filter(|(_, _, distance)| *distance < CONSOLIDATE_RANGE)
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+        let Some((container_entity, mut container, _)) = closest_container else {
+            continue;
+        };
+
+        if let Err(rejection) = container.check_capacity(package) {
+            info!(
+                "{} can't take {}: {:?}",
+                container.label, package.tracking_id, rejection
+            );
+            continue;
+        }
+
+        // Without its own rigid body the package's collider joins the container's.
+        let slot = container.add(package);
+        commands
+            .entity(target)
+            .remove::<RigidBody>()
+            .set_parent(container_entity)
+            .insert(Transform::from_translation(slot));
+
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::Consolidated(container.tracking_id.clone()),
+            clock.now(),
+        );
+    }
+}
@@ -0,0 +1,298 @@
+// Customs forms for international packages and the inspection station where the player decides
+// what happens to them. Each declaration is screened against the destination country's rules;
+// the player only sees the form and has to make the call.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{is_inside_any, spawn_level_markers, TriggerZone};
+use crate::levels::package_data::{Package, COUNTRIES, HOME_COUNTRY};
+use crate::logistics::economy::Wallet;
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct CustomsPlugin;
+
+impl Plugin for CustomsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CustomsDecisionMade>()
+            .add_systems(
+                OnEnter(AssetLoaderState::Done),
+                setup_inspection_stations.after(spawn_level_markers),
+            )
+            .add_systems(
+                Update,
+                (inspect_package, settle_customs_decisions)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct CustomsDeclaration {
+    pub contents: String,
+    pub declared_value: f32,
+    pub hs_code: String,
+    pub sender_country: String,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CustomsStatus {
+    Pending,
+    Held,
+    Cleared,
+    Returned,
+}
+
+// Trigger zones whose name contains "inspection" become inspection stations.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct InspectionStation;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CustomsRestriction {
+    Restricted,
+    Prohibited,
+}
+
+// What a correct inspection would conclude. The player never sees this directly.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CustomsVerdict {
+    Admissible,
+    Undervalued,
+    Restricted,
+    Prohibited,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CustomsDecision {
+    Hold,
+    Clear,
+    Return,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CustomsDecisionMade {
+    pub package: Entity,
+    pub decision: CustomsDecision,
+    pub verdict: CustomsVerdict,
+}
+
+// Description, HS code and typical value of the goods a declaration can list.
+const DECLARED_GOODS: [(&str, &str, f32); 15] = [
+    ("Cotton t-shirts", "6109.10", 40.0),
+    ("Mobile phone", "8517.13", 600.0),
+    ("Books", "4901.99", 30.0),
+    ("Roasted coffee", "0901.21", 25.0),
+    ("Lithium-ion batteries", "8507.60", 80.0),
+    ("Perfume", "3303.00", 90.0),
+    ("Red wine", "2204.21", 60.0),
+    ("Cigarettes", "2402.20", 50.0),
+    ("Hunting knife", "8211.92", 70.0),
+    ("Fireworks", "3604.10", 45.0),
+    ("Cured sausages", "1601.00", 20.0),
+    ("Prescription medicine", "3004.90", 120.0),
+    ("Camera drone", "8806.22", 250.0),
+    ("Gold necklace", "7113.19", 900.0),
+    ("Vegetable seeds", "1209.91", 10.0),
+];
+
+// Destination country, HS code prefix and restriction. "*" applies to every destination.
+const CUSTOMS_RULES: [(&str, &str, CustomsRestriction); 16] = [
+    ("*", "36", CustomsRestriction::Prohibited),
+    ("*", "8507", CustomsRestriction::Restricted),
+    ("Saudi Arabia", "22", CustomsRestriction::Prohibited),
+    ("Kuwait", "22", CustomsRestriction::Prohibited),
+    ("Iran", "22", CustomsRestriction::Prohibited),
+    ("Qatar", "22", CustomsRestriction::Restricted),
+    ("Australia", "12", CustomsRestriction::Prohibited),
+    ("Australia", "16", CustomsRestriction::Prohibited),
+    ("New Zealand", "12", CustomsRestriction::Prohibited),
+    ("Japan", "16", CustomsRestriction::Prohibited),
+    ("Japan", "30", CustomsRestriction::Restricted),
+    ("Singapore", "24", CustomsRestriction::Prohibited),
+    ("United Kingdom", "8211", CustomsRestriction::Restricted),
+    ("Canada", "8806", CustomsRestriction::Restricted),
+    ("China", "8806", CustomsRestriction::Restricted),
+    ("India", "7113", CustomsRestriction::Restricted),
+];
+
+// HS code prefixes of goods that ship as dangerous goods: fireworks and lithium batteries.
+const DANGEROUS_GOODS: [&str; 2] = ["36", "8507"];
+
+// Declared values below this fraction of what the goods are usually worth are flagged.
+const UNDERVALUED_RATIO: f32 = 0.25;
+
+// Fines for letting bad declarations through, and the cost of delaying a clean one.
+const PROHIBITED_CLEARED_FINE: f32 = 500.0;
+const RESTRICTED_CLEARED_FINE: f32 = 150.0;
+const WRONGFUL_HOLD_COST: f32 = 10.0;
+
+impl CustomsDeclaration {
+    pub fn random() -> Self {
+        let (contents, hs_code, typical_value) =
+            DECLARED_GOODS[thread_rng().gen_range(0..DECLARED_GOODS.len())];
+
+        // Some senders lowball the value to dodge duties.
+        let value_ratio = if thread_rng().gen_bool(0.1) {
+            thread_rng().gen_range(0.05..0.2)
+        } else {
+            thread_rng().gen_range(0.6..1.5)
+        };
+
+        // Most international mail is outbound; the rest is transiting through the facility.
+        let sender_country = if thread_rng().gen_bool(0.8) {
+            HOME_COUNTRY
+        } else {
+            COUNTRIES[thread_rng().gen_range(0..COUNTRIES.len())]
+        };
+
+        CustomsDeclaration {
+            contents: contents.to_string(),
+            declared_value: typical_value * value_ratio,
+            hs_code: hs_code.to_string(),
+            sender_country: sender_country.to_string(),
+        }
+    }
+
+    // Dangerous goods need a hazmat label, wherever they're going.
+    pub fn is_dangerous_goods(&self) -> bool {
+        let digits: String = self.hs_code.chars().filter(char::is_ascii_digit).collect();
+        DANGEROUS_GOODS
+            .iter()
+            .any(|prefix| digits.starts_with(prefix))
+    }
+
+    fn typical_value(&self) -> Option<f32> {
+        DECLARED_GOODS
+            .iter()
+            .find(|(_, hs_code, _)| *hs_code == self.hs_code)
+            .map(|(_, _, value)| *value)
+    }
+}
+
+pub fn restriction_for(destination: &str, hs_code: &str) -> Option<CustomsRestriction> {
+    let digits: String = hs_code.chars().filter(char::is_ascii_digit).collect();
+
+    CUSTOMS_RULES
+        .iter()
+        .filter(|(country, prefix, _)| {
+            (*country == "*" || *country == destination) && digits.starts_with(prefix)
+        })
+        .map(|(_, _, restriction)| *restriction)
+        .max_by_key(|restriction| *restriction == CustomsRestriction::Prohibited)
+}
+
+pub fn screen(package: &Package, declaration: &CustomsDeclaration) -> CustomsVerdict {
+    match restriction_for(&package.country, &declaration.hs_code) {
+        Some(CustomsRestriction::Prohibited) => CustomsVerdict::Prohibited,
+        Some(CustomsRestriction::Restricted) => CustomsVerdict::Restricted,
+        None => match declaration.typical_value() {
+            Some(value) if declaration.declared_value < value * UNDERVALUED_RATIO => {
+                CustomsVerdict::Undervalued
+            }
+            _ => CustomsVerdict::Admissible,
+        },
+    }
+}
+
+impl CustomsVerdict {
+    pub fn expected_decision(&self) -> CustomsDecision {
+        match self {
+            CustomsVerdict::Admissible => CustomsDecision::Clear,
+            CustomsVerdict::Undervalued | CustomsVerdict::Restricted => CustomsDecision::Hold,
+            CustomsVerdict::Prohibited => CustomsDecision::Return,
+        }
+    }
+}
+
+fn setup_inspection_stations(mut commands: Commands, zones: Query<(Entity, &TriggerZone)>) {
+    for (entity, zone) in zones.iter() {
+        if zone.name.to_lowercase().contains("inspection") {
+            commands.entity(entity).insert(InspectionStation);
+        }
+    }
+}
+
+// The player looks at a package sitting in an inspection station and decides: Hold (H), Clear (C)
+// or Return to sender (R).
+fn inspect_package(
+    actions: Res<ActionState>,
+    rapier_context: Res<RapierContext>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    station_query: Query<Entity, With<InspectionStation>>,
+    mut package_query: Query<(&Package, &CustomsDeclaration, &mut CustomsStatus)>,
+    mut decisions: EventWriter<CustomsDecisionMade>,
+) {
+    let decision = if actions.just_pressed(Action::Hold) {
+        CustomsDecision::Hold
+    } else if actions.just_pressed(Action::Clear) {
+        CustomsDecision::Clear
+    } else if actions.just_pressed(Action::ReturnToSender) {
+        CustomsDecision::Return
+    } else {
+        return;
+    };
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let Ok((package, declaration, mut status)) = package_query.get_mut(target) else {
+            continue;
+        };
+
+        // Held packages can be revisited once the paperwork is sorted out.
+        if !matches!(*status, CustomsStatus::Pending | CustomsStatus::Held) {
+            continue;
+        }
+
+        if !is_inside_any(&rapier_context, station_query.iter(), target) {
+            continue;
+        }
+
+        // Holding a package that's already held changes nothing, and isn't charged again.
+        let decided = match decision {
+            CustomsDecision::Hold => CustomsStatus::Held,
+            CustomsDecision::Clear => CustomsStatus::Cleared,
+            CustomsDecision::Return => CustomsStatus::Returned,
+        };
+        if *status == decided {
+            continue;
+        }
+        *status = decided;
+
+        decisions.send(CustomsDecisionMade {
+            package: target,
+            decision,
+            verdict: screen(package, declaration),
+        });
+    }
+}
+
+fn settle_customs_decisions(
+    mut decisions: EventReader<CustomsDecisionMade>,
+    package_query: Query<&Package>,
+    mut wallet: ResMut<Wallet>,
+) {
+    for event in decisions.read() {
+        let cost = match (event.verdict, event.decision) {
+            (CustomsVerdict::Prohibited, CustomsDecision::Clear) => PROHIBITED_CLEARED_FINE,
+            (CustomsVerdict::Restricted | CustomsVerdict::Undervalued, CustomsDecision::Clear) => {
+                RESTRICTED_CLEARED_FINE
+            }
+            // Decisions are only sent when they change the status, so this is once per hold.
+            (CustomsVerdict::Admissible, CustomsDecision::Hold) => WRONGFUL_HOLD_COST,
+            // Sending back a package that was fine refunds the sender's postage.
+            (CustomsVerdict::Admissible, CustomsDecision::Return) => package_query
+                .get(event.package)
+                .map_or(0.0, |package| package.postage),
+            _ => 0.0,
+        };
+
+        wallet.debit(cost);
+    }
+}
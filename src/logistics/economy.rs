@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wallet>()
+            .add_systems(Update, collect_postage.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// Postage is paid up front when a package is accepted, and guaranteed services are refunded if
+// they miss their promised delivery day.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Wallet {
+    pub balance: f32,
+}
+
+impl Wallet {
+    pub fn credit(&mut self, amount: f32) {
+        self.balance += amount;
+    }
+
+    pub fn debit(&mut self, amount: f32) {
+        self.balance -= amount;
+    }
+}
+
+fn collect_postage(package_query: Query<&Package, Added<Package>>, mut wallet: ResMut<Wallet>) {
+    for package in package_query.iter() {
+        wallet.credit(package.postage);
+    }
+}
@@ -0,0 +1,37 @@
+pub mod address;
+pub mod containers;
+pub mod customs;
+pub mod economy;
+pub mod outbound;
+pub mod shift;
+pub mod shipping_label;
+pub mod sorting;
+pub mod tracking;
+
+use bevy::prelude::*;
+
+use self::address::AddressPlugin;
+use self::containers::ContainersPlugin;
+use self::customs::CustomsPlugin;
+use self::economy::EconomyPlugin;
+use self::outbound::OutboundPlugin;
+use self::shift::ShiftClockPlugin;
+use self::shipping_label::ShippingLabelPlugin;
+use self::sorting::SortingPlugin;
+use self::tracking::TrackingPlugin;
+
+pub struct LogisticsPlugin;
+
+impl Plugin for LogisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ShiftClockPlugin)
+            .add_plugins(EconomyPlugin)
+            .add_plugins(CustomsPlugin)
+            .add_plugins(AddressPlugin)
+            .add_plugins(SortingPlugin)
+            .add_plugins(ContainersPlugin)
+            .add_plugins(OutboundPlugin)
+            .add_plugins(TrackingPlugin)
+            .add_plugins(ShippingLabelPlugin);
+    }
+}
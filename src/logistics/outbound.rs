@@ -0,0 +1,601 @@
+// Outbound trucks parked at the level's dock markers. Packages count as loaded while they sit in a
+// truck's cargo area, whether the player carried them there or used the load action. Each truck
+// leaves on schedule with whatever is aboard, and a new one backs into the dock for the next run.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{spawn_level_markers, DockLocation};
+use crate::levels::package_data::Package;
+use crate::logistics::address::AddressException;
+use crate::logistics::containers::{take_out, ShippingContainer};
+use crate::logistics::customs::CustomsStatus;
+use crate::logistics::economy::Wallet;
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::tracking::TrackingLog;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::staff::jobs::is_blocked;
+
+pub struct OutboundPlugin;
+
+impl Plugin for OutboundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PackageLoaded>()
+            .add_event::<PackageDeparted>()
+            .add_event::<TruckDeparted>()
+            .add_systems(
+                OnEnter(AssetLoaderState::Done),
+                spawn_outbound_trucks.after(spawn_level_markers),
+            )
+            .add_systems(
+                Update,
+                (load_package_action, update_truck_loads, depart_trucks)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Half extents of a trailer's cargo area, with the doors facing the dock along -Z.
+const CARGO_HALF_EXTENTS: Vec3 = Vec3::new(1.5, 1.4, 4.2);
+const TRUCK_MAX_WEIGHT: f32 = 4000.0;
+
+// Every package uses the same 1.4m box collider (see `spawn_box`).
+const PACKAGE_SIZE: f32 = 1.4;
+const PACKAGE_VOLUME: f32 = PACKAGE_SIZE * PACKAGE_SIZE * PACKAGE_SIZE;
+
+// How far from a truck the load action can reach, and how often trucks leave each dock.
+pub const LOAD_RANGE: f32 = 8.0;
+const DEPARTURE_INTERVAL_HOURS: f32 = 4.0;
+
+// A package on top of another one this many times lighter risks crushing it.
+const CRUSH_WEIGHT_RATIO: f32 = 1.5;
+
+#[derive(Component, Clone, Debug)]
+pub struct OutboundTruck {
+    pub name: String,
+    pub dock: String,
+    pub max_weight: f32,
+    pub max_volume: f32,
+    // Absolute hours, see `ShiftClock::now`.
+    pub departs_at: f32,
+    pub manifest: Vec<Entity>,
+    // Containers sitting in the trailer. Their contents are on the manifest.
+    pub containers: Vec<Entity>,
+    // Cargo slots held for whatever a worker is carrying over, so two workers don't pick the
+    // same one.
+    reserved: HashMap<Entity, Vec<usize>>,
+}
+
+impl OutboundTruck {
+    pub fn loaded_weight(&self, package_query: &Query<&Package>) -> f32 {
+        self.manifest
+            .iter()
+            .filter_map(|entity| package_query.get(*entity).ok())
+            .map(|package| package.weight)
+            .sum()
+    }
+
+    pub fn loaded_volume(&self) -> f32 {
+        self.manifest.len() as f32 * PACKAGE_VOLUME
+    }
+
+    pub fn has_room_for(&self, package: &Package, package_query: &Query<&Package>) -> bool {
+        self.loaded_volume() + PACKAGE_VOLUME <= self.max_volume
+            && self.loaded_weight(package_query) + package.weight <= self.max_weight
+    }
+
+    // Everything taking up room in the trailer, including reserved slots.
+    pub fn occupied_space<F: QueryFilter>(
+        &self,
+        truck_transform: &GlobalTransform,
+        transforms: &Query<&GlobalTransform, F>,
+        container_query: &Query<&ShippingContainer>,
+    ) -> Vec<CargoSpace> {
+        let to_local = truck_transform.compute_matrix().inverse();
+        let local = |entity: &Entity| {
+            transforms
+                .get(*entity)
+                .ok()
+                .map(|transform| to_local.transform_point3(transform.translation()))
+        };
+
+        let packages = self
+            .manifest
+            .iter()
+            .filter_map(local)
+            .map(|position| (position, Vec3::splat(PACKAGE_SIZE * 0.5)));
+        let containers = self.containers.iter().filter_map(|entity| {
+            let container = container_query.get(*entity).ok()?;
+            Some((local(entity)?, container.kind.base_half_extents()))
+        });
+        let reserved = self
+            .reserved
+            .values()
+            .flatten()
+            .filter_map(|index| cargo_slot(*index))
+            .map(|slot| (slot, Vec3::splat(PACKAGE_SIZE * 0.5)));
+
+        packages.chain(containers).chain(reserved).collect()
+    }
+
+    pub fn reserve(&mut self, entity: Entity, slots: Vec<usize>) {
+        self.reserved.insert(entity, slots);
+    }
+
+    pub fn release(&mut self, entity: Entity) {
+        self.reserved.remove(&entity);
+    }
+}
+
+// The center and half extents of a box in a trailer's local space.
+pub type CargoSpace = (Vec3, Vec3);
+
+fn overlaps(a: CargoSpace, b: CargoSpace) -> bool {
+    let gap = (a.0 - b.0).abs() - (a.1 + b.1);
+    gap.max_element() < -0.01
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct LoadQuality {
+    pub fill_rate: f32,
+    // 1.0 when the load's center of mass sits over the middle of the trailer.
+    pub balance: f32,
+    // Share of stacked packages sitting under something much heavier than themselves.
+    pub crush_risk: f32,
+    pub score: f32,
+}
+
+// Scores a load from each package's position in the trailer's local space and its weight.
+pub fn load_quality(max_volume: f32, packages: &[(Vec3, f32)]) -> LoadQuality {
+    if packages.is_empty() {
+        return LoadQuality::default();
+    }
+
+    let fill_rate = (packages.len() as f32 * PACKAGE_VOLUME / max_volume).min(1.0);
+
+    let total_weight: f32 = packages.iter().map(|(_, weight)| weight).sum();
+    let center_of_mass = if total_weight > 0.0 {
+        packages
+            .iter()
+            .map(|(position, weight)| *position * *weight)
+            .sum::<Vec3>()
+            / total_weight
+    } else {
+        Vec3::ZERO
+    };
+    let balance = 1.0
+        - (center_of_mass.x.abs() / CARGO_HALF_EXTENTS.x)
+            .max(center_of_mass.z.abs() / CARGO_HALF_EXTENTS.z)
+            .min(1.0);
+
+    let mut stacked = 0;
+    let mut crushed = 0;
+    for (below, below_weight) in packages {
+        let above = packages.iter().filter(|(position, _)| {
+            position.y - below.y > PACKAGE_SIZE * 0.5
+                && position.xz().distance(below.xz()) < PACKAGE_SIZE
+        });
+        for (_, above_weight) in above {
+            stacked += 1;
+            if *above_weight > below_weight * CRUSH_WEIGHT_RATIO {
+                crushed += 1;
+            }
+        }
+    }
+    let crush_risk = if stacked > 0 {
+        crushed as f32 / stacked as f32
+    } else {
+        0.0
+    };
+
+    LoadQuality {
+        fill_rate,
+        balance,
+        crush_risk,
+        score: (fill_rate + balance + (1.0 - crush_risk)) / 3.0,
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PackageLoaded {
+    pub package: Entity,
+    pub truck: String,
+}
+
+// Sent with the tracking ID since the package entity is gone by the time anyone reads this.
+#[derive(Event, Clone, Debug)]
+pub struct PackageDeparted {
+    pub tracking_id: String,
+    pub truck: String,
+    pub late: bool,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TruckDeparted {
+    pub truck: String,
+    pub dock: String,
+    pub packages: usize,
+    pub late_packages: usize,
+    pub quality: LoadQuality,
+}
+
+fn spawn_outbound_trucks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    dock_query: Query<(&DockLocation, &Transform)>,
+    clock: Res<ShiftClock>,
+) {
+    let cargo_size = CARGO_HALF_EXTENTS * 2.0;
+    let trailer_mesh = meshes.add(Cuboid::new(cargo_size.x, cargo_size.y, cargo_size.z));
+    let trailer_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.8, 0.8, 0.85, 0.25),
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    for (index, (dock, dock_transform)) in dock_query.iter().enumerate() {
+        // Park the trailer behind the dock door so its open end lines up with the marker.
+        let offset = Vec3::new(0.0, CARGO_HALF_EXTENTS.y, CARGO_HALF_EXTENTS.z);
+        let transform = Transform {
+            translation: dock_transform.translation + dock_transform.rotation * offset,
+            rotation: dock_transform.rotation,
+            ..default()
+        };
+
+        commands
+            .spawn((
+                PbrBundle {
+                    mesh: trailer_mesh.clone(),
+                    material: trailer_material.clone(),
+                    transform,
+                    ..default()
+                },
+                OutboundTruck {
+                    name: format!("Truck {}", index + 1),
+                    dock: dock.name.clone(),
+                    max_weight: TRUCK_MAX_WEIGHT,
+                    max_volume: cargo_size.x * cargo_size.y * cargo_size.z,
+                    departs_at: clock.now() + DEPARTURE_INTERVAL_HOURS,
+                    manifest: Vec::new(),
+                    containers: Vec::new(),
+                    reserved: HashMap::new(),
+                },
+                Collider::cuboid(
+                    CARGO_HALF_EXTENTS.x,
+                    CARGO_HALF_EXTENTS.y,
+                    CARGO_HALF_EXTENTS.z,
+                ),
+                Sensor,
+            ))
+            .with_children(|trailer| {
+                trailer.spawn((
+                    Collider::cuboid(CARGO_HALF_EXTENTS.x, 0.05, CARGO_HALF_EXTENTS.z),
+                    RigidBody::Fixed,
+                    TransformBundle::from_transform(Transform::from_xyz(
+                        0.0,
+                        -CARGO_HALF_EXTENTS.y,
+                        0.0,
+                    )),
+                ));
+            });
+    }
+}
+
+// Local position of the nth slot, filling the trailer from the front wall toward the doors.
+fn cargo_slot(index: usize) -> Option<Vec3> {
+    let columns = (CARGO_HALF_EXTENTS.x * 2.0 / PACKAGE_SIZE).floor() as usize;
+    let rows = (CARGO_HALF_EXTENTS.z * 2.0 / PACKAGE_SIZE).floor() as usize;
+    let layers = (CARGO_HALF_EXTENTS.y * 2.0 / PACKAGE_SIZE).floor() as usize;
+
+    let layer = index / (columns * rows);
+    if layer >= layers {
+        return None;
+    }
+    let row = (index % (columns * rows)) / columns;
+    let column = index % columns;
+
+    Some(Vec3::new(
+        (column as f32 + 0.5) * PACKAGE_SIZE - CARGO_HALF_EXTENTS.x,
+        (layer as f32 + 0.5) * PACKAGE_SIZE - CARGO_HALF_EXTENTS.y,
+        CARGO_HALF_EXTENTS.z - (row as f32 + 0.5) * PACKAGE_SIZE,
+    ))
+}
+
+// The first slot with nothing in it, and its local position. Packages taken back out leave gaps,
+// so this isn't just the next slot after the last one filled.
+pub fn free_cargo_slot(occupied: &[CargoSpace]) -> Option<(usize, Vec3)> {
+    (0..)
+        .map_while(|index| cargo_slot(index).map(|slot| (index, slot)))
+        .find(|(_, slot)| {
+            let space = (*slot, Vec3::splat(PACKAGE_SIZE * 0.5));
+            !occupied.iter().any(|other| overlaps(space, *other))
+        })
+}
+
+// Where a container with these base half extents can stand on the trailer floor, as far forward
+// as it fits, along with the cargo slots it takes up. Nothing gets stacked on a container, so it
+// takes every slot above it too.
+pub fn free_container_spot(
+    half_extents: Vec3,
+    occupied: &[CargoSpace],
+) -> Option<(Vec<usize>, Vec3)> {
+    let columns = ((CARGO_HALF_EXTENTS.x - half_extents.x) * 2.0 / PACKAGE_SIZE).floor() as i32;
+    let rows = ((CARGO_HALF_EXTENTS.z - half_extents.z) * 2.0 / PACKAGE_SIZE).floor() as i32;
+
+    for row in 0..=rows {
+        for column in 0..=columns {
+            let x = column as f32 * PACKAGE_SIZE + half_extents.x - CARGO_HALF_EXTENTS.x;
+            let z = CARGO_HALF_EXTENTS.z - half_extents.z - row as f32 * PACKAGE_SIZE;
+            let column_space = (
+                Vec3::new(x, 0.0, z),
+                Vec3::new(half_extents.x, CARGO_HALF_EXTENTS.y, half_extents.z),
+            );
+            if occupied.iter().any(|other| overlaps(column_space, *other)) {
+                continue;
+            }
+
+            let slots = (0..)
+                .map_while(|index| cargo_slot(index).map(|slot| (index, slot)))
+                .filter(|(_, slot)| {
+                    overlaps(column_space, (*slot, Vec3::splat(PACKAGE_SIZE * 0.5)))
+                })
+                .map(|(index, _)| index)
+                .collect();
+            let floor = -CARGO_HALF_EXTENTS.y + half_extents.y;
+            return Some((slots, Vec3::new(x, floor, z)));
+        }
+    }
+
+    None
+}
+
+// Open address exceptions and customs holds, which keep a package off the trucks.
+type HoldQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static AddressException>,
+        Option<&'static CustomsStatus>,
+    ),
+    With<Package>,
+>;
+
+fn on_hold(hold_query: &HoldQuery, entity: Entity) -> bool {
+    hold_query
+        .get(entity)
+        .is_ok_and(|(exception, customs)| is_blocked(exception, customs))
+}
+
+// Load truck (L) puts the package the player is looking at onto the closest truck in range.
+#[allow(clippy::too_many_arguments)]
+fn load_package_action(
+    actions: Res<ActionState>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    truck_query: Query<(&OutboundTruck, &GlobalTransform), Without<Package>>,
+    mut package_transforms: Query<&mut Transform, With<Package>>,
+    package_query: Query<&Package>,
+    hold_query: HoldQuery,
+    transforms: Query<&GlobalTransform>,
+    container_query: Query<&ShippingContainer>,
+) {
+    if !actions.just_pressed(Action::LoadTruck) {
+        return;
+    }
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let Ok(package) = package_query.get(target) else {
+            continue;
+        };
+        if on_hold(&hold_query, target) {
+            info!(
+                "{} can't ship until its exception or customs hold is settled",
+                package.tracking_id
+            );
+            continue;
+        }
+        let Ok(mut package_transform) = package_transforms.get_mut(target) else {
+            continue;
+        };
+
+        let closest_truck = truck_query
+            .iter()
+            .filter(|(truck, _)| !truck.manifest.contains(&target))
+            .map(|(truck, transform)| {
+                let distance = transform
+                    .translation()
+                    .distance(package_transform.translation);
+                (truck, transform, distance)
+            })
+            .filter(|(_, _, distance)| *distance < LOAD_RANGE)
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+        let Some((truck, truck_transform, _)) = closest_truck else {
+            continue;
+        };
+        if !truck.has_room_for(package, &package_query) {
+            info!("{} has no room for {}", truck.name, package.tracking_id);
+            continue;
+        }
+        let occupied = truck.occupied_space(truck_transform, &transforms, &container_query);
+        let Some((_, slot)) = free_cargo_slot(&occupied) else {
+            continue;
+        };
+
+        // The load itself is picked up by `update_truck_loads` once the box is in the trailer.
+        let (_, rotation, _) = truck_transform.to_scale_rotation_translation();
+        package_transform.translation = truck_transform.transform_point(slot);
+        package_transform.rotation = rotation;
+    }
+}
+
+fn update_truck_loads(
+    rapier_context: Res<RapierContext>,
+    mut truck_query: Query<(Entity, &mut OutboundTruck)>,
+    package_entities: Query<Entity, With<Package>>,
+    package_query: Query<&Package>,
+    hold_query: HoldQuery,
+    container_query: Query<Entity, With<ShippingContainer>>,
+    mut loaded: EventWriter<PackageLoaded>,
+) {
+    for (truck_entity, mut truck) in truck_query.iter_mut() {
+        // Packages that were taken back out, or no longer exist, come off the manifest, and so do
+        // ones that went on hold after they were loaded.
+        truck.manifest.retain(|package| {
+            rapier_context.intersection_pair(truck_entity, *package) == Some(true)
+                && !on_hold(&hold_query, *package)
+        });
+        truck.containers = container_query
+            .iter()
+            .filter(|container| {
+                rapier_context.intersection_pair(truck_entity, *container) == Some(true)
+            })
+            .collect();
+
+        // A reserved slot is free again once whatever it was held for is aboard, or gone.
+        let aboard: Vec<Entity> = truck
+            .reserved
+            .keys()
+            .copied()
+            .filter(|entity| {
+                truck.containers.contains(entity)
+                    || rapier_context.intersection_pair(truck_entity, *entity) == Some(true)
+                    || !(package_entities.contains(*entity) || container_query.contains(*entity))
+            })
+            .collect();
+        for entity in aboard {
+            truck.release(entity);
+        }
+
+        for package_entity in package_entities.iter() {
+            if truck.manifest.contains(&package_entity)
+                || rapier_context.intersection_pair(truck_entity, package_entity) != Some(true)
+                || on_hold(&hold_query, package_entity)
+            {
+                continue;
+            }
+            let Ok(package) = package_query.get(package_entity) else {
+                continue;
+            };
+            if !truck.has_room_for(package, &package_query) {
+                continue;
+            }
+
+            truck.manifest.push(package_entity);
+            loaded.send(PackageLoaded {
+                package: package_entity,
+                truck: truck.name.clone(),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn depart_trucks(
+    mut commands: Commands,
+    clock: Res<ShiftClock>,
+    mut wallet: ResMut<Wallet>,
+    mut truck_query: Query<(&mut OutboundTruck, &GlobalTransform)>,
+    package_query: Query<(&Package, &GlobalTransform)>,
+    hold_query: HoldQuery,
+    mut container_query: Query<(&mut ShippingContainer, Option<&Children>)>,
+    mut log: ResMut<TrackingLog>,
+    mut departed_packages: EventWriter<PackageDeparted>,
+    mut departed_trucks: EventWriter<TruckDeparted>,
+) {
+    let now = clock.now();
+
+    for (mut truck, truck_transform) in truck_query.iter_mut() {
+        if now < truck.departs_at {
+            continue;
+        }
+
+        let to_local = truck_transform.compute_matrix().inverse();
+        let mut placements = Vec::new();
+        let mut late_packages = 0;
+
+        // Containers go with the truck, along with everything in them. Their contents are
+        // normally on the manifest already, but one that didn't fit the load still leaves. One
+        // that's on hold is taken out and stays behind in the trailer.
+        let containers = std::mem::take(&mut truck.containers);
+        let mut iter = container_query.iter_many_mut(&containers);
+        while let Some((mut container, children)) = iter.fetch_next() {
+            for child in children.into_iter().flatten() {
+                let Ok((package, _)) = package_query.get(*child) else {
+                    continue;
+                };
+                if on_hold(&hold_query, *child) {
+                    take_out(
+                        &mut commands,
+                        &mut container,
+                        *child,
+                        package,
+                        &clock,
+                        &mut log,
+                    );
+                } else if !truck.manifest.contains(child) {
+                    truck.manifest.push(*child);
+                }
+            }
+        }
+
+        for package_entity in std::mem::take(&mut truck.manifest) {
+            let Ok((package, package_transform)) = package_query.get(package_entity) else {
+                continue;
+            };
+
+            placements.push((
+                to_local.transform_point3(package_transform.translation()),
+                package.weight,
+            ));
+
+            let late = now > package.sort_deadline();
+            if late {
+                late_packages += 1;
+            }
+            wallet.debit(package.late_refund(package.estimated_delivery_day(now)));
+
+            departed_packages.send(PackageDeparted {
+                tracking_id: package.tracking_id.clone(),
+                truck: truck.name.clone(),
+                late,
+            });
+            commands.entity(package_entity).despawn_recursive();
+        }
+
+        for container in containers {
+            commands.entity(container).despawn_recursive();
+        }
+
+        let quality = load_quality(truck.max_volume, &placements);
+        info!(
+            "{} left {} with {} packages ({} late), load score {:.2}",
+            truck.name,
+            truck.dock,
+            placements.len(),
+            late_packages,
+            quality.score
+        );
+        departed_trucks.send(TruckDeparted {
+            truck: truck.name.clone(),
+            dock: truck.dock.clone(),
+            packages: placements.len(),
+            late_packages,
+            quality,
+        });
+
+        // The next truck backs into the same dock straight away. After a jump in the clock the
+        // runs that were missed are skipped rather than each leaving empty.
+        let missed = ((now - truck.departs_at) / DEPARTURE_INTERVAL_HOURS).floor() + 1.0;
+        truck.departs_at += missed * DEPARTURE_INTERVAL_HOURS;
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+
+pub struct ShiftClockPlugin;
+
+impl Plugin for ShiftClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShiftClock>()
+            .add_systems(Update, tick_shift_clock.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// In-game calendar. Package deadlines are expressed in absolute hours since day 0, which is what
+// `ShiftClock::now` returns.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ShiftClock {
+    pub day: u32,
+    pub hour: f32,
+    pub game_hours_per_second: f32,
+}
+
+impl Default for ShiftClock {
+    fn default() -> Self {
+        ShiftClock {
+            day: 0,
+            hour: 6.0,
+            // One in-game hour per real minute, so an eight hour shift takes eight minutes.
+            game_hours_per_second: 1.0 / 60.0,
+        }
+    }
+}
+
+impl ShiftClock {
+    pub fn now(&self) -> f32 {
+        self.day as f32 * 24.0 + self.hour
+    }
+}
+
+// Formats absolute hours as "Day 3 14:05".
+pub fn format_timestamp(hours: f32) -> String {
+    let day = (hours / 24.0).floor();
+    let hour_of_day = hours - day * 24.0;
+    let minutes = (hour_of_day.fract() * 60.0).floor();
+
+    format!("Day {} {:02}:{:02}", day, hour_of_day.floor(), minutes)
+}
+
+fn tick_shift_clock(mut clock: ResMut<ShiftClock>, time: Res<Time>) {
+    clock.hour += time.delta_seconds() * clock.game_hours_per_second;
+
+    while clock.hour >= 24.0 {
+        clock.hour -= 24.0;
+        clock.day += 1;
+    }
+}
@@ -0,0 +1,235 @@
+// The shipping label on top of every package, drawn from its routing data: a band in the
+// service's color, a stripe down the side for international mail, the tracking number as a
+// barcode, the ZIP code as POSTNET bars and a diamond for hazmat. Printing a new label (see
+// `label_printer`) redraws it, so a relabeled package looks relabeled.
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::f32::consts::FRAC_PI_2;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::{Package, ServiceLevel, HOME_COUNTRY};
+use crate::player::items::label_printer::RoutingLabel;
+
+pub struct ShippingLabelPlugin;
+
+impl Plugin for ShippingLabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (stick_on_labels, redraw_labels)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+const WIDTH: usize = 192;
+const HEIGHT: usize = 96;
+// On the box's top face, which is 1.25m across, and just above it so it doesn't flicker.
+const LABEL_SIZE: Vec2 = Vec2::new(0.8, 0.4);
+const LABEL_HEIGHT: f32 = 0.63;
+
+const PAPER: [u8; 4] = [245, 244, 236, 255];
+const INK: [u8; 4] = [20, 20, 24, 255];
+const INTERNATIONAL: [u8; 4] = [40, 90, 200, 255];
+const HAZMAT: [u8; 4] = [215, 30, 30, 255];
+
+const BAND_HEIGHT: usize = 14;
+const STRIPE_WIDTH: usize = 8;
+const BARCODE_TOP: usize = 22;
+const BARCODE_BOTTOM: usize = 58;
+const POSTNET_TOP: usize = 68;
+const POSTNET_SHORT_TOP: usize = 76;
+const POSTNET_BOTTOM: usize = 84;
+const MARGIN: usize = 16;
+const HAZMAT_CENTER: (isize, isize) = (174, 76);
+const HAZMAT_RADIUS: isize = 14;
+
+// Which of each digit's five bars are wide, in "2 of 5".
+const TWO_OF_FIVE: [[bool; 5]; 10] = [
+    [false, false, true, true, false],
+    [true, false, false, false, true],
+    [false, true, false, false, true],
+    [true, true, false, false, false],
+    [false, false, true, false, true],
+    [true, false, true, false, false],
+    [false, true, true, false, false],
+    [false, false, false, true, true],
+    [true, false, false, true, false],
+    [false, true, false, true, false],
+];
+
+// Which of each digit's five bars are tall, in POSTNET.
+const POSTNET: [[bool; 5]; 10] = [
+    [true, true, false, false, false],
+    [false, false, false, true, true],
+    [false, false, true, false, true],
+    [false, false, true, true, false],
+    [false, true, false, false, true],
+    [false, true, false, true, false],
+    [false, true, true, false, false],
+    [true, false, false, false, true],
+    [true, false, false, true, false],
+    [true, false, true, false, false],
+];
+
+// On packages, with the label as it was last drawn.
+#[derive(Component, Clone, Debug)]
+pub struct ShippingLabel {
+    image: Handle<Image>,
+    drawn: RoutingLabel,
+}
+
+fn service_color(service_level: ServiceLevel) -> [u8; 4] {
+    match service_level {
+        ServiceLevel::Overnight => [200, 40, 40, 255],
+        ServiceLevel::TwoDay => [230, 150, 30, 255],
+        ServiceLevel::Ground => [120, 85, 50, 255],
+        ServiceLevel::InternationalEconomy => [40, 90, 200, 255],
+    }
+}
+
+struct Canvas {
+    data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Canvas {
+            data: PAPER.repeat(WIDTH * HEIGHT),
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 4]) {
+        for row in y..(y + height).min(HEIGHT) {
+            for column in x..(x + width).min(WIDTH) {
+                let index = (row * WIDTH + column) * 4;
+                self.data[index..index + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn digits(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|digit| digit as usize)
+}
+
+fn draw_label(tracking_id: &str, label: &RoutingLabel) -> Vec<u8> {
+    let mut canvas = Canvas::new();
+
+    canvas.fill(0, 0, WIDTH, BAND_HEIGHT, service_color(label.service_level));
+    if label.country != HOME_COUNTRY {
+        canvas.fill(0, BAND_HEIGHT, STRIPE_WIDTH, HEIGHT, INTERNATIONAL);
+    }
+
+    // Narrow bars are one pixel, wide ones two, with a one pixel gap after each.
+    let barcode_height = BARCODE_BOTTOM - BARCODE_TOP;
+    let mut x = MARGIN;
+    for digit in digits(tracking_id) {
+        for wide in TWO_OF_FIVE[digit] {
+            let width = if wide { 2 } else { 1 };
+            canvas.fill(x, BARCODE_TOP, width, barcode_height, INK);
+            x += width + 1;
+        }
+    }
+
+    // A label without a ZIP code has no bars for it, which is easy to spot on the belt.
+    if let Some(zip_code) = label.zip_code {
+        let mut x = MARGIN;
+        let mut bar = |canvas: &mut Canvas, tall: bool| {
+            let top = if tall { POSTNET_TOP } else { POSTNET_SHORT_TOP };
+            canvas.fill(x, top, 1, POSTNET_BOTTOM - top, INK);
+            x += 3;
+        };
+        bar(&mut canvas, true);
+        for digit in digits(&zip_code.to_string()) {
+            for tall in POSTNET[digit] {
+                bar(&mut canvas, tall);
+            }
+        }
+        bar(&mut canvas, true);
+    }
+
+    if label.hazmat {
+        let (center_x, center_y) = HAZMAT_CENTER;
+        for y in -HAZMAT_RADIUS..=HAZMAT_RADIUS {
+            let half_width = HAZMAT_RADIUS - y.abs();
+            let left = (center_x - half_width) as usize;
+            let row = (center_y + y) as usize;
+            canvas.fill(left, row, (half_width * 2 + 1) as usize, 1, HAZMAT);
+        }
+    }
+
+    canvas.data
+}
+
+fn label_image(tracking_id: &str, label: &RoutingLabel) -> Image {
+    Image::new(
+        Extent3d {
+            width: WIDTH as u32,
+            height: HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        draw_label(tracking_id, label),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn stick_on_labels(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    package_query: Query<(Entity, &Package), Added<Package>>,
+    mut label_mesh: Local<Option<Handle<Mesh>>>,
+) {
+    for (entity, package) in package_query.iter() {
+        let drawn = RoutingLabel::from_package(package);
+        let image = images.add(label_image(&package.tracking_id, &drawn));
+        let mesh = label_mesh
+            .get_or_insert_with(|| meshes.add(Rectangle::from_size(LABEL_SIZE)))
+            .clone();
+        let material = materials.add(StandardMaterial {
+            base_color_texture: Some(image.clone()),
+            perceptual_roughness: 0.9,
+            ..default()
+        });
+
+        // The quad faces +Z; lying it down puts the top of the label towards the back of the box.
+        let label = commands
+            .spawn(PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_xyz(0.0, LABEL_HEIGHT, 0.0)
+                    .with_rotation(Quat::from_rotation_x(-FRAC_PI_2)),
+                ..default()
+            })
+            .id();
+        commands
+            .entity(entity)
+            .insert(ShippingLabel { image, drawn })
+            .add_child(label);
+    }
+}
+
+fn redraw_labels(
+    mut images: ResMut<Assets<Image>>,
+    mut package_query: Query<(&Package, &mut ShippingLabel), Changed<Package>>,
+) {
+    for (package, mut shipping_label) in package_query.iter_mut() {
+        let label = RoutingLabel::from_package(package);
+        if label == shipping_label.drawn {
+            continue;
+        }
+        if let Some(image) = images.get_mut(&shipping_label.image) {
+            image.data = draw_label(&package.tracking_id, &label);
+        }
+        shipping_label.drawn = label;
+    }
+}
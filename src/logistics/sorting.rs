@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::markers::ChuteInput;
+use crate::levels::package_data::Package;
+
+pub struct SortingPlugin;
+
+impl Plugin for SortingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PackageSorted>().add_systems(
+            Update,
+            sort_into_chutes.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// How close a package has to get to a chute input to count as dropped into it.
+const CHUTE_RADIUS: f32 = 1.0;
+
+// The chute a package was last sorted into.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct SortedTo {
+    pub chute: String,
+}
+
+// Chutes don't carry a destination of their own yet, so routes are spread over them in name order:
+// domestic packages by zip code, international ones by country.
+pub fn destination_chute<'a>(package: &Package, chutes: &[&'a str]) -> Option<&'a str> {
+    if chutes.is_empty() {
+        return None;
+    }
+
+    let route = match package.zip_code {
+        Some(zip_code) if !package.is_international() => zip_code as usize,
+        _ => package.country.bytes().map(usize::from).sum(),
+    };
+
+    Some(chutes[route % chutes.len()])
+}
+
+// Floor-level drop chutes are named "Chute_Drop...". Robots can only reach those, so they sort over
+// just them.
+pub fn is_drop_chute(name: &str) -> bool {
+    name.to_lowercase().contains("drop")
+}
+
+// Whether `chute` is where the sort plan sends `package`, either over every chute or over the drop
+// chutes only. `chutes` is every chute name in the level.
+pub fn is_correct_chute(package: &Package, chute: &str, chutes: &[&str]) -> bool {
+    let mut chutes = chutes.to_vec();
+    chutes.sort();
+    let drop_chutes: Vec<&str> = chutes
+        .iter()
+        .copied()
+        .filter(|name| is_drop_chute(name))
+        .collect();
+
+    destination_chute(package, &chutes) == Some(chute)
+        || (is_drop_chute(chute) && destination_chute(package, &drop_chutes) == Some(chute))
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PackageSorted {
+    pub package: Entity,
+    pub chute: String,
+}
+
+fn sort_into_chutes(
+    mut commands: Commands,
+    chute_query: Query<(&ChuteInput, &GlobalTransform)>,
+    package_query: Query<(Entity, &GlobalTransform, Option<&SortedTo>), With<Package>>,
+    mut sorted: EventWriter<PackageSorted>,
+) {
+    for (package, package_transform, sorted_to) in package_query.iter() {
+        let Some((chute, _)) = chute_query.iter().find(|(_, chute_transform)| {
+            chute_transform
+                .translation()
+                .distance(package_transform.translation())
+                < CHUTE_RADIUS
+        }) else {
+            continue;
+        };
+
+        if sorted_to.is_some_and(|sorted_to| sorted_to.chute == chute.name) {
+            continue;
+        }
+
+        commands.entity(package).insert(SortedTo {
+            chute: chute.name.clone(),
+        });
+        sorted.send(PackageSorted {
+            package,
+            chute: chute.name.clone(),
+        });
+    }
+}
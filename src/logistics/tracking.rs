@@ -0,0 +1,249 @@
+// Tracking history for every package, keyed by tracking ID. Other systems report what happens to
+// a package through their own events; this module turns those into the timeline a customer would
+// see, and keeps it around after the package entity itself is gone.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt;
+
+use crate::levels::package_data::Package;
+use crate::logistics::address::{AddressException, AddressExceptionResolved, ExceptionStatus};
+use crate::logistics::customs::{CustomsDecision, CustomsDecisionMade};
+use crate::logistics::outbound::{PackageDeparted, PackageLoaded};
+use crate::logistics::shift::{format_timestamp, ShiftClock};
+use crate::logistics::sorting::PackageSorted;
+use crate::player::items::scanner::PackageScanned;
+
+pub struct TrackingPlugin;
+
+impl Plugin for TrackingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrackingLog>().add_systems(
+            Update,
+            (
+                record_intake,
+                record_scans,
+                record_sorting,
+                record_address_exceptions,
+                record_customs_decisions,
+                record_outbound,
+            ),
+        );
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TrackingEventKind {
+    Created,
+    ArrivedAtFacility,
+    Scanned,
+    SortedToDestination(String),
+    Consolidated(String),
+    Deconsolidated(String),
+    Loaded(String),
+    Departed(String),
+    Damaged,
+    Repaired,
+    Relabeled {
+        field: String,
+        from: String,
+        to: String,
+    },
+    Exception(String),
+    Customs(String),
+}
+
+impl fmt::Display for TrackingEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrackingEventKind::Created => write!(f, "Label created"),
+            TrackingEventKind::ArrivedAtFacility => write!(f, "Arrived at facility"),
+            TrackingEventKind::Scanned => write!(f, "Scanned"),
+            TrackingEventKind::SortedToDestination(chute) => write!(f, "Sorted to {}", chute),
+            TrackingEventKind::Consolidated(container) => {
+                write!(f, "Consolidated into {}", container)
+            }
+            TrackingEventKind::Deconsolidated(container) => {
+                write!(f, "Removed from {}", container)
+            }
+            TrackingEventKind::Loaded(vehicle) => write!(f, "Loaded onto {}", vehicle),
+            TrackingEventKind::Departed(vehicle) => write!(f, "Departed on {}", vehicle),
+            TrackingEventKind::Damaged => write!(f, "Damaged in handling"),
+            TrackingEventKind::Repaired => write!(f, "Repackaged"),
+            TrackingEventKind::Relabeled { field, from, to } => {
+                write!(f, "Relabeled: {} {} -> {}", field, from, to)
+            }
+            TrackingEventKind::Exception(reason) => write!(f, "Exception: {}", reason),
+            TrackingEventKind::Customs(status) => write!(f, "Customs: {}", status),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TrackingEvent {
+    pub kind: TrackingEventKind,
+    // Absolute hours, see `ShiftClock::now`.
+    pub at: f32,
+}
+
+impl fmt::Display for TrackingEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  {}", format_timestamp(self.at), self.kind)
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct TrackingLog {
+    histories: HashMap<String, Vec<TrackingEvent>>,
+}
+
+impl TrackingLog {
+    pub fn record(&mut self, tracking_id: &str, kind: TrackingEventKind, at: f32) {
+        self.histories
+            .entry(tracking_id.to_string())
+            .or_default()
+            .push(TrackingEvent { kind, at });
+    }
+
+    pub fn history(&self, tracking_id: &str) -> Option<&[TrackingEvent]> {
+        self.histories.get(tracking_id).map(Vec::as_slice)
+    }
+
+    // Every package whose history contains an event matching `predicate`.
+    pub fn find<'a>(
+        &'a self,
+        predicate: impl Fn(&TrackingEvent) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.histories
+            .iter()
+            .filter(move |(_, history)| history.iter().any(&predicate))
+            .map(|(tracking_id, _)| tracking_id.as_str())
+    }
+}
+
+fn record_intake(
+    package_query: Query<&Package, Added<Package>>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for package in package_query.iter() {
+        log.record(&package.tracking_id, TrackingEventKind::Created, clock.now());
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::ArrivedAtFacility,
+            clock.now(),
+        );
+    }
+}
+
+fn record_scans(
+    mut scans: EventReader<PackageScanned>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for scan in scans.read() {
+        if let Ok(package) = package_query.get(scan.package) {
+            log.record(&package.tracking_id, TrackingEventKind::Scanned, clock.now());
+        }
+    }
+}
+
+fn record_sorting(
+    mut sorted: EventReader<PackageSorted>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for event in sorted.read() {
+        if let Ok(package) = package_query.get(event.package) {
+            log.record(
+                &package.tracking_id,
+                TrackingEventKind::SortedToDestination(event.chute.clone()),
+                clock.now(),
+            );
+        }
+    }
+}
+
+fn record_address_exceptions(
+    flagged_query: Query<(&Package, &AddressException), Added<AddressException>>,
+    mut resolutions: EventReader<AddressExceptionResolved>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for (package, exception) in flagged_query.iter() {
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::Exception(format!("{:?}", exception.issues)),
+            clock.now(),
+        );
+    }
+
+    for resolution in resolutions.read() {
+        let Ok(package) = package_query.get(resolution.package) else {
+            continue;
+        };
+        let reason = match resolution.status {
+            ExceptionStatus::Open => continue,
+            ExceptionStatus::Held => "held at exception desk",
+            ExceptionStatus::Corrected => "address corrected",
+            ExceptionStatus::ReturnedToSender => "returned to sender",
+        };
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::Exception(reason.to_string()),
+            clock.now(),
+        );
+    }
+}
+
+fn record_customs_decisions(
+    mut decisions: EventReader<CustomsDecisionMade>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for decision in decisions.read() {
+        let Ok(package) = package_query.get(decision.package) else {
+            continue;
+        };
+        let reason = match decision.decision {
+            CustomsDecision::Hold => "held for inspection",
+            CustomsDecision::Clear => "cleared",
+            CustomsDecision::Return => "refused, returned to sender",
+        };
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::Customs(reason.to_string()),
+            clock.now(),
+        );
+    }
+}
+
+fn record_outbound(
+    mut loaded: EventReader<PackageLoaded>,
+    mut departed: EventReader<PackageDeparted>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    for event in loaded.read() {
+        if let Ok(package) = package_query.get(event.package) {
+            log.record(
+                &package.tracking_id,
+                TrackingEventKind::Loaded(event.truck.clone()),
+                clock.now(),
+            );
+        }
+    }
+
+    for event in departed.read() {
+        log.record(
+            &event.tracking_id,
+            TrackingEventKind::Departed(event.truck.clone()),
+            clock.now(),
+        );
+    }
+}
@@ -1,21 +1,41 @@
 use bevy::{prelude::*, ui::SetUiViewBindGroup};
-use bevy_framepace::Limiter;
 use bevy_rapier3d::prelude::RapierDebugRenderPlugin;
+use courier::actions::ActionsPlugin;
+use courier::analytics::AnalyticsPlugin;
+use courier::automation::AutomationPlugin;
+use courier::building::BuildingPlugin;
+use courier::game_state::GameStatePlugin;
+use courier::lighting::LightingPlugin;
+use courier::logistics::LogisticsPlugin;
+use courier::navigation::NavigationPlugin;
 use courier::player::controller::CharacterController;
+use courier::settings::SettingsPlugin;
+use courier::sound::SoundPlugin;
+use courier::staff::StaffPlugin;
+use courier::ui::GameUiPlugin;
+use courier::vehicles::VehiclesPlugin;
 use std::env;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(RapierDebugRenderPlugin::default())
+        .add_plugins(GameStatePlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(ActionsPlugin)
         .add_plugins(CharacterController)
+        .add_plugins(LogisticsPlugin)
+        .add_plugins(GameUiPlugin)
+        .add_plugins(NavigationPlugin)
+        .add_plugins(VehiclesPlugin)
+        .add_plugins(StaffPlugin)
+        .add_plugins(AutomationPlugin)
+        .add_plugins(AnalyticsPlugin)
+        .add_plugins(BuildingPlugin)
+        .add_plugins(LightingPlugin)
         .add_plugins(bevy_framepace::FramepacePlugin)
-        .add_systems(Update, frame_pace)
         .run();
 
     env::set_var("RUST_BACKTRACE", "1");
 }
-
-fn frame_pace(mut settings: ResMut<bevy_framepace::FramepaceSettings>) {
-    settings.limiter = Limiter::Auto;
-}
@@ -0,0 +1,429 @@
+// Walkability grid for workers and robots, rasterized from the level's meshes (the same ones
+// `generate_gltf_colliders` turns into colliders). Each cell remembers the floor height under it
+// and whether the level geometry leaves room to stand there. Placed equipment is stamped on top
+// and restamped in place whenever it moves, so after load the grid only ever changes locally.
+
+use bevy::gltf::{Gltf, GltfMesh, GltfNode};
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::utils::HashMap;
+use std::collections::VecDeque;
+
+use crate::levels::asset_loader_plugin::MyAssetPack;
+use crate::tools::gltf::level_meshes;
+
+pub const CELL_SIZE: f32 = 0.5;
+// Walls and equipment are padded by this much so agents don't scrape along them.
+pub const AGENT_RADIUS: f32 = 0.4;
+pub const STEP_HEIGHT: f32 = 0.35;
+const AGENT_HEIGHT: f32 = 1.8;
+// Surfaces steeper than about 35 degrees are walls, not floor.
+const MIN_FLOOR_NORMAL_Y: f32 = 0.82;
+// How many revisions' worth of changed cells are kept for agents checking their paths.
+const CHANGE_LOG_LENGTH: usize = 64;
+
+// Placed equipment that agents have to walk around, with the half extents of its footprint.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NavObstacle {
+    pub half_extents: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct NavCell {
+    // Lowest walkable surface in the cell. Upper floors and mezzanines aren't supported yet.
+    pub floor: Option<f32>,
+    pub geometry_blocked: bool,
+    pub equipment_blocked: bool,
+}
+
+impl NavCell {
+    pub fn is_walkable(&self) -> bool {
+        self.floor.is_some() && !self.geometry_blocked && !self.equipment_blocked
+    }
+}
+
+// A piece of equipment's rectangle on the floor.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Footprint {
+    center: Vec3,
+    inverse_rotation: Quat,
+    half_extents: Vec2,
+}
+
+impl Footprint {
+    fn new(transform: &GlobalTransform, half_extents: Vec2) -> Self {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        Footprint {
+            center: translation,
+            inverse_rotation: rotation.inverse(),
+            half_extents,
+        }
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        let local = self.inverse_rotation * (point - self.center);
+        let reach = self.half_extents + AGENT_RADIUS;
+        local.x.abs() <= reach.x && local.z.abs() <= reach.y
+    }
+
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let reach = (self.half_extents + AGENT_RADIUS).length();
+        (self.center.xz() - reach, self.center.xz() + reach)
+    }
+
+    // Ignores jitter from physics so resting equipment doesn't restamp every frame.
+    fn moved_from(&self, other: &Footprint) -> bool {
+        self.center.distance(other.center) > 0.05
+            || self.inverse_rotation.angle_between(other.inverse_rotation) > 0.05
+            || self.half_extents != other.half_extents
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct NavGrid {
+    origin: Vec2,
+    width: i32,
+    depth: i32,
+    cells: Vec<NavCell>,
+    obstacles: HashMap<Entity, Footprint>,
+    // Bumped whenever cells change, so agents know their paths may be stale.
+    pub revision: u32,
+    // The cells each of the latest revisions changed, oldest first.
+    changes: VecDeque<Vec<IVec2>>,
+}
+
+impl NavGrid {
+    pub fn from_triangles(triangles: &[[Vec3; 3]]) -> Self {
+        let floors: Vec<&[Vec3; 3]> = triangles
+            .iter()
+            .filter(|triangle| triangle_normal(triangle).y >= MIN_FLOOR_NORMAL_Y)
+            .collect();
+        if floors.is_empty() {
+            return NavGrid::default();
+        }
+
+        let (min, max) = floors.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), triangle| {
+                let (triangle_min, triangle_max) = triangle_bounds(triangle);
+                (min.min(triangle_min), max.max(triangle_max))
+            },
+        );
+        let size = ((max - min) / CELL_SIZE).ceil().as_ivec2() + 1;
+
+        let mut grid = NavGrid {
+            origin: min,
+            width: size.x,
+            depth: size.y,
+            cells: vec![NavCell::default(); (size.x * size.y) as usize],
+            ..default()
+        };
+
+        for triangle in &floors {
+            let (triangle_min, triangle_max) = triangle_bounds(triangle);
+            for cell in grid.cells_in(triangle_min, triangle_max) {
+                let point = grid.cell_center(cell).xz();
+                let Some(height) = height_on_triangle(triangle, point) else {
+                    continue;
+                };
+                let index = grid.index(cell).unwrap();
+                let floor = &mut grid.cells[index].floor;
+                *floor = Some(floor.map_or(height, |floor| floor.min(height)));
+            }
+        }
+
+        // Anything between step and head height above the floor is in the way.
+        for triangle in triangles {
+            let (triangle_min, triangle_max) = triangle_bounds(triangle);
+            let low = triangle
+                .iter()
+                .map(|vertex| vertex.y)
+                .fold(f32::INFINITY, f32::min);
+            let high = triangle
+                .iter()
+                .map(|vertex| vertex.y)
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            let padding = Vec2::splat(AGENT_RADIUS);
+            for cell in grid.cells_in(triangle_min - padding, triangle_max + padding) {
+                let index = grid.index(cell).unwrap();
+                let Some(floor) = grid.cells[index].floor else {
+                    continue;
+                };
+                if high <= floor + STEP_HEIGHT || low >= floor + AGENT_HEIGHT {
+                    continue;
+                }
+                let point = grid.cell_center(cell).xz();
+                if distance_to_triangle(triangle, point) <= AGENT_RADIUS {
+                    grid.cells[index].geometry_blocked = true;
+                }
+            }
+        }
+
+        grid
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cell_at(&self, position: Vec3) -> Option<IVec2> {
+        let cell = ((position.xz() - self.origin) / CELL_SIZE)
+            .round()
+            .as_ivec2();
+        self.index(cell).map(|_| cell)
+    }
+
+    // World position of the middle of a cell, standing on its floor.
+    pub fn cell_center(&self, cell: IVec2) -> Vec3 {
+        let xz = self.origin + cell.as_vec2() * CELL_SIZE;
+        let floor = self.get(cell).and_then(|cell| cell.floor).unwrap_or(0.0);
+        Vec3::new(xz.x, floor, xz.y)
+    }
+
+    pub fn get(&self, cell: IVec2) -> Option<&NavCell> {
+        self.index(cell).map(|index| &self.cells[index])
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        self.get(cell).is_some_and(NavCell::is_walkable)
+    }
+
+    // Closest walkable cell to `position`, searching outward ring by ring.
+    pub fn nearest_walkable(&self, position: Vec3, max_rings: i32) -> Option<IVec2> {
+        let clamped = ((position.xz() - self.origin) / CELL_SIZE)
+            .round()
+            .as_ivec2()
+            .clamp(IVec2::ZERO, IVec2::new(self.width - 1, self.depth - 1));
+
+        (0..=max_rings).find_map(|ring| {
+            let mut ring_cells = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |y| IVec2::new(x, y)))
+                .filter(|offset| offset.x.abs() == ring || offset.y.abs() == ring)
+                .map(|offset| clamped + offset)
+                .filter(|cell| self.is_walkable(*cell))
+                .collect::<Vec<_>>();
+            ring_cells.sort_by(|a, b| {
+                let a = self.cell_center(*a).xz().distance(position.xz());
+                let b = self.cell_center(*b).xz().distance(position.xz());
+                a.total_cmp(&b)
+            });
+            ring_cells.first().copied()
+        })
+    }
+
+    // Cells whose centers fall inside the given rectangle on the floor.
+    pub fn cells_in(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = IVec2> {
+        let low = ((min - self.origin) / CELL_SIZE)
+            .ceil()
+            .as_ivec2()
+            .max(IVec2::ZERO);
+        let high = ((max - self.origin) / CELL_SIZE)
+            .floor()
+            .as_ivec2()
+            .min(IVec2::new(self.width - 1, self.depth - 1));
+
+        (low.y..=high.y).flat_map(move |y| (low.x..=high.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    // Every cell changed since `revision`, or `None` if that's further back than the log goes.
+    pub fn changed_since(&self, revision: u32) -> Option<impl Iterator<Item = IVec2> + '_> {
+        let missed = self.revision.wrapping_sub(revision) as usize;
+        if missed > self.changes.len() {
+            return None;
+        }
+        Some(
+            self.changes
+                .iter()
+                .skip(self.changes.len() - missed)
+                .flatten()
+                .copied(),
+        )
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let inside = cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.depth;
+        inside.then(|| (cell.y * self.width + cell.x) as usize)
+    }
+
+    // Moves, adds or (with `None`) removes a piece of equipment, restamping only the cells under
+    // its old and new footprints. The revision only moves if a cell actually changed, which it
+    // often doesn't for equipment nudged within the cells it already covered.
+    fn set_obstacle(&mut self, entity: Entity, footprint: Option<Footprint>) {
+        let previous = self.obstacles.get(&entity).copied();
+        let unchanged = match (previous, footprint) {
+            (Some(previous), Some(footprint)) => !footprint.moved_from(&previous),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged || self.is_empty() {
+            return;
+        }
+
+        match footprint {
+            Some(footprint) => self.obstacles.insert(entity, footprint),
+            None => self.obstacles.remove(&entity),
+        };
+
+        let mut changed = Vec::new();
+        for (min, max) in [previous, footprint]
+            .iter()
+            .flatten()
+            .map(Footprint::bounds)
+        {
+            let nearby: Vec<Footprint> = self
+                .obstacles
+                .values()
+                .filter(|other| {
+                    let (other_min, other_max) = other.bounds();
+                    other_min.cmple(max).all() && other_max.cmpge(min).all()
+                })
+                .copied()
+                .collect();
+
+            for cell in self.cells_in(min, max).collect::<Vec<_>>() {
+                let center = self.cell_center(cell);
+                let index = self.index(cell).unwrap();
+                let blocked = nearby.iter().any(|other| other.contains(center));
+                if self.cells[index].equipment_blocked != blocked {
+                    self.cells[index].equipment_blocked = blocked;
+                    changed.push(cell);
+                }
+            }
+        }
+
+        if changed.is_empty() {
+            return;
+        }
+        self.revision = self.revision.wrapping_add(1);
+        self.changes.push_back(changed);
+        if self.changes.len() > CHANGE_LOG_LENGTH {
+            self.changes.pop_front();
+        }
+    }
+}
+
+fn triangle_normal(triangle: &[Vec3; 3]) -> Vec3 {
+    (triangle[1] - triangle[0])
+        .cross(triangle[2] - triangle[0])
+        .normalize_or_zero()
+}
+
+fn triangle_bounds(triangle: &[Vec3; 3]) -> (Vec2, Vec2) {
+    let xz = triangle.map(|vertex| vertex.xz());
+    (xz[0].min(xz[1]).min(xz[2]), xz[0].max(xz[1]).max(xz[2]))
+}
+
+// Barycentric weights of `point` in the triangle seen from above, if it's inside.
+fn barycentric(triangle: &[Vec3; 3], point: Vec2) -> Option<Vec3> {
+    let [a, b, c] = triangle.map(|vertex| vertex.xz());
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+
+    let denominator = ab.perp_dot(ac);
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+    let v = ap.perp_dot(ac) / denominator;
+    let w = ab.perp_dot(ap) / denominator;
+    let u = 1.0 - v - w;
+
+    let inside = u >= -1e-4 && v >= -1e-4 && w >= -1e-4;
+    inside.then_some(Vec3::new(u, v, w))
+}
+
+fn height_on_triangle(triangle: &[Vec3; 3], point: Vec2) -> Option<f32> {
+    barycentric(triangle, point)
+        .map(|weights| weights.dot(Vec3::new(triangle[0].y, triangle[1].y, triangle[2].y)))
+}
+
+// Distance from `point` to the triangle seen from above; zero inside it.
+fn distance_to_triangle(triangle: &[Vec3; 3], point: Vec2) -> f32 {
+    if barycentric(triangle, point).is_some() {
+        return 0.0;
+    }
+
+    let xz = triangle.map(|vertex| vertex.xz());
+    [(xz[0], xz[1]), (xz[1], xz[2]), (xz[2], xz[0])]
+        .into_iter()
+        .map(|(start, end)| {
+            let edge = end - start;
+            let along =
+                ((point - start).dot(edge) / edge.length_squared().max(1e-6)).clamp(0.0, 1.0);
+            point.distance(start + edge * along)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn mesh_triangles(mesh: &Mesh, transform: &Transform) -> Vec<[Vec3; 3]> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return Vec::new();
+    };
+
+    let vertices: Vec<Vec3> = positions
+        .iter()
+        .map(|position| transform.transform_point(Vec3::from_array(*position)))
+        .collect();
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..vertices.len()).collect(),
+    };
+
+    indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            [
+                vertices[triangle[0]],
+                vertices[triangle[1]],
+                vertices[triangle[2]],
+            ]
+        })
+        .collect()
+}
+
+pub fn build_nav_grid(
+    mut commands: Commands,
+    asset_pack: Res<MyAssetPack>,
+    gltf_assets: Res<Assets<Gltf>>,
+    gltf_mesh_assets: Res<Assets<GltfMesh>>,
+    gltf_node_assets: Res<Assets<GltfNode>>,
+    mesh_assets: Res<Assets<Mesh>>,
+) {
+    let Some(gltf) = gltf_assets.get(&asset_pack.main_scene) else {
+        return;
+    };
+
+    let triangles: Vec<[Vec3; 3]> =
+        level_meshes(gltf, &gltf_mesh_assets, &gltf_node_assets, &mesh_assets)
+            .into_iter()
+            .flat_map(|(mesh, transform)| mesh_triangles(mesh, &transform))
+            .collect();
+
+    let grid = NavGrid::from_triangles(&triangles);
+    info!("Navigation grid: {} x {} cells", grid.width, grid.depth);
+    commands.insert_resource(grid);
+}
+
+// Equipment that gets picked up (a container on a forklift) is carried by something else that's
+// already an obstacle, so it comes off the grid until it's set down again.
+pub fn sync_nav_obstacles(
+    mut grid: ResMut<NavGrid>,
+    obstacle_query: Query<
+        (Entity, &NavObstacle, &GlobalTransform, Option<&Parent>),
+        Changed<GlobalTransform>,
+    >,
+    mut removed: RemovedComponents<NavObstacle>,
+) {
+    for entity in removed.read() {
+        grid.set_obstacle(entity, None);
+    }
+
+    for (entity, obstacle, transform, parent) in obstacle_query.iter() {
+        let footprint = match parent {
+            Some(_) => None,
+            None => Some(Footprint::new(transform, obstacle.half_extents)),
+        };
+        grid.set_obstacle(entity, footprint);
+    }
+}
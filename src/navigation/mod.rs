@@ -0,0 +1,25 @@
+pub mod grid;
+pub mod pathfinding;
+
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+
+use self::grid::{build_nav_grid, sync_nav_obstacles, NavGrid};
+use self::pathfinding::{follow_paths, plan_paths};
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .add_systems(OnEnter(AssetLoaderState::Done), build_nav_grid)
+            .add_systems(
+                Update,
+                (sync_nav_obstacles, plan_paths, follow_paths)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
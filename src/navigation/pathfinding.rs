@@ -0,0 +1,349 @@
+// A* over the navigation grid. Packages lying around and other agents move too often to be part
+// of the grid, so each query stamps them into a scratch set instead. Anything near the start or the
+// goal is let through, since that's usually the agent itself or the thing it's walking up to.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::levels::package_data::Package;
+use crate::navigation::grid::{NavGrid, AGENT_RADIUS, CELL_SIZE, STEP_HEIGHT};
+
+// How many rings of cells to search for a walkable cell near the start or goal.
+const SNAP_RINGS: i32 = 8;
+// Obstacles this close to the start or goal are ignored.
+const ENDPOINT_CLEARANCE: f32 = 1.2;
+// Gives up rather than stall a frame on an unreachable goal.
+const MAX_EXPANDED_CELLS: usize = 20_000;
+
+// Agents replan this often even if nothing on their path changed, to get around whatever has
+// wandered into their way since.
+const REPLAN_SECONDS: f32 = 1.0;
+// A waypoint counts as reached this close, measured on the floor.
+const WAYPOINT_REACHED: f32 = CELL_SIZE;
+const PACKAGE_RADIUS: f32 = 1.0;
+
+const NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DynamicObstacle {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+// Something that walks or drives around using the navigation grid. Its owner sets `destination`
+// and moves toward `next_waypoint`; the path in between is kept up to date here.
+#[derive(Component, Clone, Debug, Default)]
+pub struct NavAgent {
+    pub destination: Option<Vec3>,
+    pub path: Vec<Vec3>,
+    planned_for: Option<Vec3>,
+    planned_at_revision: u32,
+    replan_in: f32,
+}
+
+impl NavAgent {
+    // Straight to the destination until a path has been planned for it, or if there's no grid.
+    pub fn next_waypoint(&self) -> Option<Vec3> {
+        if self.destination_moved() {
+            return self.destination;
+        }
+        self.path.first().copied().or(self.destination)
+    }
+
+    // Whether the destination is more than a cell away from the one the path was planned for.
+    // Targets jitter a little from frame to frame, which isn't worth a new path.
+    fn destination_moved(&self) -> bool {
+        match (self.planned_for, self.destination) {
+            (Some(planned_for), Some(destination)) => planned_for.distance(destination) > CELL_SIZE,
+            (planned_for, destination) => planned_for != destination,
+        }
+    }
+
+    fn needs_replan(&self, grid: &NavGrid, position: Vec3) -> bool {
+        if self.destination.is_none() {
+            return false;
+        }
+
+        self.destination_moved() || self.replan_in <= 0.0 || self.path_changed(grid, position)
+    }
+
+    // Whether any cell along what's left of the path changed since it was planned. Equipment moving
+    // elsewhere in the building is none of this agent's business.
+    fn path_changed(&self, grid: &NavGrid, position: Vec3) -> bool {
+        if self.planned_at_revision == grid.revision {
+            return false;
+        }
+        let Some(changed) = grid.changed_since(self.planned_at_revision) else {
+            return true;
+        };
+        let changed: HashSet<IVec2> = changed.collect();
+        // Without a path the agent heads straight for the destination, and any change might be
+        // the way through.
+        if self.path.is_empty() {
+            return !changed.is_empty();
+        }
+
+        let mut from = position;
+        for to in &self.path {
+            if grid
+                .cells_along(from, *to)
+                .any(|cell| changed.contains(&cell))
+            {
+                return true;
+            }
+            from = *to;
+        }
+        false
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct OpenCell {
+    estimate: f32,
+    cell: IVec2,
+}
+
+impl Eq for OpenCell {}
+
+// Reversed so the heap pops the cheapest estimate first.
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Octile distance, in cells.
+fn heuristic(from: IVec2, to: IVec2) -> f32 {
+    let delta = (to - from).abs();
+    let (long, short) = (delta.max_element() as f32, delta.min_element() as f32);
+    long + (std::f32::consts::SQRT_2 - 1.0) * short
+}
+
+impl NavGrid {
+    fn can_step(&self, from: IVec2, to: IVec2, blocked: &HashSet<IVec2>) -> bool {
+        if !self.is_walkable(to) || blocked.contains(&to) {
+            return false;
+        }
+        let (Some(from_floor), Some(to_floor)) = (
+            self.get(from).and_then(|cell| cell.floor),
+            self.get(to).and_then(|cell| cell.floor),
+        ) else {
+            return false;
+        };
+        (from_floor - to_floor).abs() <= STEP_HEIGHT
+    }
+
+    // The cells under a straight line, sampled every half cell.
+    fn cells_along(&self, from: Vec3, to: Vec3) -> impl Iterator<Item = IVec2> + '_ {
+        let samples = (from.xz().distance(to.xz()) / (CELL_SIZE * 0.5)).ceil() as i32;
+        (0..=samples).filter_map(move |sample| {
+            self.cell_at(from.lerp(to, sample as f32 / samples.max(1) as f32))
+        })
+    }
+
+    // Whether an agent can walk straight from one cell to another, sampling every half cell.
+    fn line_of_sight(&self, from: IVec2, to: IVec2, blocked: &HashSet<IVec2>) -> bool {
+        let start = self.cell_center(from);
+        let end = self.cell_center(to);
+        let samples = (start.xz().distance(end.xz()) / (CELL_SIZE * 0.5)).ceil() as i32;
+
+        let mut previous = from;
+        for sample in 1..=samples {
+            let point = start.lerp(end, sample as f32 / samples as f32);
+            let Some(cell) = self.cell_at(point) else {
+                return false;
+            };
+            if cell != previous && !self.can_step(previous, cell, blocked) {
+                return false;
+            }
+            previous = cell;
+        }
+        true
+    }
+
+    // Waypoints from `from` to `to`, ending exactly at `to` even when that's off the grid (inside a
+    // trailer, say) so the last stretch is walked straight. `None` if there's no way through.
+    pub fn find_path(
+        &self,
+        from: Vec3,
+        to: Vec3,
+        dynamic: &[DynamicObstacle],
+    ) -> Option<Vec<Vec3>> {
+        if self.is_empty() {
+            return None;
+        }
+        let start = self.nearest_walkable(from, SNAP_RINGS)?;
+        let goal = self.nearest_walkable(to, SNAP_RINGS)?;
+
+        let mut blocked = HashSet::new();
+        for obstacle in dynamic {
+            let reach = obstacle.radius + AGENT_RADIUS;
+            let min = obstacle.position.xz() - reach;
+            let max = obstacle.position.xz() + reach;
+            for cell in self.cells_in(min, max) {
+                let center = self.cell_center(cell);
+                let near_endpoint = center.xz().distance(from.xz()) < ENDPOINT_CLEARANCE
+                    || center.xz().distance(to.xz()) < ENDPOINT_CLEARANCE;
+                if !near_endpoint && center.xz().distance(obstacle.position.xz()) <= reach {
+                    blocked.insert(cell);
+                }
+            }
+        }
+        blocked.remove(&start);
+        blocked.remove(&goal);
+
+        let mut open = BinaryHeap::new();
+        let mut cost_so_far: HashMap<IVec2, f32> = HashMap::new();
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+
+        open.push(OpenCell {
+            estimate: heuristic(start, goal),
+            cell: start,
+        });
+        cost_so_far.insert(start, 0.0);
+
+        let mut expanded = 0;
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == goal {
+                break;
+            }
+            expanded += 1;
+            if expanded > MAX_EXPANDED_CELLS {
+                return None;
+            }
+
+            for offset in NEIGHBORS {
+                let next = cell + offset;
+                if !self.can_step(cell, next, &blocked) {
+                    continue;
+                }
+                // No cutting corners past a wall.
+                if offset.x != 0
+                    && offset.y != 0
+                    && !(self.can_step(cell, cell + IVec2::new(offset.x, 0), &blocked)
+                        && self.can_step(cell, cell + IVec2::new(0, offset.y), &blocked))
+                {
+                    continue;
+                }
+
+                let step = offset.as_vec2().length();
+                let cost = cost_so_far[&cell] + step;
+                if cost_so_far.get(&next).is_some_and(|known| *known <= cost) {
+                    continue;
+                }
+                cost_so_far.insert(next, cost);
+                came_from.insert(next, cell);
+                open.push(OpenCell {
+                    estimate: cost + heuristic(next, goal),
+                    cell: next,
+                });
+            }
+        }
+
+        if start != goal && !came_from.contains_key(&goal) {
+            return None;
+        }
+
+        let mut cells = vec![goal];
+        while let Some(previous) = came_from.get(cells.last().unwrap()) {
+            cells.push(*previous);
+        }
+        cells.reverse();
+
+        // Skip every cell that can be walked past in a straight line.
+        let mut corners = vec![cells[0]];
+        for window in 1..cells.len() {
+            let anchor = *corners.last().unwrap();
+            if !self.line_of_sight(anchor, cells[window], &blocked) {
+                corners.push(cells[window - 1]);
+            }
+        }
+
+        let mut path: Vec<Vec3> = corners
+            .into_iter()
+            .skip(1)
+            .map(|cell| self.cell_center(cell))
+            .collect();
+        path.push(self.cell_center(goal));
+        path.push(to);
+        Some(path)
+    }
+}
+
+pub fn plan_paths(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut agent_query: Query<(Entity, &mut NavAgent, &GlobalTransform)>,
+    package_query: Query<&GlobalTransform, (With<Package>, Without<Parent>)>,
+) {
+    let dt = time.delta_seconds();
+    let agents: Vec<(Entity, Vec3)> = agent_query
+        .iter()
+        .map(|(entity, _, transform)| (entity, transform.translation()))
+        .collect();
+
+    for (entity, mut agent, transform) in agent_query.iter_mut() {
+        agent.replan_in -= dt;
+        if !agent.needs_replan(&grid, transform.translation()) {
+            // The path has been checked against everything that changed so far.
+            agent.planned_at_revision = grid.revision;
+            continue;
+        }
+        let Some(destination) = agent.destination else {
+            continue;
+        };
+
+        let others = agents
+            .iter()
+            .filter(|(other, _)| *other != entity)
+            .map(|(_, position)| DynamicObstacle {
+                position: *position,
+                radius: AGENT_RADIUS,
+            });
+        let packages = package_query.iter().map(|package| DynamicObstacle {
+            position: package.translation(),
+            radius: PACKAGE_RADIUS,
+        });
+        let dynamic: Vec<DynamicObstacle> = others.chain(packages).collect();
+
+        // With no route, head straight for it and try again shortly.
+        agent.path = grid
+            .find_path(transform.translation(), destination, &dynamic)
+            .unwrap_or_default();
+        agent.planned_for = Some(destination);
+        agent.planned_at_revision = grid.revision;
+        agent.replan_in = REPLAN_SECONDS;
+    }
+}
+
+pub fn follow_paths(mut agent_query: Query<(&mut NavAgent, &GlobalTransform)>) {
+    for (mut agent, transform) in agent_query.iter_mut() {
+        if agent.destination.is_none() {
+            agent.path.clear();
+            agent.planned_for = None;
+            continue;
+        }
+
+        let position = transform.translation().xz();
+        while agent.path.len() > 1 && agent.path[0].xz().distance(position) < WAYPOINT_REACHED {
+            agent.path.remove(0);
+        }
+    }
+}
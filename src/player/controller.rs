@@ -1,34 +1,49 @@
-use crate::levels::asset_loader_plugin::{AssetLoaderPlugin, AssetLoaderState, MyAssetPack};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::{AssetLoaderPlugin, AssetLoaderState};
+use crate::levels::markers::{spawn_level_markers, PlayerSpawnPoint};
 use crate::raycasting::PlayerRaycast;
-use bevy::core_pipeline::bloom::BloomSettings;
-use bevy::gltf::Gltf;
-use bevy::pbr::DirectionalLightShadowMap;
 use bevy::{prelude::*, window::CursorGrabMode};
-use bevy_atmosphere::plugin::{AtmosphereCamera, AtmospherePlugin};
+use bevy_atmosphere::plugin::AtmospherePlugin;
 use bevy_fps_controller::controller::*;
 use bevy_rapier3d::prelude::*;
 use std::f32::consts::TAU;
 
+use super::inventory::InventoryPlugin;
+use super::items::box_cutter::BoxCutterPlugin;
+use super::items::carry::CarryPlugin;
+use super::items::label_printer::LabelPrinterPlugin;
 use super::items::scanner::ScannerTool;
+use super::items::tablet::TabletPlugin;
+use super::items::tape_gun::TapeGunPlugin;
+use super::stamina::{Stamina, StaminaPlugin};
+use super::viewmodel::ViewmodelPlugin;
 
 pub struct CharacterController;
 
 impl Plugin for CharacterController {
     fn build(&self, app: &mut App) {
         app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-            .add_systems(Update, (manage_cursor, respawn))
-            //.add_plugins(PlayerRaycast)
+            .add_systems(
+                Update,
+                (manage_cursor, respawn).run_if(in_state(GameState::InGame)),
+            )
+            .add_plugins(PlayerRaycast)
             .add_plugins(AtmospherePlugin)
             .add_plugins(AssetLoaderPlugin)
-            .insert_resource(DirectionalLightShadowMap { size: 4096 })
-            .insert_resource(AmbientLight {
-                color: Color::WHITE,
-                brightness: 100.0,
-            })
             .add_plugins(FpsControllerPlugin)
-            .add_systems(OnEnter(AssetLoaderState::Done), setup)
+            .add_systems(
+                OnEnter(AssetLoaderState::Done),
+                setup.after(spawn_level_markers),
+            )
             .add_plugins(ScannerTool)
-            .insert_resource(ClearColor(Color::hex("D4F5F5").unwrap()));
+            .add_plugins(CarryPlugin)
+            .add_plugins(LabelPrinterPlugin)
+            .add_plugins(TapeGunPlugin)
+            .add_plugins(BoxCutterPlugin)
+            .add_plugins(TabletPlugin)
+            .add_plugins(InventoryPlugin)
+            .add_plugins(StaminaPlugin)
+            .add_plugins(ViewmodelPlugin);
     }
 }
 
@@ -43,30 +58,30 @@ pub struct PlayerInteractionSystem {
 #[derive(Component, Clone, Copy, Debug)]
 pub struct MainCharacter;
 
+// Low enough for the camera to see, and the player to reach, the bottom shelves.
+const CROUCH_HEIGHT: f32 = 0.8;
+
+// Used when the level has no player spawn marker.
 const SPAWN_POINT: Vec3 = Vec3::new(0.0, 1.0, 0.0);
 
+fn player_spawn_point(spawn_points: &Query<&Transform, With<PlayerSpawnPoint>>) -> Vec3 {
+    spawn_points
+        .iter()
+        .next()
+        .map_or(SPAWN_POINT, |transform| transform.translation)
+}
+
 fn setup(
     mut commands: Commands,
     mut window: Query<&mut Window>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_pack: Res<MyAssetPack>,
-    assets_gltf: Res<Assets<Gltf>>,
+    spawn_points: Query<&Transform, With<PlayerSpawnPoint>>,
 ) {
     let mut window = window.single_mut();
     window.title = String::from("Courier");
 
-    commands.spawn(PointLightBundle {
-        transform: Transform::from_xyz(0., 2.5, 0.).looking_at(Vec3::new(0., 0., 0.), Vec3::Z),
-
-        point_light: PointLight {
-            intensity: 60000.0,
-            color: Color::WHITE,
-            shadows_enabled: true,
-            ..default()
-        },
-        ..default()
-    });
+    let spawn_point = player_spawn_point(&spawn_points);
 
     // Note that we have two entities for the player
     // One is a "logical" player that handles the physics computation and collision
@@ -92,7 +107,7 @@ fn setup(
             AdditionalMassProperties::Mass(1.0),
             GravityScale(0.0),
             Ccd { enabled: true }, // Prevent clipping when going fast
-            TransformBundle::from_transform(Transform::from_translation(SPAWN_POINT)),
+            TransformBundle::from_transform(Transform::from_translation(spawn_point)),
             LogicalPlayer,
             FpsControllerInput {
                 pitch: -TAU / 12.0,
@@ -101,75 +116,48 @@ fn setup(
             },
             FpsController {
                 air_acceleration: 80.0,
+                crouch_height: CROUCH_HEIGHT,
                 ..default()
             },
         ))
-        .insert(CameraConfig {
-            height_offset: 0.0,
-            radius_scale: 0.75,
-        })
+        .insert((
+            CameraConfig {
+                height_offset: 0.0,
+                radius_scale: 0.75,
+            },
+            Stamina::default(),
+        ))
         .id();
 
-    if let Some(scanner) = assets_gltf.get(&asset_pack.scanner) {
-        if let Some(player_hand) = assets_gltf.get(&asset_pack.player_hand) {
-            commands
-                .spawn((
-                    Camera3dBundle {
-                        camera: Camera {
-                            hdr: true,
-                            ..default()
-                        },
-                        projection: Projection::Perspective(PerspectiveProjection {
-                            fov: TAU / 5.0,
-                            ..default()
-                        }),
-                        ..default()
-                    },
-                    PlayerInteractionSystem {
-                        is_holding_item: false,
-                        is_looking_at_item: false,
-                        interactable_entity: None,
-                    },
-                    BloomSettings::OLD_SCHOOL,
-                    RenderPlayer { logical_entity },
-                    AtmosphereCamera::default(),
-                ))
-                .with_children(|cam| {
-                    cam.spawn((
-                        SceneBundle {
-                            scene: scanner.named_scenes["Scene"].clone(),
-                            transform: Transform::from_xyz(0.3, -0.2, -0.5),
-
-                            ..default()
-                        },
-                        ScannerTool,
-                    ));
-                })
-                .with_children(|cam| {
-                    cam.spawn(SceneBundle {
-                        scene: player_hand.named_scenes["Scene"].clone(),
-                        transform: Transform::from_xyz(0.4, -0.7, -0.4),
-                        ..default()
-                    });
-                });
-        }
-    }
+    // The hand and the tool in it are added by the viewmodel.
+    commands.spawn((
+        Camera3dBundle::default(),
+        PlayerInteractionSystem {
+            is_holding_item: false,
+            is_looking_at_item: false,
+            interactable_entity: None,
+        },
+        RenderPlayer { logical_entity },
+    ));
 }
 
-fn respawn(mut query: Query<(&mut Transform, &mut Velocity)>) {
+fn respawn(
+    mut query: Query<(&mut Transform, &mut Velocity), Without<PlayerSpawnPoint>>,
+    spawn_points: Query<&Transform, With<PlayerSpawnPoint>>,
+) {
     for (mut transform, mut velocity) in &mut query {
         if transform.translation.y > -50.0 {
             continue;
         }
 
         velocity.linvel = Vec3::ZERO;
-        transform.translation = SPAWN_POINT;
+        transform.translation = player_spawn_point(&spawn_points);
     }
 }
 
+// Pausing releases the cursor (see `GameState`).
 fn manage_cursor(
     btn: Res<ButtonInput<MouseButton>>,
-    key: Res<ButtonInput<KeyCode>>,
     mut window_query: Query<(Entity, &mut Window)>,
     mut controller_query: Query<&mut FpsController>,
 ) {
@@ -185,11 +173,4 @@ fn manage_cursor(
             controller.enable_input = true;
         }
     }
-    if key.just_pressed(KeyCode::Escape) {
-        window.1.cursor.grab_mode = CursorGrabMode::None;
-        window.1.cursor.visible = true;
-        for mut controller in &mut controller_query {
-            controller.enable_input = false;
-        }
-    }
 }
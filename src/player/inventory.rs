@@ -0,0 +1,141 @@
+// What the player carries: the tools on the hotbar, and the supplies some of them use up. The
+// slot keys (1-9), the scroll wheel and the next/previous tool actions pick a tool off the hotbar,
+// and the viewmodel puts it in the player's hand.
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::player::viewmodel::{use_tools, HeldTool, Tool};
+use crate::ui::label_editor::label_editor_closed;
+use crate::ui::staff_panel::staff_panel_closed;
+use crate::ui::tracking_panel::tracking_panel_closed;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>().add_systems(
+            Update,
+            select_tool
+                .before(use_tools)
+                .run_if(in_state(GameState::InGame))
+                .run_if(label_editor_closed)
+                .run_if(staff_panel_closed)
+                .run_if(tracking_panel_closed),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Supply {
+    Labels,
+    Tape,
+    HazmatStickers,
+}
+
+impl Supply {
+    pub const ALL: [Supply; 3] = [Supply::Labels, Supply::Tape, Supply::HazmatStickers];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Supply::Labels => "Labels",
+            Supply::Tape => "Tape",
+            Supply::HazmatStickers => "Hazmat stickers",
+        }
+    }
+
+    // How many come in a case, which is also what a shift starts with.
+    pub fn case_size(&self) -> u32 {
+        match self {
+            Supply::Labels => 20,
+            Supply::Tape => 15,
+            Supply::HazmatStickers => 10,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct Inventory {
+    pub hotbar: Vec<Tool>,
+    supplies: HashMap<Supply, u32>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory {
+            hotbar: Tool::ALL.to_vec(),
+            supplies: Supply::ALL
+                .iter()
+                .map(|supply| (*supply, supply.case_size()))
+                .collect(),
+        }
+    }
+}
+
+impl Inventory {
+    pub fn count(&self, supply: Supply) -> u32 {
+        self.supplies.get(&supply).copied().unwrap_or(0)
+    }
+
+    // Uses up one of `supply`. False, with nothing taken, when there's none left.
+    pub fn take(&mut self, supply: Supply) -> bool {
+        match self.supplies.get_mut(&supply) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn add(&mut self, supply: Supply, count: u32) {
+        *self.supplies.entry(supply).or_default() += count;
+    }
+
+    // The tool `step` slots along the hotbar from `tool`, wrapping around at either end.
+    fn cycle(&self, tool: Tool, step: isize) -> Option<Tool> {
+        if self.hotbar.is_empty() {
+            return None;
+        }
+        let slots = self.hotbar.len() as isize;
+        let slot = self
+            .hotbar
+            .iter()
+            .position(|slot| *slot == tool)
+            .map_or(0, |slot| slot as isize);
+        Some(self.hotbar[(slot + step).rem_euclid(slots) as usize])
+    }
+}
+
+fn select_tool(
+    actions: Res<ActionState>,
+    mut wheel: EventReader<MouseWheel>,
+    inventory: Res<Inventory>,
+    mut held: ResMut<HeldTool>,
+) {
+    // Scrolling down moves right along the hotbar.
+    let scrolled: f32 = wheel.read().map(|event| event.y).sum();
+    let step = if actions.just_pressed(Action::NextTool) || scrolled < 0.0 {
+        1
+    } else if actions.just_pressed(Action::PreviousTool) || scrolled > 0.0 {
+        -1
+    } else {
+        0
+    };
+
+    let picked = actions
+        .just_pressed_slot()
+        .and_then(|slot| inventory.hotbar.get(slot).copied());
+    let tool = match picked {
+        Some(tool) => Some(tool),
+        None if step != 0 => inventory.cycle(held.0, step),
+        None => None,
+    };
+
+    if let Some(tool) = tool {
+        held.set_if_neq(HeldTool(tool));
+    }
+}
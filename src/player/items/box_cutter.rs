@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+use crate::logistics::containers::{take_out, ShippingContainer};
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::tracking::TrackingLog;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::viewmodel::{use_tools, Tool, ToolUsed};
+
+pub struct BoxCutterPlugin;
+
+impl Plugin for BoxCutterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            cut_open_container
+                .after(use_tools)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Using the box cutter on a container, or on a package in one, cuts the wrap holding the load
+// together and everything in it comes out loose. Quicker than taking packages out one at a time
+// when a container was built for the wrong route.
+#[allow(clippy::too_many_arguments)]
+fn cut_open_container(
+    mut commands: Commands,
+    mut uses: EventReader<ToolUsed>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    parent_query: Query<&Parent>,
+    mut container_query: Query<(&mut ShippingContainer, Option<&Children>)>,
+    package_query: Query<&Package>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    let box_cutter_uses = uses.read().filter(|used| used.tool == Tool::BoxCutter);
+    if box_cutter_uses.count() == 0 {
+        return;
+    }
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let container_entity = if container_query.contains(target) {
+            target
+        } else {
+            match parent_query.get(target) {
+                Ok(parent) if container_query.contains(parent.get()) => parent.get(),
+                _ => continue,
+            }
+        };
+        let Ok((mut container, children)) = container_query.get_mut(container_entity) else {
+            continue;
+        };
+
+        let children = children
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+        for child in children {
+            if let Ok(package) = package_query.get(child) {
+                take_out(
+                    &mut commands,
+                    &mut container,
+                    child,
+                    package,
+                    &clock,
+                    &mut log,
+                );
+            }
+        }
+        info!("Cut open {}", container.label);
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::building::build_mode::not_building;
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::vehicles::driving::Occupied;
+
+pub struct CarryPlugin;
+
+impl Plugin for CarryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            pick_up_or_put_down
+                .run_if(in_state(GameState::InGame))
+                .run_if(not_building),
+        );
+    }
+}
+
+// Out in front and a little below the crosshair, so the player can still see where it's going.
+const HOLD_OFFSET: Vec3 = Vec3::new(0.0, -0.45, -1.2);
+// Meters per second.
+const THROW_SPEED: f32 = 7.0;
+
+// On the package the player is holding. Like the workers' loads, it rides along on the camera
+// without a rigid body of its own, and its collider is off so it doesn't shove the player.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Carried;
+
+type Pickup<'a> = (&'a Package, Option<&'a Parent>, Option<&'a RigidBody>);
+
+// Interact picks up the package under the crosshair, or puts down the one in hand. Throw throws
+// it instead. Getting in and out of vehicles waits until the player's hands are empty.
+#[allow(clippy::type_complexity)]
+fn pick_up_or_put_down(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut camera_query: Query<(Entity, &GlobalTransform, &mut PlayerInteractionSystem)>,
+    package_query: Query<Pickup>,
+    carried_query: Query<Entity, With<Carried>>,
+    vehicle_query: Query<(), With<Occupied>>,
+) {
+    let interact = actions.just_pressed(Action::Interact);
+    let throw = actions.just_pressed(Action::Throw);
+    if !interact && !throw {
+        return;
+    }
+    let Ok((camera, camera_transform, mut interaction)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(carried) = carried_query.iter().next() {
+        let velocity = if throw {
+            camera_transform.forward() * THROW_SPEED
+        } else {
+            Vec3::ZERO
+        };
+        commands
+            .entity(carried)
+            .remove_parent_in_place()
+            .remove::<(Carried, ColliderDisabled)>()
+            .insert((RigidBody::Dynamic, Velocity::linear(velocity)));
+        interaction.is_holding_item = false;
+        return;
+    }
+
+    if !interact || !vehicle_query.is_empty() {
+        return;
+    }
+    let Some(target) = interaction.interactable_entity else {
+        return;
+    };
+    // Packages in containers, on trucks or in someone else's hands stay where they are.
+    let Ok((_, None, Some(_))) = package_query.get(target) else {
+        return;
+    };
+
+    commands
+        .entity(target)
+        .remove::<RigidBody>()
+        .insert((
+            Carried,
+            ColliderDisabled,
+            Transform::from_translation(HOLD_OFFSET),
+        ))
+        .set_parent(camera);
+    interaction.is_holding_item = true;
+}
@@ -0,0 +1,245 @@
+// The handheld label printer. Using it on a package opens the label editor (see
+// `ui::label_editor`); printing the edited label rewrites the package's routing data, and the
+// label on the box is redrawn to match (see `shipping_label`). Every field that changes goes
+// into the tracking history along with its old value, so relabeling can be audited later.
+
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::{Package, ServiceLevel};
+use crate::logistics::address::{
+    validate_address, AddressException, AddressExceptionResolved, AddressOnFile, ExceptionStatus,
+};
+use crate::logistics::customs::{CustomsDeclaration, CustomsStatus};
+use crate::logistics::economy::Wallet;
+use crate::logistics::outbound::OutboundTruck;
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::sorting::SortedTo;
+use crate::logistics::tracking::{TrackingEventKind, TrackingLog};
+use crate::player::inventory::{Inventory, Supply};
+
+pub struct LabelPrinterPlugin;
+
+impl Plugin for LabelPrinterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PrintLabel>()
+            .add_systems(Update, print_labels.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// Declared dangerous goods that haven't been stickered yet.
+pub fn needs_hazmat_label(package: &Package, declaration: Option<&CustomsDeclaration>) -> bool {
+    !package.hazmat && declaration.is_some_and(CustomsDeclaration::is_dangerous_goods)
+}
+
+// Everything on a package's label that decides where and how it goes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoutingLabel {
+    pub recipient_name: String,
+    pub street_address: String,
+    pub country: String,
+    pub zip_code: Option<u32>,
+    pub service_level: ServiceLevel,
+    pub hazmat: bool,
+}
+
+fn describe_zip_code(zip_code: Option<u32>) -> String {
+    zip_code.map_or("none".to_string(), |zip_code| zip_code.to_string())
+}
+
+fn describe_hazmat(hazmat: bool) -> String {
+    if hazmat { "yes" } else { "no" }.to_string()
+}
+
+impl RoutingLabel {
+    pub fn from_package(package: &Package) -> Self {
+        RoutingLabel {
+            recipient_name: package.recipient_name.clone(),
+            street_address: package.street_address.clone(),
+            country: package.country.clone(),
+            zip_code: package.zip_code,
+            service_level: package.service_level,
+            hazmat: package.hazmat,
+        }
+    }
+
+    pub fn with_address(&self, address: &AddressOnFile) -> Self {
+        RoutingLabel {
+            recipient_name: address.recipient_name.clone(),
+            street_address: address.street_address.clone(),
+            country: address.country.clone(),
+            zip_code: address.zip_code,
+            ..self.clone()
+        }
+    }
+
+    // Each field that differs in `printed`, with its old and new values.
+    pub fn changes(&self, printed: &RoutingLabel) -> Vec<(&'static str, String, String)> {
+        let fields = [
+            (
+                "recipient",
+                self.recipient_name.clone(),
+                printed.recipient_name.clone(),
+            ),
+            (
+                "street",
+                self.street_address.clone(),
+                printed.street_address.clone(),
+            ),
+            ("country", self.country.clone(), printed.country.clone()),
+            (
+                "zip code",
+                describe_zip_code(self.zip_code),
+                describe_zip_code(printed.zip_code),
+            ),
+            (
+                "service",
+                format!("{:?}", self.service_level),
+                format!("{:?}", printed.service_level),
+            ),
+            (
+                "hazmat",
+                describe_hazmat(self.hazmat),
+                describe_hazmat(printed.hazmat),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .collect()
+    }
+
+    fn apply(&self, package: &mut Package) {
+        package.recipient_name = self.recipient_name.clone();
+        package.street_address = self.street_address.clone();
+        package.country = self.country.clone();
+        package.zip_code = self.zip_code;
+        package.hazmat = self.hazmat;
+        package.set_service_level(self.service_level);
+    }
+}
+
+// Sent by the label editor when the player prints.
+#[derive(Event, Clone, Debug)]
+pub struct PrintLabel {
+    pub package: Entity,
+    pub label: RoutingLabel,
+}
+
+type Relabeled<'a> = (
+    &'a mut Package,
+    Option<&'a mut AddressException>,
+    Option<&'a CustomsStatus>,
+    Option<&'a CustomsDeclaration>,
+);
+
+// Whether customs still has to make a call on the package.
+pub fn customs_open(customs: Option<&CustomsStatus>) -> bool {
+    customs.is_some_and(|status| matches!(status, CustomsStatus::Pending | CustomsStatus::Held))
+}
+
+// Every label takes a label off the roll, and adding a hazmat label takes a sticker too. The
+// postage was paid when the package came in, so a downgrade refunds the difference and an
+// upgrade earns nothing. A label that fixes the address settles the package's address exception,
+// even if it had been sent back. A package waiting on customs can't be readdressed to another
+// country, so relabeling can't get it past the inspection, and a domestic package readdressed
+// abroad has to go through customs like any other. A new destination means sorting it again, and
+// a package already loaded on a truck has to come off before it can be relabeled.
+#[allow(clippy::too_many_arguments)]
+fn print_labels(
+    mut commands: Commands,
+    mut prints: EventReader<PrintLabel>,
+    mut package_query: Query<Relabeled>,
+    truck_query: Query<&OutboundTruck>,
+    mut inventory: ResMut<Inventory>,
+    mut wallet: ResMut<Wallet>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+    mut resolutions: EventWriter<AddressExceptionResolved>,
+) {
+    for print in prints.read() {
+        let Ok((mut package, exception, customs, declaration)) =
+            package_query.get_mut(print.package)
+        else {
+            continue;
+        };
+
+        let old = RoutingLabel::from_package(&package);
+        let changes = old.changes(&print.label);
+        if changes.is_empty() {
+            continue;
+        }
+        if truck_query
+            .iter()
+            .any(|truck| truck.manifest.contains(&print.package))
+        {
+            info!(
+                "{} is loaded on a truck; take it off before relabeling",
+                package.tracking_id
+            );
+            continue;
+        }
+        if customs_open(customs) && print.label.country != old.country {
+            info!(
+                "{} has to clear customs before it can go to another country",
+                package.tracking_id
+            );
+            continue;
+        }
+        let stickered = print.label.hazmat && !old.hazmat;
+        if inventory.count(Supply::Labels) == 0 {
+            info!("Out of labels");
+            continue;
+        }
+        if stickered && inventory.count(Supply::HazmatStickers) == 0 {
+            info!("Out of hazmat stickers");
+            continue;
+        }
+        inventory.take(Supply::Labels);
+        if stickered {
+            inventory.take(Supply::HazmatStickers);
+        }
+
+        let old_postage = package.postage;
+        let was_international = package.is_international();
+        print.label.apply(&mut package);
+        if package.postage < old_postage {
+            wallet.debit(old_postage - package.postage);
+        }
+
+        if package.is_international() && !was_international && customs.is_none() {
+            let mut entity = commands.entity(print.package);
+            entity.insert(CustomsStatus::Pending);
+            if declaration.is_none() {
+                entity.insert(CustomsDeclaration::random());
+            }
+        }
+        if print.label.country != old.country || print.label.zip_code != old.zip_code {
+            commands.entity(print.package).remove::<SortedTo>();
+        }
+
+        for (field, from, to) in changes {
+            log.record(
+                &package.tracking_id,
+                TrackingEventKind::Relabeled {
+                    field: field.to_string(),
+                    from,
+                    to,
+                },
+                clock.now(),
+            );
+        }
+
+        if let Some(mut exception) = exception {
+            let unsettled = exception.status != ExceptionStatus::Corrected;
+            if unsettled && validate_address(&package).is_ok() {
+                exception.status = ExceptionStatus::Corrected;
+                resolutions.send(AddressExceptionResolved {
+                    package: print.package,
+                    status: ExceptionStatus::Corrected,
+                });
+            }
+        }
+    }
+}
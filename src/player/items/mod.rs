@@ -1 +1,6 @@
+pub mod box_cutter;
+pub mod carry;
+pub mod label_printer;
 pub mod scanner;
+pub mod tablet;
+pub mod tape_gun;
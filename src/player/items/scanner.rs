@@ -1,29 +1,55 @@
 use bevy::prelude::*;
-use bevy_fps_controller::controller::LogicalPlayer;
-use bevy_rapier3d::prelude::Velocity;
 
-#[derive(Component, Debug)]
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::viewmodel::{use_tools, Tool, ToolUsed};
+
+#[derive(Debug)]
 pub struct ScannerTool;
 
 impl Plugin for ScannerTool {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, scanner_sway);
+        app.add_event::<PackageScanned>()
+            .add_event::<ScanMissed>()
+            .add_systems(
+                Update,
+                scan_package
+                    .after(use_tools)
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
-fn scanner_sway(
-    mut scanner_query: Query<&mut Transform, With<ScannerTool>>,
-    time: Res<Time>,
-    player_query: Query<&Velocity, With<LogicalPlayer>>,
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PackageScanned {
+    pub package: Entity,
+}
+
+// Scanning with no package under the crosshair.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ScanMissed;
+
+// Using the scanner scans whichever package the player is looking at.
+fn scan_package(
+    mut uses: EventReader<ToolUsed>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    package_query: Query<(), With<Package>>,
+    mut scans: EventWriter<PackageScanned>,
+    mut misses: EventWriter<ScanMissed>,
 ) {
-    let step = 6.0;
+    let scanner_uses = uses.read().filter(|used| used.tool == Tool::Scanner);
+    if scanner_uses.count() == 0 {
+        return;
+    }
 
-    for player_velocity in player_query.iter() {
-        if player_velocity.linvel != Vec3::ZERO {
-            for mut scanner_transform in scanner_query.iter_mut() {
-                let time_since_start = time.elapsed_seconds();
-                let delta: f32 = (time_since_start * step).sin();
-                scanner_transform.translation.y += delta / 1500.0;
+    for interaction in interaction_query.iter() {
+        match interaction.interactable_entity {
+            Some(target) if package_query.contains(target) => {
+                scans.send(PackageScanned { package: target });
+            }
+            _ => {
+                misses.send(ScanMissed);
             }
         }
     }
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::logistics::economy::Wallet;
+use crate::player::inventory::{Inventory, Supply};
+use crate::player::viewmodel::{use_tools, Tool, ToolUsed};
+
+pub struct TabletPlugin;
+
+impl Plugin for TabletPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            order_supplies
+                .after(use_tools)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// A case of every supply.
+pub const SUPPLY_ORDER_COST: f32 = 35.0;
+
+// Using the tablet orders more supplies. They turn up straight away.
+fn order_supplies(
+    mut uses: EventReader<ToolUsed>,
+    mut inventory: ResMut<Inventory>,
+    mut wallet: ResMut<Wallet>,
+) {
+    for _ in uses.read().filter(|used| used.tool == Tool::Tablet) {
+        if wallet.balance < SUPPLY_ORDER_COST {
+            info!("A supply order costs ${:.0}", SUPPLY_ORDER_COST);
+            continue;
+        }
+        for supply in Supply::ALL {
+            inventory.add(supply, supply.case_size());
+        }
+        wallet.debit(SUPPLY_ORDER_COST);
+    }
+}
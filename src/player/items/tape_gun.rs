@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::{Damaged, Package};
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::tracking::{TrackingEventKind, TrackingLog};
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::inventory::{Inventory, Supply};
+use crate::player::viewmodel::{use_tools, Tool, ToolUsed};
+
+pub struct TapeGunPlugin;
+
+impl Plugin for TapeGunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            tape_up_package
+                .after(use_tools)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// Using the tape gun on a package that was damaged in handling tapes it back up.
+fn tape_up_package(
+    mut commands: Commands,
+    mut uses: EventReader<ToolUsed>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    package_query: Query<&Package, With<Damaged>>,
+    mut inventory: ResMut<Inventory>,
+    clock: Res<ShiftClock>,
+    mut log: ResMut<TrackingLog>,
+) {
+    let tape_gun_uses = uses.read().filter(|used| used.tool == Tool::TapeGun);
+    if tape_gun_uses.count() == 0 {
+        return;
+    }
+
+    for interaction in interaction_query.iter() {
+        let Some(target) = interaction.interactable_entity else {
+            continue;
+        };
+        let Ok(package) = package_query.get(target) else {
+            continue;
+        };
+        if !inventory.take(Supply::Tape) {
+            info!("Out of tape");
+            continue;
+        }
+
+        commands.entity(target).remove::<Damaged>();
+        log.record(
+            &package.tracking_id,
+            TrackingEventKind::Repaired,
+            clock.now(),
+        );
+    }
+}
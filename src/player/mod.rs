@@ -1,2 +1,5 @@
 pub mod controller;
+pub mod inventory;
 pub mod items;
+pub mod stamina;
+pub mod viewmodel;
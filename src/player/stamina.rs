@@ -0,0 +1,129 @@
+// Stamina and encumbrance. Sprinting wears the player out, faster with a heavy package in their
+// arms, and walking with a heavy one wears them out too. Whatever they carry slows them down in
+// proportion to its weight. Once stamina runs out there's no sprinting until they've caught their
+// breath.
+
+use bevy::prelude::*;
+use bevy_fps_controller::controller::{FpsController, FpsControllerInput};
+use bevy_rapier3d::prelude::Velocity;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::{Package, MAX_PACKAGE_WEIGHT};
+use crate::player::items::carry::Carried;
+
+pub struct StaminaPlugin;
+
+impl Plugin for StaminaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_stamina, apply_movement_speeds)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+pub const MAX_STAMINA: f32 = 100.0;
+// Per second of sprinting empty-handed. The heaviest package triples it.
+const SPRINT_DRAIN: f32 = 12.0;
+const LOADED_SPRINT_DRAIN: f32 = 2.0;
+// Per second of walking with the heaviest package, less for lighter ones.
+const LOADED_WALK_DRAIN: f32 = 6.0;
+// Per second, once the player has rested a moment.
+const RECOVERY: f32 = 20.0;
+const RECOVERY_DELAY_SECONDS: f32 = 1.0;
+// How much has to come back after running out before the player can sprint again.
+const CATCH_BREATH: f32 = 30.0;
+// Meters per second. Slower than this counts as standing still.
+const MOVING_SPEED: f32 = 0.5;
+
+// The controller's own speeds, in meters per second, for an empty-handed player.
+const WALK_SPEED: f32 = 9.0;
+const RUN_SPEED: f32 = 14.0;
+const CROUCHED_SPEED: f32 = 5.0;
+// How much of the player's speed the heaviest package takes away.
+const MAX_LOAD_SLOWDOWN: f32 = 0.5;
+
+// On the logical player.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Stamina {
+    pub current: f32,
+    // Ran out and hasn't caught their breath yet.
+    pub exhausted: bool,
+    resting_for: f32,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: MAX_STAMINA,
+            exhausted: false,
+            resting_for: 0.0,
+        }
+    }
+}
+
+// The weight in the player's arms as a share of the heaviest package there is.
+fn load_share(carried_query: &Query<&Package, With<Carried>>) -> f32 {
+    let weight: f32 = carried_query.iter().map(|package| package.weight).sum();
+    (weight / MAX_PACKAGE_WEIGHT).clamp(0.0, 1.0)
+}
+
+fn update_stamina(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Stamina, &FpsControllerInput, &Velocity)>,
+    carried_query: Query<&Package, With<Carried>>,
+) {
+    let dt = time.delta_seconds();
+    let load = load_share(&carried_query);
+
+    for (mut stamina, input, velocity) in player_query.iter_mut() {
+        let moving = Vec2::new(velocity.linvel.x, velocity.linvel.z).length() > MOVING_SPEED;
+        let sprinting = moving && input.sprint && !input.crouch && !stamina.exhausted;
+
+        let mut drain = 0.0;
+        if sprinting {
+            drain += SPRINT_DRAIN * (1.0 + LOADED_SPRINT_DRAIN * load);
+        }
+        if moving {
+            drain += LOADED_WALK_DRAIN * load;
+        }
+
+        if drain > 0.0 {
+            stamina.current = (stamina.current - drain * dt).max(0.0);
+            stamina.resting_for = 0.0;
+            if stamina.current == 0.0 {
+                stamina.exhausted = true;
+            }
+            continue;
+        }
+
+        stamina.resting_for += dt;
+        if stamina.resting_for >= RECOVERY_DELAY_SECONDS {
+            stamina.current = (stamina.current + RECOVERY * dt).min(MAX_STAMINA);
+        }
+        if stamina.exhausted && stamina.current >= CATCH_BREATH {
+            stamina.exhausted = false;
+        }
+    }
+}
+
+// The controller picks between these itself, depending on whether sprint or crouch is held.
+fn apply_movement_speeds(
+    mut controller_query: Query<(&mut FpsController, &Stamina)>,
+    carried_query: Query<&Package, With<Carried>>,
+) {
+    let speed_share = 1.0 - MAX_LOAD_SLOWDOWN * load_share(&carried_query);
+
+    for (mut controller, stamina) in controller_query.iter_mut() {
+        let walk_speed = WALK_SPEED * speed_share;
+        controller.walk_speed = walk_speed;
+        controller.run_speed = if stamina.exhausted {
+            walk_speed
+        } else {
+            RUN_SPEED * speed_share
+        };
+        controller.crouched_speed = CROUCHED_SPEED * speed_share;
+    }
+}
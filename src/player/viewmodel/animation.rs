@@ -0,0 +1,253 @@
+// The viewmodel's animation state machine. Each state plays the tool model's glTF clip of the
+// same name, if the model has one ("Equip", "Use", "Idle" and "Walk"). Models without clips are
+// moved by hand instead: the rig's offset from where it rests is worked out from scratch every
+// frame, so nothing piles up between frames or depends on the frame rate.
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use bevy::animation::RepeatAnimation;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy_fps_controller::controller::LogicalPlayer;
+use bevy_rapier3d::prelude::Velocity;
+
+use super::{ToolUsed, ViewmodelModels, ViewmodelRig};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViewmodelState {
+    Equipping,
+    Idle,
+    Walking,
+    Using,
+}
+
+impl ViewmodelState {
+    fn clip_name(&self) -> &'static str {
+        match self {
+            ViewmodelState::Equipping => "Equip",
+            ViewmodelState::Idle => "Idle",
+            ViewmodelState::Walking => "Walk",
+            ViewmodelState::Using => "Use",
+        }
+    }
+
+    // How long the state lasts when the model has no clip for it. Idling and walking last until
+    // the player starts or stops.
+    fn default_seconds(&self) -> Option<f32> {
+        match self {
+            ViewmodelState::Equipping => Some(EQUIP_SECONDS),
+            ViewmodelState::Using => Some(USE_SECONDS),
+            ViewmodelState::Idle | ViewmodelState::Walking => None,
+        }
+    }
+}
+
+const EQUIP_SECONDS: f32 = 0.35;
+const USE_SECONDS: f32 = 0.3;
+const BLEND: Duration = Duration::from_millis(150);
+// Meters per second the player has to be moving at for the tool to bob.
+const WALKING_SPEED: f32 = 0.5;
+
+// How far below the rest pose, and how far tipped down, a tool starts when it's brought out.
+const EQUIP_DROP: f32 = 0.3;
+const EQUIP_TILT: f32 = 0.6;
+// How far a tool jerks back, and up, when it's used.
+const USE_KICK: f32 = 0.05;
+const USE_TILT: f32 = 0.15;
+// Meters walked per bob. One bob per footstep.
+const BOB_STRIDE: f32 = 1.6;
+const BOB_SWAY: f32 = 0.012;
+const BOB_HEIGHT: f32 = 0.01;
+// How quickly the bob fades in and out as the player starts and stops, per second.
+const BOB_BLEND: f32 = 8.0;
+const BREATH_HEIGHT: f32 = 0.004;
+const BREATHS_PER_SECOND: f32 = 0.3;
+
+// On the rig.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ViewmodelAnimation {
+    pub state: ViewmodelState,
+    // Seconds spent in the current state, and how long it lasts if it ends by itself.
+    elapsed: f32,
+    duration: Option<f32>,
+    // Whether the current state's clip has been looked for, and found.
+    clip_started: bool,
+    clip_playing: bool,
+    // Where the bob is in its cycle, in radians, and how much of it is showing.
+    bob_phase: f32,
+    bob_weight: f32,
+}
+
+impl Default for ViewmodelAnimation {
+    fn default() -> Self {
+        let mut animation = ViewmodelAnimation {
+            state: ViewmodelState::Idle,
+            elapsed: 0.0,
+            duration: None,
+            clip_started: false,
+            clip_playing: false,
+            bob_phase: 0.0,
+            bob_weight: 0.0,
+        };
+        animation.equip();
+        animation
+    }
+}
+
+impl ViewmodelAnimation {
+    fn enter(&mut self, state: ViewmodelState) {
+        self.state = state;
+        self.elapsed = 0.0;
+        self.duration = state.default_seconds();
+        self.clip_started = false;
+        self.clip_playing = false;
+    }
+
+    pub fn equip(&mut self) {
+        self.enter(ViewmodelState::Equipping);
+    }
+
+    fn finished(&self) -> bool {
+        self.duration
+            .is_none_or(|duration| self.elapsed >= duration)
+    }
+
+    // 0 to 1 through a state that ends by itself.
+    fn progress(&self) -> f32 {
+        self.duration
+            .map_or(1.0, |duration| (self.elapsed / duration).min(1.0))
+    }
+}
+
+fn player_speed(player_query: &Query<&Velocity, With<LogicalPlayer>>) -> f32 {
+    player_query.iter().next().map_or(0.0, |velocity| {
+        Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z).length()
+    })
+}
+
+// Moves between the states, and starts the clip for the state the rig has just moved into.
+#[allow(clippy::too_many_arguments)]
+pub fn update_viewmodel_state(
+    time: Res<Time>,
+    mut uses: EventReader<ToolUsed>,
+    models: Res<ViewmodelModels>,
+    gltfs: Res<Assets<Gltf>>,
+    clips: Res<Assets<AnimationClip>>,
+    player_query: Query<&Velocity, With<LogicalPlayer>>,
+    mut rig_query: Query<(&ViewmodelRig, &mut ViewmodelAnimation)>,
+    children_query: Query<&Children>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+) {
+    let used: Vec<_> = uses.read().map(|used| used.tool).collect();
+    let speed = player_speed(&player_query);
+
+    for (rig, mut animation) in rig_query.iter_mut() {
+        animation.elapsed += time.delta_seconds();
+
+        // Tools can't be used while they're still coming out.
+        if used.contains(&rig.tool) && animation.state != ViewmodelState::Equipping {
+            animation.enter(ViewmodelState::Using);
+        } else if animation.finished() {
+            let next = if speed > WALKING_SPEED {
+                ViewmodelState::Walking
+            } else {
+                ViewmodelState::Idle
+            };
+            if animation.state != next {
+                animation.enter(next);
+            }
+        }
+
+        if animation.clip_started {
+            continue;
+        }
+        let Some(model) = rig.model else {
+            continue;
+        };
+        let clip = models
+            .tools
+            .get(&rig.tool)
+            .and_then(|handle| gltfs.get(handle))
+            .and_then(|gltf| gltf.named_animations.get(animation.state.clip_name()));
+        let Some(clip) = clip else {
+            // Loaded, with nothing to play.
+            animation.clip_started = gltfs.contains(&models.tools[&rig.tool]);
+            continue;
+        };
+        // The model's animation player turns up once its scene has spawned.
+        let player = children_query
+            .iter_descendants(model)
+            .find(|entity| animation_players.contains(*entity));
+        let Some(mut player) = player.and_then(|entity| animation_players.get_mut(entity).ok())
+        else {
+            continue;
+        };
+
+        player.start_with_transition(clip.clone(), BLEND);
+        match animation.state.default_seconds() {
+            Some(_) => {
+                player.set_repeat(RepeatAnimation::Never);
+                if let Some(clip) = clips.get(clip) {
+                    animation.duration = Some(clip.duration());
+                }
+            }
+            None => {
+                player.repeat();
+            }
+        }
+        animation.clip_started = true;
+        animation.clip_playing = true;
+    }
+}
+
+// Moves the rig for the states the model has no clip for.
+pub fn animate_viewmodel(
+    time: Res<Time>,
+    player_query: Query<&Velocity, With<LogicalPlayer>>,
+    mut rig_query: Query<(&mut ViewmodelAnimation, &mut Transform)>,
+) {
+    let speed = player_speed(&player_query);
+    let delta = time.delta_seconds();
+
+    for (mut animation, mut transform) in rig_query.iter_mut() {
+        let walking = animation.state == ViewmodelState::Walking;
+        let bob_target = if walking && !animation.clip_playing {
+            1.0
+        } else {
+            0.0
+        };
+        animation.bob_weight += (bob_target - animation.bob_weight) * (delta * BOB_BLEND).min(1.0);
+        animation.bob_phase = (animation.bob_phase + speed * delta * PI / BOB_STRIDE) % (2.0 * PI);
+
+        let mut offset = Vec3::ZERO;
+        let mut tilt = 0.0;
+
+        // Side to side once per stride and down at every footstep, with a slow breath when the
+        // player is standing still.
+        let bob = animation.bob_weight;
+        offset.x += bob * BOB_SWAY * animation.bob_phase.sin();
+        offset.y += bob * BOB_HEIGHT * ((2.0 * animation.bob_phase).cos() - 1.0) / 2.0;
+        let breath = (time.elapsed_seconds() * BREATHS_PER_SECOND * 2.0 * PI).sin();
+        offset.y += (1.0 - bob) * BREATH_HEIGHT * breath;
+
+        if !animation.clip_playing {
+            let progress = animation.progress();
+            match animation.state {
+                ViewmodelState::Equipping => {
+                    let remaining = (1.0 - progress).powi(2);
+                    offset.y -= EQUIP_DROP * remaining;
+                    tilt -= EQUIP_TILT * remaining;
+                }
+                ViewmodelState::Using => {
+                    let kick = (progress * PI).sin();
+                    offset.z += USE_KICK * kick;
+                    tilt += USE_TILT * kick;
+                }
+                ViewmodelState::Idle | ViewmodelState::Walking => {}
+            }
+        }
+
+        *transform = Transform::from_translation(offset).with_rotation(Quat::from_rotation_x(tilt));
+    }
+}
@@ -0,0 +1,343 @@
+// The first-person viewmodel: the player's hand and the tool in it. They're drawn by a camera of
+// their own, on their own render layer and with a fixed field of view, after the world has been
+// drawn and with the depth cleared, so the tool never clips into a wall or stretches with the
+// FOV setting.
+
+pub mod animation;
+
+use bevy::asset::LoadState;
+use bevy::gltf::Gltf;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::camera::CameraUpdateSystem;
+use bevy::render::view::{Layer, RenderLayers};
+use bevy::utils::HashMap;
+
+use self::animation::{animate_viewmodel, update_viewmodel_state, ViewmodelAnimation};
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct ViewmodelPlugin;
+
+impl Plugin for ViewmodelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeldTool>()
+            .init_resource::<ViewmodelModels>()
+            .add_event::<ToolUsed>()
+            .add_systems(
+                Update,
+                (spawn_viewmodel, layer_viewmodel_meshes, show_held_tool),
+            )
+            .add_systems(
+                Update,
+                (use_tools, update_viewmodel_state, animate_viewmodel)
+                    .chain()
+                    .after(show_held_tool)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(PostUpdate, sync_viewmodel_camera.before(CameraUpdateSystem));
+    }
+}
+
+pub const VIEWMODEL_LAYER: Layer = 1;
+const VIEWMODEL_FOV_DEGREES: f32 = 50.0;
+// Close enough for the hand, which sits right in front of the camera.
+const VIEWMODEL_NEAR: f32 = 0.01;
+const HAND_POSITION: Vec3 = Vec3::new(0.4, -0.7, -0.4);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Tool {
+    #[default]
+    Scanner,
+    BoxCutter,
+    LabelPrinter,
+    TapeGun,
+    Tablet,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 5] = [
+        Tool::Scanner,
+        Tool::BoxCutter,
+        Tool::LabelPrinter,
+        Tool::TapeGun,
+        Tool::Tablet,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tool::Scanner => "Scanner",
+            Tool::BoxCutter => "Box cutter",
+            Tool::LabelPrinter => "Label printer",
+            Tool::TapeGun => "Tape gun",
+            Tool::Tablet => "Tablet",
+        }
+    }
+
+    fn model_path(&self) -> &'static str {
+        match self {
+            Tool::Scanner => "models/scanner.glb",
+            Tool::BoxCutter => "models/box_cutter.glb",
+            Tool::LabelPrinter => "models/label_printer.glb",
+            Tool::TapeGun => "models/tape_gun.glb",
+            Tool::Tablet => "models/tablet.glb",
+        }
+    }
+
+    // Where the tool sits in the hand, relative to the camera.
+    fn hold_position(&self) -> Vec3 {
+        match self {
+            Tool::Scanner => Vec3::new(0.3, -0.2, -0.5),
+            Tool::BoxCutter => Vec3::new(0.3, -0.25, -0.45),
+            Tool::LabelPrinter => Vec3::new(0.3, -0.25, -0.5),
+            Tool::TapeGun => Vec3::new(0.3, -0.25, -0.5),
+            // Held flat and a little further in, so the screen can be read.
+            Tool::Tablet => Vec3::new(0.15, -0.3, -0.55),
+        }
+    }
+
+    // Size and color of the block held instead when the tool's model is missing.
+    fn placeholder(&self) -> (Vec3, Color) {
+        match self {
+            Tool::Scanner => (Vec3::new(0.08, 0.05, 0.22), Color::rgb(0.9, 0.75, 0.1)),
+            Tool::BoxCutter => (Vec3::new(0.03, 0.02, 0.15), Color::rgb(0.95, 0.45, 0.1)),
+            Tool::LabelPrinter => (Vec3::new(0.1, 0.08, 0.16), Color::rgb(0.25, 0.25, 0.28)),
+            Tool::TapeGun => (Vec3::new(0.06, 0.16, 0.18), Color::rgb(0.8, 0.15, 0.1)),
+            Tool::Tablet => (Vec3::new(0.22, 0.015, 0.16), Color::rgb(0.05, 0.05, 0.06)),
+        }
+    }
+}
+
+// The tool in the player's hand, picked off the hotbar (see `Inventory`).
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct HeldTool(pub Tool);
+
+// Sent when the player uses a tool. Whatever the tool does happens wherever it's read.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ToolUsed {
+    pub tool: Tool,
+}
+
+// Loaded at startup rather than with the level, so a missing tool model doesn't hold up the
+// loading screen.
+#[derive(Resource, Debug)]
+pub struct ViewmodelModels {
+    pub hand: Handle<Gltf>,
+    pub tools: HashMap<Tool, Handle<Gltf>>,
+}
+
+impl FromWorld for ViewmodelModels {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        ViewmodelModels {
+            hand: asset_server.load("models/playerhand.glb"),
+            tools: Tool::ALL
+                .iter()
+                .map(|tool| (*tool, asset_server.load(tool.model_path())))
+                .collect(),
+        }
+    }
+}
+
+fn model_scene(gltfs: &Assets<Gltf>, handle: &Handle<Gltf>) -> Option<Handle<Scene>> {
+    let gltf = gltfs.get(handle)?;
+    gltf.named_scenes
+        .get("Scene")
+        .or(gltf.default_scene.as_ref())
+        .cloned()
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ViewmodelCamera;
+
+// Follows the player camera around, and carries the hand and the tool. Animating the rig moves
+// them together.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ViewmodelRig {
+    pub tool: Tool,
+    pub model: Option<Entity>,
+    hand: Option<Entity>,
+}
+
+// On the scenes drawn on the viewmodel layer, so their meshes can be moved onto it once they
+// spawn.
+#[derive(Component, Clone, Copy, Debug)]
+struct ViewmodelPart;
+
+fn spawn_viewmodel(
+    mut commands: Commands,
+    held: Res<HeldTool>,
+    camera_query: Query<Entity, Added<PlayerInteractionSystem>>,
+) {
+    for camera in camera_query.iter() {
+        commands.entity(camera).with_children(|parent| {
+            parent.spawn((
+                Camera3dBundle {
+                    camera: Camera {
+                        order: 1,
+                        clear_color: ClearColorConfig::None,
+                        ..default()
+                    },
+                    projection: PerspectiveProjection {
+                        fov: VIEWMODEL_FOV_DEGREES.to_radians(),
+                        near: VIEWMODEL_NEAR,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                },
+                RenderLayers::layer(VIEWMODEL_LAYER),
+                ViewmodelCamera,
+            ));
+            parent.spawn((
+                SpatialBundle::default(),
+                ViewmodelRig {
+                    tool: held.0,
+                    model: None,
+                    hand: None,
+                },
+                ViewmodelAnimation::default(),
+            ));
+        });
+    }
+}
+
+// Scene meshes spawn a few frames after their scene, so they're moved onto the viewmodel layer
+// as they turn up. They'd cast shadows from where the camera is otherwise.
+fn layer_viewmodel_meshes(
+    mut commands: Commands,
+    mesh_query: Query<Entity, Added<Handle<Mesh>>>,
+    parent_query: Query<&Parent>,
+    part_query: Query<(), With<ViewmodelPart>>,
+) {
+    for mesh in mesh_query.iter() {
+        if parent_query
+            .iter_ancestors(mesh)
+            .any(|ancestor| part_query.contains(ancestor))
+        {
+            commands
+                .entity(mesh)
+                .insert((RenderLayers::layer(VIEWMODEL_LAYER), NotShadowCaster));
+        }
+    }
+}
+
+fn spawn_part(
+    commands: &mut Commands,
+    rig: Entity,
+    scene: Handle<Scene>,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            SceneBundle {
+                scene,
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            ViewmodelPart,
+        ))
+        .set_parent(rig)
+        .id()
+}
+
+type PlaceholderModel = (Handle<Mesh>, Handle<StandardMaterial>);
+
+// Swaps the model in the rig's hand for the held tool's, and brings it out. Models that haven't
+// loaded yet are put in the hand once they have, and a tool whose model failed to load is held
+// as a plain block instead.
+#[allow(clippy::too_many_arguments)]
+fn show_held_tool(
+    mut commands: Commands,
+    held: Res<HeldTool>,
+    models: Res<ViewmodelModels>,
+    gltfs: Res<Assets<Gltf>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut placeholders: Local<HashMap<Tool, PlaceholderModel>>,
+    mut rig_query: Query<(Entity, &mut ViewmodelRig, &mut ViewmodelAnimation)>,
+) {
+    for (rig_entity, mut rig, mut animation) in rig_query.iter_mut() {
+        if rig.hand.is_none() {
+            if let Some(scene) = model_scene(&gltfs, &models.hand) {
+                rig.hand = Some(spawn_part(&mut commands, rig_entity, scene, HAND_POSITION));
+            }
+        }
+
+        if rig.tool != held.0 {
+            if let Some(model) = rig.model.take() {
+                commands.entity(model).despawn_recursive();
+            }
+            rig.tool = held.0;
+            animation.equip();
+        }
+
+        if rig.model.is_none() {
+            let handle = models.tools.get(&rig.tool);
+            let position = rig.tool.hold_position();
+            let failed =
+                handle.is_none_or(|handle| asset_server.load_state(handle) == LoadState::Failed);
+            if let Some(scene) = handle.and_then(|handle| model_scene(&gltfs, handle)) {
+                rig.model = Some(spawn_part(&mut commands, rig_entity, scene, position));
+            } else if failed {
+                let (size, color) = rig.tool.placeholder();
+                let (mesh, material) = placeholders
+                    .entry(rig.tool)
+                    .or_insert_with(|| (meshes.add(Cuboid::from_size(size)), materials.add(color)))
+                    .clone();
+                let model = commands
+                    .spawn((
+                        PbrBundle {
+                            mesh,
+                            material,
+                            transform: Transform::from_translation(position),
+                            ..default()
+                        },
+                        RenderLayers::layer(VIEWMODEL_LAYER),
+                        NotShadowCaster,
+                    ))
+                    .set_parent(rig_entity)
+                    .id();
+                rig.model = Some(model);
+            }
+        }
+    }
+}
+
+// Use Tool uses whatever's in hand. Scan brings out the scanner and uses it in one go.
+pub fn use_tools(
+    actions: Res<ActionState>,
+    mut held: ResMut<HeldTool>,
+    mut uses: EventWriter<ToolUsed>,
+) {
+    if actions.just_pressed(Action::Scan) {
+        held.set_if_neq(HeldTool(Tool::Scanner));
+        uses.send(ToolUsed {
+            tool: Tool::Scanner,
+        });
+    } else if actions.just_pressed(Action::UseTool) {
+        uses.send(ToolUsed { tool: held.0 });
+    }
+}
+
+// The viewmodel is drawn wherever the world is, which is an image while the resolution is
+// scaled down, and needs the same HDR setting to share it.
+fn sync_viewmodel_camera(
+    player_query: Query<&Camera, (With<PlayerInteractionSystem>, Without<ViewmodelCamera>)>,
+    mut viewmodel_query: Query<&mut Camera, With<ViewmodelCamera>>,
+) {
+    let Ok(player_camera) = player_query.get_single() else {
+        return;
+    };
+
+    for mut camera in viewmodel_query.iter_mut() {
+        if camera.target.normalize(None) != player_camera.target.normalize(None) {
+            camera.target = player_camera.target.clone();
+        }
+        if camera.hdr != player_camera.hdr {
+            camera.hdr = player_camera.hdr;
+        }
+    }
+}
@@ -1,12 +1,23 @@
 use bevy::math::Vec2;
 use bevy::prelude::*;
+use bevy_fps_controller::controller::LogicalPlayer;
+use bevy_rapier3d::prelude::*;
+
+use crate::player::controller::PlayerInteractionSystem;
+
 pub struct PlayerRaycast;
 
 impl Plugin for PlayerRaycast {
-    fn build(&self, app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_interaction_target);
+    }
 }
 
-// Lazily calling Camera transform. There's only one camera, so no need for a special marker.
+// How far in front of the camera the player can reach.
+const INTERACTION_RANGE: f32 = 3.0;
+
+// Lazily calling Camera transform. The player camera is the one with the interaction system, so
+// no need for a special marker.
 // Transform has a method for getting forward vector; to be used with Rapier's raycast.
 //
 
@@ -21,3 +32,27 @@ fn get_viewport_center(window: &Window) -> Vec2 {
 
 //Raycast constantly updates, modiftying the player's interaction system resource (defined in
 //controller.rs).
+fn update_interaction_target(
+    rapier_context: Res<RapierContext>,
+    mut camera_query: Query<(&GlobalTransform, &mut PlayerInteractionSystem)>,
+    player_query: Query<Entity, With<LogicalPlayer>>,
+) {
+    // Only dynamic bodies can be interacted with, so the level geometry never counts as an item.
+    let mut filter = QueryFilter::new().exclude_sensors().only_dynamic();
+    if let Ok(player) = player_query.get_single() {
+        filter = filter.exclude_rigid_body(player);
+    }
+
+    for (camera_transform, mut interaction) in &mut camera_query {
+        let hit = rapier_context.cast_ray(
+            camera_transform.translation(),
+            camera_transform.forward(),
+            INTERACTION_RANGE,
+            true,
+            filter,
+        );
+
+        interaction.interactable_entity = hit.map(|(entity, _)| entity);
+        interaction.is_looking_at_item = hit.is_some();
+    }
+}
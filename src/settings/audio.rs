@@ -0,0 +1,103 @@
+// Audio settings: a volume for each bus. Every sound plays on one bus (see `sound`), and the
+// master volume scales them all.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{check_range, serialize_rounded};
+use crate::settings::SETTINGS_PATH;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumeBus {
+    Master,
+    Sfx,
+    Ambience,
+    Ui,
+}
+
+impl VolumeBus {
+    pub const ALL: [VolumeBus; 4] = [
+        VolumeBus::Master,
+        VolumeBus::Sfx,
+        VolumeBus::Ambience,
+        VolumeBus::Ui,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VolumeBus::Master => "Master volume",
+            VolumeBus::Sfx => "Effects volume",
+            VolumeBus::Ambience => "Ambience volume",
+            VolumeBus::Ui => "Interface volume",
+        }
+    }
+
+    // The bus's key in the settings file, which is also its field name.
+    fn config_key(&self) -> &'static str {
+        match self {
+            VolumeBus::Master => "master_volume",
+            VolumeBus::Sfx => "sfx_volume",
+            VolumeBus::Ambience => "ambience_volume",
+            VolumeBus::Ui => "ui_volume",
+        }
+    }
+}
+
+// Volumes go from 0.0 to 1.0.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    #[serde(serialize_with = "serialize_rounded")]
+    pub master_volume: f32,
+    #[serde(serialize_with = "serialize_rounded")]
+    pub sfx_volume: f32,
+    #[serde(serialize_with = "serialize_rounded")]
+    pub ambience_volume: f32,
+    #[serde(serialize_with = "serialize_rounded")]
+    pub ui_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            ambience_volume: 0.7,
+            ui_volume: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn volume(&self, bus: VolumeBus) -> f32 {
+        match bus {
+            VolumeBus::Master => self.master_volume,
+            VolumeBus::Sfx => self.sfx_volume,
+            VolumeBus::Ambience => self.ambience_volume,
+            VolumeBus::Ui => self.ui_volume,
+        }
+    }
+
+    pub fn volume_mut(&mut self, bus: VolumeBus) -> &mut f32 {
+        match bus {
+            VolumeBus::Master => &mut self.master_volume,
+            VolumeBus::Sfx => &mut self.sfx_volume,
+            VolumeBus::Ambience => &mut self.ambience_volume,
+            VolumeBus::Ui => &mut self.ui_volume,
+        }
+    }
+
+    // Puts back the defaults for volumes the settings file had out of range.
+    pub fn validate(&mut self) {
+        let defaults = AudioSettings::default();
+        for bus in VolumeBus::ALL {
+            check_range(
+                SETTINGS_PATH,
+                bus.config_key(),
+                self.volume_mut(bus),
+                0.0,
+                1.0,
+                defaults.volume(bus),
+            );
+        }
+    }
+}
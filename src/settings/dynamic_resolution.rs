@@ -0,0 +1,223 @@
+// Dynamic resolution. When frames take longer than the frame limit allows, the player camera
+// renders the world into a smaller image, which a second camera stretches over the window; the
+// HUD is drawn by that second camera, so it stays sharp. The scale creeps back up once there's
+// time to spare. The viewmodel is drawn into the same image, on top of the world.
+//
+// The frame limiter and vsync pad every frame out to the target, so the frame time alone can only
+// say when frames are too slow. Whether there's time to spare is judged from how long the frame's
+// own work took, from the start of `First` to the end of `Last`. That doesn't see the GPU, so a
+// step down holds the scale there for a while rather than stepping straight back up.
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::utils::Instant;
+use bevy::window::{PrimaryWindow, WindowRef};
+
+use crate::player::controller::PlayerInteractionSystem;
+use crate::settings::graphics::FrameLimit;
+use crate::settings::Settings;
+
+pub struct DynamicResolutionPlugin;
+
+impl Plugin for DynamicResolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderScale>()
+            .init_resource::<FrameWork>()
+            .add_systems(First, start_frame_work)
+            .add_systems(Update, (adjust_render_scale, apply_render_scale).chain())
+            .add_systems(Last, end_frame_work);
+    }
+}
+
+const MIN_SCALE: f32 = 0.5;
+const SCALE_STEP: f32 = 0.05;
+const ADJUST_SECONDS: f32 = 0.5;
+// How much of each new frame time goes into the running average.
+const SMOOTHING: f32 = 0.1;
+// Frames slower than this share of the target scale down; frames whose work takes less than this
+// share scale up.
+const TOO_SLOW: f32 = 1.05;
+const FAST_ENOUGH: f32 = 0.85;
+// How long after a step down the scale is held before it can go back up.
+const HOLD_SECONDS: f32 = 5.0;
+// Without a fixed frame limit there's no target to read, so aim for this.
+const FALLBACK_TARGET_FPS: f32 = 60.0;
+
+// The share of the window's resolution the world is rendered at.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct RenderScale(pub f32);
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        RenderScale(1.0)
+    }
+}
+
+// The camera drawing the scaled image, and the sprite it's drawn on.
+#[derive(Component)]
+struct ScaledView;
+
+#[derive(Component)]
+struct ScaledViewSprite;
+
+// When the current frame's work started, and how long the last frame's took.
+#[derive(Resource, Default)]
+struct FrameWork {
+    started: Option<Instant>,
+    seconds: f32,
+}
+
+fn start_frame_work(mut work: ResMut<FrameWork>) {
+    work.started = Some(Instant::now());
+}
+
+fn end_frame_work(mut work: ResMut<FrameWork>) {
+    if let Some(started) = work.started.take() {
+        work.seconds = started.elapsed().as_secs_f32();
+    }
+}
+
+fn target_frame_seconds(limit: FrameLimit) -> f32 {
+    match limit {
+        FrameLimit::Fps(fps) => 1.0 / fps,
+        FrameLimit::Auto | FrameLimit::Off => 1.0 / FALLBACK_TARGET_FPS,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn adjust_render_scale(
+    time: Res<Time<Real>>,
+    work: Res<FrameWork>,
+    settings: Res<Settings>,
+    mut render_scale: ResMut<RenderScale>,
+    mut average_frame_seconds: Local<f32>,
+    mut average_work_seconds: Local<f32>,
+    mut adjust_in: Local<f32>,
+    mut hold_for: Local<f32>,
+) {
+    if !settings.graphics.dynamic_resolution {
+        render_scale.set_if_neq(RenderScale(1.0));
+        return;
+    }
+
+    let frame_seconds = time.delta_seconds();
+    *average_frame_seconds += (frame_seconds - *average_frame_seconds) * SMOOTHING;
+    *average_work_seconds += (work.seconds - *average_work_seconds) * SMOOTHING;
+    *hold_for -= frame_seconds;
+    *adjust_in -= frame_seconds;
+    if *adjust_in > 0.0 {
+        return;
+    }
+    *adjust_in = ADJUST_SECONDS;
+
+    let target = target_frame_seconds(settings.graphics.frame_limit);
+    let scale = render_scale.0;
+    let scale = if *average_frame_seconds > target * TOO_SLOW {
+        *hold_for = HOLD_SECONDS;
+        (scale - SCALE_STEP).max(MIN_SCALE)
+    } else if *hold_for <= 0.0 && *average_work_seconds < target * FAST_ENOUGH {
+        (scale + SCALE_STEP).min(1.0)
+    } else {
+        scale
+    };
+    render_scale.set_if_neq(RenderScale(scale));
+}
+
+fn render_image(size: Extent3d) -> Image {
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("scaled view"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // Fills the image with zeroes.
+    image.resize(size);
+    image
+}
+
+// Keeps the scaled view matching the scale and the window, and hands rendering back to the
+// window when the scale is back to full.
+fn apply_render_scale(
+    mut commands: Commands,
+    render_scale: Res<RenderScale>,
+    mut images: ResMut<Assets<Image>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<&mut Camera, With<PlayerInteractionSystem>>,
+    view_query: Query<Entity, With<ScaledView>>,
+    mut sprite_query: Query<(Entity, &mut Sprite, &Handle<Image>), With<ScaledViewSprite>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok(mut camera) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    if render_scale.0 >= 1.0 {
+        if !matches!(camera.target, RenderTarget::Window(_)) {
+            camera.target = RenderTarget::Window(WindowRef::Primary);
+        }
+        for view in view_query.iter() {
+            commands.entity(view).despawn_recursive();
+        }
+        for (sprite, _, _) in sprite_query.iter() {
+            commands.entity(sprite).despawn_recursive();
+        }
+        return;
+    }
+
+    let size = Extent3d {
+        width: ((window.physical_width() as f32 * render_scale.0) as u32).max(1),
+        height: ((window.physical_height() as f32 * render_scale.0) as u32).max(1),
+        depth_or_array_layers: 1,
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+
+    if let Ok((_, mut sprite, image)) = sprite_query.get_single_mut() {
+        if let Some(image) = images.get_mut(image) {
+            if image.texture_descriptor.size != size {
+                image.resize(size);
+            }
+        }
+        if sprite.custom_size != Some(window_size) {
+            sprite.custom_size = Some(window_size);
+        }
+        return;
+    }
+
+    let image = images.add(render_image(size));
+    camera.target = RenderTarget::Image(image.clone());
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 2,
+                ..default()
+            },
+            ..default()
+        },
+        ScaledView,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: image,
+            sprite: Sprite {
+                custom_size: Some(window_size),
+                ..default()
+            },
+            ..default()
+        },
+        ScaledViewSprite,
+    ));
+}
@@ -0,0 +1,424 @@
+// Graphics settings: the window, the player camera, shadows, lights, ambient light and frame
+// pacing. Everything here can be changed while playing, so players on weaker machines can tune
+// it without restarting, either one option at a time or with a quality preset.
+
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::pbr::DirectionalLightShadowMap;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use bevy_atmosphere::plugin::AtmosphereCamera;
+use bevy_framepace::{FramepaceSettings, Limiter};
+use serde::{Deserialize, Serialize};
+
+use crate::config::check_range;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::settings::{Settings, SETTINGS_PATH};
+
+pub struct GraphicsSettingsPlugin;
+
+impl Plugin for GraphicsSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (apply_window_settings, apply_render_settings).run_if(resource_changed::<Settings>),
+                apply_camera_settings,
+                apply_light_settings,
+            ),
+        );
+    }
+}
+
+pub const MIN_FOV_DEGREES: f32 = 50.0;
+pub const MAX_FOV_DEGREES: f32 = 110.0;
+pub const SHADOW_MAP_SIZES: [usize; 5] = [512, 1024, 2048, 4096, 8192];
+const MAX_AMBIENT_BRIGHTNESS: f32 = 1000.0;
+// Frame caps below this feel broken rather than slow.
+const MIN_FRAME_LIMIT: f32 = 20.0;
+const MAX_FRAME_LIMIT: f32 = 360.0;
+
+// Written in the settings file in snake case.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BloomPreset {
+    Off,
+    Natural,
+    OldSchool,
+    ScreenBlur,
+}
+
+impl BloomPreset {
+    pub const ALL: [BloomPreset; 4] = [
+        BloomPreset::Off,
+        BloomPreset::Natural,
+        BloomPreset::OldSchool,
+        BloomPreset::ScreenBlur,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BloomPreset::Off => "off",
+            BloomPreset::Natural => "natural",
+            BloomPreset::OldSchool => "old school",
+            BloomPreset::ScreenBlur => "screen blur",
+        }
+    }
+
+    fn settings(&self) -> Option<BloomSettings> {
+        match self {
+            BloomPreset::Off => None,
+            BloomPreset::Natural => Some(BloomSettings::NATURAL),
+            BloomPreset::OldSchool => Some(BloomSettings::OLD_SCHOOL),
+            BloomPreset::ScreenBlur => Some(BloomSettings::SCREEN_BLUR),
+        }
+    }
+}
+
+// Written in the settings file as "auto", "off" or a number.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameLimit {
+    // Matches the monitor's refresh rate.
+    Auto,
+    Off,
+    #[serde(untagged)]
+    Fps(f32),
+}
+
+impl FrameLimit {
+    // What the settings menu cycles through.
+    pub const CHOICES: [FrameLimit; 6] = [
+        FrameLimit::Auto,
+        FrameLimit::Fps(30.0),
+        FrameLimit::Fps(60.0),
+        FrameLimit::Fps(120.0),
+        FrameLimit::Fps(144.0),
+        FrameLimit::Off,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            FrameLimit::Auto => "auto".to_string(),
+            FrameLimit::Off => "off".to_string(),
+            FrameLimit::Fps(fps) => format!("{:.0}", fps),
+        }
+    }
+
+    fn limiter(&self) -> Limiter {
+        match self {
+            FrameLimit::Auto => Limiter::Auto,
+            FrameLimit::Off => Limiter::Off,
+            FrameLimit::Fps(fps) => Limiter::from_framerate(*fps as f64),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 4] = [
+        QualityPreset::Low,
+        QualityPreset::Medium,
+        QualityPreset::High,
+        QualityPreset::Ultra,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            QualityPreset::Low => "low",
+            QualityPreset::Medium => "medium",
+            QualityPreset::High => "high",
+            QualityPreset::Ultra => "ultra",
+        }
+    }
+
+    // Shadow map size, point light shadows, HDR, bloom, atmosphere and MSAA.
+    fn options(&self) -> (usize, bool, bool, BloomPreset, bool, bool) {
+        match self {
+            QualityPreset::Low => (1024, false, false, BloomPreset::Off, false, false),
+            QualityPreset::Medium => (2048, false, true, BloomPreset::Natural, false, true),
+            QualityPreset::High => (4096, true, true, BloomPreset::OldSchool, true, true),
+            QualityPreset::Ultra => (8192, true, true, BloomPreset::OldSchool, true, true),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(from = "GraphicsFile", into = "GraphicsFile")]
+pub struct GraphicsSettings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub fov_degrees: f32,
+    pub shadow_map_size: usize,
+    pub point_light_shadows: bool,
+    pub hdr: bool,
+    // In daylight; it dims at night (see `lighting::day_night`).
+    pub ambient_brightness: f32,
+    pub bloom: BloomPreset,
+    pub atmosphere: bool,
+    pub msaa: bool,
+    pub frame_limit: FrameLimit,
+    // Renders the world at a lower resolution when frames take longer than the frame limit allows.
+    pub dynamic_resolution: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        let mut graphics = GraphicsSettings {
+            fullscreen: false,
+            vsync: true,
+            fov_degrees: 72.0,
+            shadow_map_size: 0,
+            point_light_shadows: false,
+            hdr: false,
+            ambient_brightness: 100.0,
+            bloom: BloomPreset::Off,
+            atmosphere: false,
+            msaa: false,
+            frame_limit: FrameLimit::Auto,
+            dynamic_resolution: false,
+        };
+        graphics.apply_preset(QualityPreset::High);
+        graphics
+    }
+}
+
+// The item after `current` in `choices`, wrapping around.
+pub fn next_choice<T: PartialEq + Copy>(choices: &[T], current: T) -> T {
+    let index = choices.iter().position(|choice| *choice == current);
+    choices[index.map_or(0, |index| (index + 1) % choices.len())]
+}
+
+impl GraphicsSettings {
+    pub fn apply_preset(&mut self, preset: QualityPreset) {
+        let (shadow_map_size, point_light_shadows, hdr, bloom, atmosphere, msaa) = preset.options();
+        self.shadow_map_size = shadow_map_size;
+        self.point_light_shadows = point_light_shadows;
+        self.hdr = hdr;
+        self.bloom = bloom;
+        self.atmosphere = atmosphere;
+        self.msaa = msaa;
+    }
+
+    // The preset the options match, or `None` once they've been tuned by hand.
+    pub fn preset(&self) -> Option<QualityPreset> {
+        QualityPreset::ALL.into_iter().find(|preset| {
+            let mut graphics = *self;
+            graphics.apply_preset(*preset);
+            graphics == *self
+        })
+    }
+
+    // Puts back the defaults for values the settings file had out of range.
+    pub fn validate(&mut self) {
+        let defaults = GraphicsSettings::default();
+        check_range(
+            SETTINGS_PATH,
+            "fov",
+            &mut self.fov_degrees,
+            MIN_FOV_DEGREES,
+            MAX_FOV_DEGREES,
+            defaults.fov_degrees,
+        );
+        check_range(
+            SETTINGS_PATH,
+            "ambient_brightness",
+            &mut self.ambient_brightness,
+            0.0,
+            MAX_AMBIENT_BRIGHTNESS,
+            defaults.ambient_brightness,
+        );
+        if !SHADOW_MAP_SIZES.contains(&self.shadow_map_size) {
+            warn!(
+                "{}: shadow_map_size = {} isn't one of {:?}, using {}",
+                SETTINGS_PATH, self.shadow_map_size, SHADOW_MAP_SIZES, defaults.shadow_map_size
+            );
+            self.shadow_map_size = defaults.shadow_map_size;
+        }
+        if let FrameLimit::Fps(fps) = self.frame_limit {
+            if !(MIN_FRAME_LIMIT..=MAX_FRAME_LIMIT).contains(&fps) {
+                warn!(
+                    "{}: frame_limit = {} is outside {}..={}, using \"{}\"",
+                    SETTINGS_PATH,
+                    fps,
+                    MIN_FRAME_LIMIT,
+                    MAX_FRAME_LIMIT,
+                    defaults.frame_limit.label()
+                );
+                self.frame_limit = defaults.frame_limit;
+            }
+        }
+    }
+}
+
+// The graphics options as written in the settings file. `quality` sets all the options a preset
+// covers, and any of those options also in the file override it.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct GraphicsFile {
+    // Written for reference only: the options below it say the same thing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quality: Option<QualityPreset>,
+    fullscreen: Option<bool>,
+    vsync: Option<bool>,
+    fov: Option<f32>,
+    shadow_map_size: Option<usize>,
+    point_light_shadows: Option<bool>,
+    hdr: Option<bool>,
+    ambient_brightness: Option<f32>,
+    bloom: Option<BloomPreset>,
+    atmosphere: Option<bool>,
+    msaa: Option<bool>,
+    frame_limit: Option<FrameLimit>,
+    dynamic_resolution: Option<bool>,
+}
+
+impl From<GraphicsFile> for GraphicsSettings {
+    fn from(file: GraphicsFile) -> Self {
+        let mut graphics = GraphicsSettings::default();
+        if let Some(preset) = file.quality {
+            graphics.apply_preset(preset);
+        }
+        GraphicsSettings {
+            fullscreen: file.fullscreen.unwrap_or(graphics.fullscreen),
+            vsync: file.vsync.unwrap_or(graphics.vsync),
+            fov_degrees: file.fov.unwrap_or(graphics.fov_degrees),
+            shadow_map_size: file.shadow_map_size.unwrap_or(graphics.shadow_map_size),
+            point_light_shadows: file
+                .point_light_shadows
+                .unwrap_or(graphics.point_light_shadows),
+            hdr: file.hdr.unwrap_or(graphics.hdr),
+            ambient_brightness: file
+                .ambient_brightness
+                .unwrap_or(graphics.ambient_brightness),
+            bloom: file.bloom.unwrap_or(graphics.bloom),
+            atmosphere: file.atmosphere.unwrap_or(graphics.atmosphere),
+            msaa: file.msaa.unwrap_or(graphics.msaa),
+            frame_limit: file.frame_limit.unwrap_or(graphics.frame_limit),
+            dynamic_resolution: file
+                .dynamic_resolution
+                .unwrap_or(graphics.dynamic_resolution),
+        }
+    }
+}
+
+impl From<GraphicsSettings> for GraphicsFile {
+    fn from(graphics: GraphicsSettings) -> Self {
+        GraphicsFile {
+            quality: graphics.preset(),
+            fullscreen: Some(graphics.fullscreen),
+            vsync: Some(graphics.vsync),
+            fov: Some(graphics.fov_degrees),
+            shadow_map_size: Some(graphics.shadow_map_size),
+            point_light_shadows: Some(graphics.point_light_shadows),
+            hdr: Some(graphics.hdr),
+            ambient_brightness: Some(graphics.ambient_brightness),
+            bloom: Some(graphics.bloom),
+            atmosphere: Some(graphics.atmosphere),
+            msaa: Some(graphics.msaa),
+            frame_limit: Some(graphics.frame_limit),
+            dynamic_resolution: Some(graphics.dynamic_resolution),
+        }
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    window.mode = if settings.graphics.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    window.present_mode = if settings.graphics.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+fn apply_render_settings(
+    settings: Res<Settings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut framepace: ResMut<FramepaceSettings>,
+    mut msaa: ResMut<Msaa>,
+) {
+    shadow_map.size = settings.graphics.shadow_map_size;
+    *msaa = if settings.graphics.msaa {
+        Msaa::Sample4
+    } else {
+        Msaa::Off
+    };
+    framepace.limiter = settings.graphics.frame_limit.limiter();
+}
+
+// Also runs for the camera spawned when the level loads.
+fn apply_camera_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut camera_query: Query<(
+        Entity,
+        Ref<PlayerInteractionSystem>,
+        &mut Camera,
+        &mut Projection,
+    )>,
+) {
+    for (entity, added, mut camera, mut projection) in camera_query.iter_mut() {
+        if !settings.is_changed() && !added.is_added() {
+            continue;
+        }
+
+        camera.hdr = settings.graphics.hdr;
+        if settings.graphics.atmosphere {
+            commands.entity(entity).insert(AtmosphereCamera::default());
+        } else {
+            commands.entity(entity).remove::<AtmosphereCamera>();
+        }
+
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.graphics.fov_degrees.to_radians();
+        }
+        match settings.graphics.bloom.settings() {
+            Some(bloom) => commands.entity(entity).insert(bloom),
+            None => commands.entity(entity).remove::<BloomSettings>(),
+        };
+    }
+}
+
+// On lights whose shadows were turned off by the settings, so they can be turned back on without
+// turning on shadows for lights that never had them.
+#[derive(Component)]
+pub struct ShadowsDisabled;
+
+// Also runs for lights added after the settings last changed.
+pub fn apply_light_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut light_query: Query<(Entity, &mut PointLight, Has<ShadowsDisabled>)>,
+) {
+    for (entity, mut light, disabled) in light_query.iter_mut() {
+        if !settings.is_changed() && !light.is_added() {
+            continue;
+        }
+
+        if !settings.graphics.point_light_shadows && light.shadows_enabled {
+            light.shadows_enabled = false;
+            commands.entity(entity).insert(ShadowsDisabled);
+        } else if settings.graphics.point_light_shadows && disabled {
+            light.shadows_enabled = true;
+            commands.entity(entity).remove::<ShadowsDisabled>();
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Player settings, kept in `settings.toml` next to the game (see `config`).
+// Changing `Settings` applies it straight away; the menus save it, and edits to the file are
+// picked up while the game is running.
+
+pub mod audio;
+pub mod dynamic_resolution;
+pub mod graphics;
+
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::prelude::*;
+use bevy_fps_controller::controller::FpsController;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+use self::audio::AudioSettings;
+use self::dynamic_resolution::DynamicResolutionPlugin;
+use self::graphics::{GraphicsSettings, GraphicsSettingsPlugin};
+use crate::config::{check_range, load_config, modified_at, save_config, serialize_rounded};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .add_plugins(GraphicsSettingsPlugin)
+            .add_plugins(DynamicResolutionPlugin)
+            .add_systems(
+                Update,
+                (
+                    reload_edited_settings,
+                    apply_audio_settings.run_if(resource_changed::<Settings>),
+                    apply_control_settings,
+                ),
+            );
+    }
+}
+
+pub const SETTINGS_PATH: &str = "settings.toml";
+// How often the file is checked for edits, in real seconds so it still works while paused.
+const RELOAD_CHECK_SECONDS: f32 = 1.0;
+// The controller's own default, which a sensitivity of 1.0 matches.
+const BASE_MOUSE_SENSITIVITY: f32 = 0.001;
+pub const MIN_MOUSE_SENSITIVITY: f32 = 0.1;
+pub const MAX_MOUSE_SENSITIVITY: f32 = 5.0;
+pub const MIN_STICK_SENSITIVITY: f32 = 0.1;
+pub const MAX_STICK_SENSITIVITY: f32 = 5.0;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    // A multiple of the controller's default sensitivity.
+    #[serde(serialize_with = "serialize_rounded")]
+    pub mouse_sensitivity: f32,
+    // A multiple of the right stick's default turn rate (see `actions`).
+    #[serde(serialize_with = "serialize_rounded")]
+    pub stick_sensitivity: f32,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        ControlSettings {
+            mouse_sensitivity: 1.0,
+            stick_sensitivity: 1.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+    pub controls: ControlSettings,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        Settings::read().unwrap_or_default()
+    }
+
+    fn read() -> Option<Self> {
+        let mut settings: Settings = load_config(SETTINGS_PATH)?;
+        settings.validate();
+        Some(settings)
+    }
+
+    pub fn save(&self) {
+        save_config(SETTINGS_PATH, self);
+    }
+
+    // The file can parse and still have values the menus would never set.
+    fn validate(&mut self) {
+        self.graphics.validate();
+        self.audio.validate();
+        check_range(
+            SETTINGS_PATH,
+            "mouse_sensitivity",
+            &mut self.controls.mouse_sensitivity,
+            MIN_MOUSE_SENSITIVITY,
+            MAX_MOUSE_SENSITIVITY,
+            ControlSettings::default().mouse_sensitivity,
+        );
+        check_range(
+            SETTINGS_PATH,
+            "stick_sensitivity",
+            &mut self.controls.stick_sensitivity,
+            MIN_STICK_SENSITIVITY,
+            MAX_STICK_SENSITIVITY,
+            ControlSettings::default().stick_sensitivity,
+        );
+    }
+}
+
+fn reload_edited_settings(
+    time: Res<Time<Real>>,
+    mut settings: ResMut<Settings>,
+    mut check_in: Local<f32>,
+    mut seen_modified: Local<Option<Option<SystemTime>>>,
+) {
+    *check_in -= time.delta_seconds();
+    if *check_in > 0.0 {
+        return;
+    }
+    *check_in = RELOAD_CHECK_SECONDS;
+
+    let modified = modified_at(SETTINGS_PATH);
+    // The first check only notes the file as it was loaded.
+    let Some(last_modified) = seen_modified.replace(modified) else {
+        return;
+    };
+    if modified == last_modified {
+        return;
+    }
+
+    // Saving from the menu lands here too, but changes nothing. A half-finished edit that
+    // doesn't parse keeps the settings as they were.
+    let Some(reloaded) = Settings::read() else {
+        return;
+    };
+    if reloaded != *settings {
+        *settings = reloaded;
+        info!("Reloaded {}", SETTINGS_PATH);
+    }
+}
+
+// Only affects sounds started after the change; the ones already playing are turned up or down
+// with their bus (see `sound`).
+fn apply_audio_settings(settings: Res<Settings>, mut volume: ResMut<GlobalVolume>) {
+    volume.volume = Volume::new(settings.audio.master_volume);
+}
+
+// Also runs for controllers spawned after the settings last changed.
+fn apply_control_settings(
+    settings: Res<Settings>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    for mut controller in controller_query.iter_mut() {
+        if !settings.is_changed() && !controller.is_added() {
+            continue;
+        }
+        controller.sensitivity = BASE_MOUSE_SENSITIVITY * settings.controls.mouse_sensitivity;
+    }
+}
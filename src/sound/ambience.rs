@@ -0,0 +1,61 @@
+// Sounds that never stop: the warehouse's background ambience, and the hum of the conveyors
+// feeding each chute.
+
+use bevy::prelude::*;
+
+use crate::levels::asset_loader_plugin::{AssetLoaderState, MyAssetPack};
+use crate::levels::markers::{spawn_level_markers, ChuteInput};
+use crate::settings::audio::VolumeBus;
+use crate::settings::Settings;
+use crate::sound::{spawn_sound, Sound, SoundAssets};
+
+pub struct AmbiencePlugin;
+
+impl Plugin for AmbiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AssetLoaderState::Done),
+            (play_ambience, play_conveyor_hum.after(spawn_level_markers)),
+        );
+    }
+}
+
+const AMBIENCE_VOLUME: f32 = 1.0;
+const CONVEYOR_HUM_VOLUME: f32 = 0.5;
+
+fn play_ambience(mut commands: Commands, asset_pack: Res<MyAssetPack>, settings: Res<Settings>) {
+    let sound = Sound {
+        bus: VolumeBus::Ambience,
+        volume: AMBIENCE_VOLUME,
+    };
+    spawn_sound(
+        &mut commands,
+        asset_pack.ambience_sound.clone(),
+        sound,
+        None,
+        PlaybackSettings::LOOP,
+        &settings.audio,
+    );
+}
+
+fn play_conveyor_hum(
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+    chute_query: Query<&Transform, With<ChuteInput>>,
+) {
+    for transform in chute_query.iter() {
+        let sound = Sound {
+            bus: VolumeBus::Ambience,
+            volume: CONVEYOR_HUM_VOLUME,
+        };
+        spawn_sound(
+            &mut commands,
+            sounds.conveyor_hum.clone(),
+            sound,
+            Some(transform.translation),
+            PlaybackSettings::LOOP,
+            &settings.audio,
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// One-shot sound effects for what happens in the warehouse: scans, boxes hitting things, trucks
+// backing into the docks, the player's footsteps and menu clicks.
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_fps_controller::controller::LogicalPlayer;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::{Package, MAX_PACKAGE_WEIGHT};
+use crate::logistics::address::AddressException;
+use crate::logistics::customs::CustomsStatus;
+use crate::logistics::outbound::{OutboundTruck, TruckDeparted};
+use crate::player::items::scanner::{PackageScanned, ScanMissed};
+use crate::sound::{PlaySound, SoundKind};
+use crate::staff::jobs::is_blocked;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                report_package_impacts,
+                scanner_beeps,
+                box_impacts,
+                truck_arrivals,
+                footsteps,
+            )
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(Update, ui_clicks);
+    }
+}
+
+// Newtons. Rapier reports contacts pushing harder than this.
+const IMPACT_FORCE_THRESHOLD: f32 = 2000.0;
+// How far past the threshold an impact has to push to play at full volume.
+const FULL_VOLUME_FORCE: f32 = 100000.0;
+const MIN_IMPACT_VOLUME: f32 = 0.1;
+// Light boxes clack, heavy ones thud.
+const LIGHT_IMPACT_SPEED: f32 = 1.3;
+const HEAVY_IMPACT_SPEED: f32 = 0.7;
+
+// Meters walked between footsteps.
+const STRIDE: f32 = 1.6;
+// Meters per second. Slower than this is shuffling, and falling or jumping makes no steps.
+const MIN_STEP_SPEED: f32 = 0.5;
+const AIRBORNE_SPEED: f32 = 1.0;
+const FOOTSTEP_VOLUME: f32 = 0.6;
+
+fn report_package_impacts(mut commands: Commands, package_query: Query<Entity, Added<Package>>) {
+    for package in package_query.iter() {
+        commands.entity(package).insert((
+            ActiveEvents::CONTACT_FORCE_EVENTS,
+            ContactForceEventThreshold(IMPACT_FORCE_THRESHOLD),
+        ));
+    }
+}
+
+// Packages on hold beep like a miss, so the player knows to check the scan panel.
+fn scanner_beeps(
+    mut scans: EventReader<PackageScanned>,
+    mut misses: EventReader<ScanMissed>,
+    package_query: Query<(Option<&AddressException>, Option<&CustomsStatus>)>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for scan in scans.read() {
+        let blocked = package_query
+            .get(scan.package)
+            .is_ok_and(|(exception, customs)| is_blocked(exception, customs));
+        let kind = if blocked {
+            SoundKind::ScanFailure
+        } else {
+            SoundKind::ScanSuccess
+        };
+        sounds.send(PlaySound::new(kind));
+    }
+
+    for _ in misses.read() {
+        sounds.send(PlaySound::new(SoundKind::ScanFailure));
+    }
+}
+
+// Louder the harder the box hits, and lower the heavier it is.
+fn box_impacts(
+    mut contacts: EventReader<ContactForceEvent>,
+    package_query: Query<(&Package, &GlobalTransform)>,
+    mut sounds: EventWriter<PlaySound>,
+    mut pushing: Local<HashSet<Entity>>,
+) {
+    let mut pushing_now = HashSet::new();
+
+    for contact in contacts.read() {
+        for collider in [contact.collider1, contact.collider2] {
+            let Ok((package, transform)) = package_query.get(collider) else {
+                continue;
+            };
+            // A box resting on something keeps pushing on it with its weight, so only the first
+            // push is an impact.
+            let first_this_frame = pushing_now.insert(collider);
+            if !first_this_frame || pushing.contains(&collider) {
+                continue;
+            }
+
+            let force = contact.total_force_magnitude - IMPACT_FORCE_THRESHOLD;
+            let volume = (force / FULL_VOLUME_FORCE).clamp(MIN_IMPACT_VOLUME, 1.0);
+            let heaviness = (package.weight / MAX_PACKAGE_WEIGHT).clamp(0.0, 1.0);
+            let speed = LIGHT_IMPACT_SPEED + (HEAVY_IMPACT_SPEED - LIGHT_IMPACT_SPEED) * heaviness;
+            sounds.send(
+                PlaySound::new(SoundKind::BoxImpact)
+                    .at(transform.translation())
+                    .with_volume(volume)
+                    .with_speed(speed),
+            );
+        }
+    }
+
+    *pushing = pushing_now;
+}
+
+// The next truck backs into a dock as soon as the last one leaves it.
+fn truck_arrivals(
+    mut departures: EventReader<TruckDeparted>,
+    truck_query: Query<(&OutboundTruck, &GlobalTransform)>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for departure in departures.read() {
+        let truck = truck_query
+            .iter()
+            .find(|(truck, _)| truck.dock == departure.dock);
+        if let Some((_, transform)) = truck {
+            sounds.send(PlaySound::new(SoundKind::TruckArrival).at(transform.translation()));
+        }
+    }
+}
+
+fn footsteps(
+    time: Res<Time>,
+    player_query: Query<(&Velocity, &GlobalTransform), With<LogicalPlayer>>,
+    mut sounds: EventWriter<PlaySound>,
+    mut walked: Local<f32>,
+) {
+    let Ok((velocity, transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let ground_speed = Vec2::new(velocity.linvel.x, velocity.linvel.z).length();
+    if ground_speed < MIN_STEP_SPEED || velocity.linvel.y.abs() > AIRBORNE_SPEED {
+        *walked = 0.0;
+        return;
+    }
+
+    *walked += ground_speed * time.delta_seconds();
+    if *walked < STRIDE {
+        return;
+    }
+    *walked -= STRIDE;
+    sounds.send(
+        PlaySound::new(SoundKind::Footstep)
+            .at(transform.translation())
+            .with_volume(FOOTSTEP_VOLUME),
+    );
+}
+
+fn ui_clicks(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            sounds.send(PlaySound::new(SoundKind::UiClick));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// Sound. Gameplay doesn't play sounds itself: the systems in here listen for what happens in the
+// warehouse and turn it into `PlaySound` events. Every sound plays on a volume bus from the audio
+// settings, and most come from a place in the world, heard from the player camera.
+
+pub mod ambience;
+pub mod effects;
+
+use bevy::audio::{DefaultSpatialScale, SpatialScale, Volume};
+use bevy::prelude::*;
+
+use self::ambience::AmbiencePlugin;
+use self::effects::EffectsPlugin;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::settings::audio::{AudioSettings, VolumeBus};
+use crate::settings::Settings;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundAssets>()
+            .insert_resource(DefaultSpatialScale(SpatialScale::new(SPATIAL_SCALE)))
+            .add_event::<PlaySound>()
+            .add_plugins(AmbiencePlugin)
+            .add_plugins(EffectsPlugin)
+            .add_systems(
+                Update,
+                (
+                    add_listener,
+                    play_sounds,
+                    apply_bus_volumes.run_if(resource_changed::<Settings>),
+                ),
+            );
+    }
+}
+
+// Meters between the listener's ears.
+const EAR_GAP: f32 = 0.3;
+// Sounds fade with distance as if everything were this much closer, so the far end of the
+// warehouse can still be heard.
+const SPATIAL_SCALE: f32 = 0.25;
+
+// Loaded at startup rather than with the level, since the menus make sounds too.
+#[derive(Resource, Debug)]
+pub struct SoundAssets {
+    pub scan_success: Handle<AudioSource>,
+    pub scan_failure: Handle<AudioSource>,
+    pub box_impact: Handle<AudioSource>,
+    pub truck_arrival: Handle<AudioSource>,
+    pub footstep: Handle<AudioSource>,
+    pub ui_click: Handle<AudioSource>,
+    pub conveyor_hum: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SoundAssets {
+            scan_success: asset_server.load("audio/scan_success.wav"),
+            scan_failure: asset_server.load("audio/scan_failure.wav"),
+            box_impact: asset_server.load("audio/box_impact.wav"),
+            truck_arrival: asset_server.load("audio/truck_arrival.wav"),
+            footstep: asset_server.load("audio/footstep.wav"),
+            ui_click: asset_server.load("audio/ui_click.wav"),
+            conveyor_hum: asset_server.load("audio/conveyor_hum.wav"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SoundKind {
+    ScanSuccess,
+    ScanFailure,
+    BoxImpact,
+    TruckArrival,
+    Footstep,
+    UiClick,
+}
+
+impl SoundKind {
+    pub fn bus(&self) -> VolumeBus {
+        match self {
+            SoundKind::UiClick => VolumeBus::Ui,
+            _ => VolumeBus::Sfx,
+        }
+    }
+
+    fn source(&self, sounds: &SoundAssets) -> Handle<AudioSource> {
+        match self {
+            SoundKind::ScanSuccess => sounds.scan_success.clone(),
+            SoundKind::ScanFailure => sounds.scan_failure.clone(),
+            SoundKind::BoxImpact => sounds.box_impact.clone(),
+            SoundKind::TruckArrival => sounds.truck_arrival.clone(),
+            SoundKind::Footstep => sounds.footstep.clone(),
+            SoundKind::UiClick => sounds.ui_click.clone(),
+        }
+    }
+}
+
+// Plays a sound once.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySound {
+    pub kind: SoundKind,
+    // Where the sound comes from. Without one, it plays as if inside the player's head.
+    pub position: Option<Vec3>,
+    // 0.0 to 1.0, before the bus volume.
+    pub volume: f32,
+    // Playback speed, which also shifts the pitch.
+    pub speed: f32,
+}
+
+impl PlaySound {
+    pub fn new(kind: SoundKind) -> Self {
+        PlaySound {
+            kind,
+            position: None,
+            volume: 1.0,
+            speed: 1.0,
+        }
+    }
+
+    pub fn at(mut self, position: Vec3) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+// On every sound that's playing, so it can follow its bus's volume.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Sound {
+    pub bus: VolumeBus,
+    // 0.0 to 1.0, before the bus volume.
+    pub volume: f32,
+}
+
+impl Sound {
+    fn bus_volume(&self, audio: &AudioSettings) -> f32 {
+        audio.volume(self.bus) * self.volume
+    }
+}
+
+// The master volume is left to `GlobalVolume`, which every new sound is started with.
+pub fn spawn_sound(
+    commands: &mut Commands,
+    source: Handle<AudioSource>,
+    sound: Sound,
+    position: Option<Vec3>,
+    playback: PlaybackSettings,
+    audio: &AudioSettings,
+) {
+    let settings = playback
+        .with_volume(Volume::new(sound.bus_volume(audio)))
+        .with_spatial(position.is_some());
+    let mut entity = commands.spawn((AudioBundle { source, settings }, sound));
+    if let Some(position) = position {
+        entity.insert(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ));
+    }
+}
+
+fn add_listener(
+    mut commands: Commands,
+    camera_query: Query<Entity, Added<PlayerInteractionSystem>>,
+) {
+    for camera in camera_query.iter() {
+        commands
+            .entity(camera)
+            .insert(SpatialListener::new(EAR_GAP));
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut requests: EventReader<PlaySound>,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    for request in requests.read() {
+        let sound = Sound {
+            bus: request.kind.bus(),
+            volume: request.volume,
+        };
+        spawn_sound(
+            &mut commands,
+            request.kind.source(&sounds),
+            sound,
+            request.position,
+            PlaybackSettings::DESPAWN.with_speed(request.speed),
+            &settings.audio,
+        );
+    }
+}
+
+// Sounds already playing were started with the old volumes, master included, so they're set
+// again from scratch.
+fn apply_bus_volumes(
+    settings: Res<Settings>,
+    sound_query: Query<(&Sound, Option<&AudioSink>, Option<&SpatialAudioSink>)>,
+) {
+    for (sound, sink, spatial_sink) in sound_query.iter() {
+        let volume = settings.audio.master_volume * sound.bus_volume(&settings.audio);
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
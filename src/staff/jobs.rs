@@ -0,0 +1,208 @@
+// The job board. Work is read off the state of the floor every frame (a package still on the
+// inbound truck, a sorted package still sitting on the floor, a full pallet), so anything the player
+// does by hand simply takes the matching job off the board. Idle workers take the most urgent job
+// they're qualified for.
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier3d::prelude::*;
+use std::cmp::Ordering;
+
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+use crate::logistics::address::{AddressException, ExceptionStatus};
+use crate::logistics::containers::ShippingContainer;
+use crate::logistics::customs::CustomsStatus;
+use crate::logistics::outbound::OutboundTruck;
+use crate::logistics::sorting::SortedTo;
+use crate::staff::workers::Worker;
+
+pub struct JobsPlugin;
+
+impl Plugin for JobsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JobQueue>().add_systems(
+            Update,
+            (unload_inbound_cargo, queue_jobs, assign_jobs)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum JobKind {
+    UnloadTruck,
+    ScanAndSort,
+    MoveContainer,
+    LoadTruck,
+}
+
+impl JobKind {
+    // Later stages go first, so what's already in the building gets out before more piles up.
+    fn urgency(&self) -> u8 {
+        match self {
+            JobKind::LoadTruck => 3,
+            JobKind::MoveContainer => 2,
+            JobKind::ScanAndSort => 1,
+            JobKind::UnloadTruck => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Job {
+    pub kind: JobKind,
+    // The package or container being worked on.
+    pub target: Entity,
+}
+
+// How far a package has to be carried from where it was delivered to count as unloaded.
+const INBOUND_DOCK_RADIUS: f32 = 3.0;
+
+// On packages still aboard the inbound truck. Deliveries are dropped at the level's package spawn
+// points, which is where the truck is docked; the package is off it once it's been carried clear,
+// whether by a worker or by hand.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct InboundCargo {
+    pub docked_at: Vec3,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct JobQueue {
+    // Open jobs, most urgent first.
+    pub pending: Vec<Job>,
+    // Targets someone is already working on.
+    claimed: HashSet<Entity>,
+}
+
+impl JobQueue {
+    pub fn release(&mut self, target: Entity) {
+        self.claimed.remove(&target);
+    }
+
+    pub fn is_claimed(&self, target: Entity) -> bool {
+        self.claimed.contains(&target)
+    }
+
+    // Takes `target` off the board for whoever asked. False if someone already has it.
+    pub fn claim(&mut self, target: Entity) -> bool {
+        self.pending.retain(|job| job.target != target);
+        self.claimed.insert(target)
+    }
+}
+
+// Packages held up at customs or the exception desk aren't ready to be sorted.
+pub fn is_blocked(exception: Option<&AddressException>, customs: Option<&CustomsStatus>) -> bool {
+    let exception_open =
+        exception.is_some_and(|exception| exception.status != ExceptionStatus::Corrected);
+    let customs_open = customs.is_some_and(|status| *status != CustomsStatus::Cleared);
+
+    exception_open || customs_open
+}
+
+type PackageState<'a> = (
+    Entity,
+    &'a Package,
+    Option<&'a InboundCargo>,
+    Option<&'a SortedTo>,
+    Option<&'a AddressException>,
+    Option<&'a CustomsStatus>,
+    Option<&'a Parent>,
+);
+
+fn unload_inbound_cargo(
+    mut commands: Commands,
+    cargo_query: Query<(Entity, &InboundCargo, &GlobalTransform)>,
+) {
+    for (entity, cargo, transform) in cargo_query.iter() {
+        if transform.translation().distance(cargo.docked_at) > INBOUND_DOCK_RADIUS {
+            commands.entity(entity).remove::<InboundCargo>();
+        }
+    }
+}
+
+fn queue_jobs(
+    mut queue: ResMut<JobQueue>,
+    rapier_context: Res<RapierContext>,
+    package_query: Query<PackageState>,
+    container_query: Query<(Entity, &ShippingContainer, Option<&Parent>)>,
+    truck_query: Query<(Entity, &OutboundTruck)>,
+) {
+    let mut pending = Vec::new();
+    let has_trucks = !truck_query.is_empty();
+
+    for (entity, _, inbound, sorted_to, exception, customs, parent) in package_query.iter() {
+        // Consolidated, carried or already on a truck.
+        if parent.is_some()
+            || truck_query
+                .iter()
+                .any(|(_, truck)| truck.manifest.contains(&entity))
+        {
+            continue;
+        }
+
+        let kind = if sorted_to.is_some() {
+            if !has_trucks {
+                continue;
+            }
+            JobKind::LoadTruck
+        } else if inbound.is_some() {
+            JobKind::UnloadTruck
+        } else if !is_blocked(exception, customs) {
+            JobKind::ScanAndSort
+        } else {
+            continue;
+        };
+
+        pending.push(Job {
+            kind,
+            target: entity,
+        });
+    }
+
+    for (entity, container, parent) in container_query.iter() {
+        let on_truck = truck_query
+            .iter()
+            .any(|(truck, _)| rapier_context.intersection_pair(truck, entity) == Some(true));
+        if parent.is_some() || on_truck || !container.is_full() || !has_trucks {
+            continue;
+        }
+
+        pending.push(Job {
+            kind: JobKind::MoveContainer,
+            target: entity,
+        });
+    }
+
+    pending.retain(|job| !queue.is_claimed(job.target));
+    // Within a stage, packages go in routing order. Containers have no order of their own.
+    pending.sort_by(|a, b| {
+        let routing = match (package_query.get(a.target), package_query.get(b.target)) {
+            (Ok((_, a, ..)), Ok((_, b, ..))) => a.routing_order(b),
+            _ => Ordering::Equal,
+        };
+        b.kind.urgency().cmp(&a.kind.urgency()).then(routing)
+    });
+    queue.pending = pending;
+}
+
+fn assign_jobs(mut queue: ResMut<JobQueue>, mut worker_query: Query<&mut Worker>) {
+    for mut worker in worker_query.iter_mut() {
+        if worker.job.is_some() {
+            continue;
+        }
+
+        let Some(index) = queue
+            .pending
+            .iter()
+            .position(|job| worker.profile.skills.level(job.kind) > 0.0)
+        else {
+            continue;
+        };
+
+        let job = queue.pending[index];
+        queue.claim(job.target);
+        worker.start(job);
+    }
+}
@@ -0,0 +1,15 @@
+pub mod jobs;
+pub mod workers;
+
+use bevy::prelude::*;
+
+use self::jobs::JobsPlugin;
+use self::workers::WorkersPlugin;
+
+pub struct StaffPlugin;
+
+impl Plugin for StaffPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JobsPlugin).add_plugins(WorkersPlugin);
+    }
+}
@@ -0,0 +1,545 @@
+// Hired floor staff. Each worker walks to whatever their current job is about, picks it up,
+// carries it to where it belongs and sets it down. How quickly they handle things depends on
+// their skill at that kind of job, and every handover is a chance to make a mistake. Wages come
+// out of the wallet for every in-game hour a worker is on the payroll.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{thread_rng, Rng};
+
+use crate::game_state::GameState;
+use crate::levels::markers::{ChuteInput, PlayerSpawnPoint, TriggerZone};
+use crate::levels::package_data::{Damaged, Package, NAMES};
+use crate::logistics::containers::ShippingContainer;
+use crate::logistics::economy::Wallet;
+use crate::logistics::outbound::{free_cargo_slot, free_container_spot, OutboundTruck};
+use crate::logistics::shift::ShiftClock;
+use crate::logistics::sorting::destination_chute;
+use crate::logistics::tracking::{TrackingEventKind, TrackingLog};
+use crate::navigation::pathfinding::NavAgent;
+use crate::player::items::scanner::PackageScanned;
+use crate::staff::jobs::{InboundCargo, Job, JobKind, JobQueue};
+
+pub struct WorkersPlugin;
+
+impl Plugin for WorkersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HiringPool>()
+            .add_event::<HireWorker>()
+            .add_event::<FireWorker>()
+            .add_systems(
+                Update,
+                (hire_workers, fire_workers, do_jobs, walk_workers, pay_wages)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+const CANDIDATES: usize = 3;
+
+// Seconds a fully skilled worker takes to pick something up or set it down.
+const HANDLING_SECONDS: f32 = 1.0;
+// How close a worker has to get to a package, chute or truck slot to reach it. Containers are
+// measured from their middle, and agents are kept clear of their edges.
+const REACH: f32 = 1.5;
+const CONTAINER_REACH: f32 = 2.5;
+// Where carried things sit relative to the worker, out in front at chest height.
+const CARRY_OFFSET: Vec3 = Vec3::new(0.0, 0.4, -1.0);
+const GRAVITY: f32 = 9.81;
+
+// Used when the level has no player spawn marker.
+const WORKER_SPAWN_POINT: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+// 0.0 means the worker can't do that kind of job at all, 1.0 is as good as it gets.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorkerSkills {
+    pub unloading: f32,
+    pub sorting: f32,
+    // Also covers moving full containers out to the trucks, which is done by hand.
+    pub loading: f32,
+}
+
+impl WorkerSkills {
+    pub fn level(&self, kind: JobKind) -> f32 {
+        match kind {
+            JobKind::UnloadTruck => self.unloading,
+            JobKind::ScanAndSort => self.sorting,
+            JobKind::LoadTruck | JobKind::MoveContainer => self.loading,
+        }
+    }
+
+    pub fn average(&self) -> f32 {
+        (self.unloading + self.sorting + self.loading) / 3.0
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct WorkerProfile {
+    pub name: String,
+    pub skills: WorkerSkills,
+    // Meters per second.
+    pub walk_speed: f32,
+    // Chance of a mistake per handover for someone with no experience; skill brings it down.
+    pub error_rate: f32,
+    pub hourly_wage: f32,
+}
+
+impl WorkerProfile {
+    pub fn random() -> Self {
+        let mut rng = thread_rng();
+
+        let skills = WorkerSkills {
+            unloading: rng.gen_range(0.2..1.0),
+            sorting: rng.gen_range(0.2..1.0),
+            loading: rng.gen_range(0.2..1.0),
+        };
+        let walk_speed = rng.gen_range(2.5..4.5);
+        let error_rate = rng.gen_range(0.01..0.12);
+
+        // Skilled, quick and careful people cost more.
+        let hourly_wage =
+            14.0 + 8.0 * skills.average() + 1.5 * (walk_speed - 2.5) + 40.0 * (0.12 - error_rate);
+
+        WorkerProfile {
+            name: NAMES.choose(&mut rng).unwrap().to_string(),
+            skills,
+            walk_speed,
+            error_rate,
+            hourly_wage,
+        }
+    }
+
+    fn handling_time(&self, kind: JobKind) -> f32 {
+        HANDLING_SECONDS / (0.5 + self.skills.level(kind))
+    }
+
+    fn fumbles(&self, kind: JobKind) -> bool {
+        let chance = self.error_rate * (1.5 - self.skills.level(kind));
+        thread_rng().gen_bool(chance.clamp(0.0, 1.0) as f64)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorkerStep {
+    Idle,
+    Fetching,
+    PickingUp { remaining: f32 },
+    Delivering { drop_at: Transform },
+    Dropping { drop_at: Transform, remaining: f32 },
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Worker {
+    pub profile: WorkerProfile,
+    pub job: Option<Job>,
+    pub step: WorkerStep,
+    // Where the worker is headed this frame, if anywhere.
+    pub walk_to: Option<Vec3>,
+}
+
+impl Worker {
+    pub fn new(profile: WorkerProfile) -> Self {
+        Worker {
+            profile,
+            job: None,
+            step: WorkerStep::Idle,
+            walk_to: None,
+        }
+    }
+
+    pub fn start(&mut self, job: Job) {
+        self.job = Some(job);
+        self.step = WorkerStep::Fetching;
+    }
+
+    fn finish(&mut self) {
+        self.job = None;
+        self.step = WorkerStep::Idle;
+        self.walk_to = None;
+    }
+}
+
+// People looking for work. Hiring one replaces them with a new face.
+#[derive(Resource, Debug)]
+pub struct HiringPool {
+    pub candidates: Vec<WorkerProfile>,
+}
+
+impl Default for HiringPool {
+    fn default() -> Self {
+        HiringPool {
+            candidates: (0..CANDIDATES).map(|_| WorkerProfile::random()).collect(),
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct HireWorker {
+    pub candidate: usize,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct FireWorker {
+    pub worker: Entity,
+}
+
+fn hire_workers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut hires: EventReader<HireWorker>,
+    mut pool: ResMut<HiringPool>,
+    spawn_points: Query<&Transform, With<PlayerSpawnPoint>>,
+) {
+    for hire in hires.read() {
+        if hire.candidate >= pool.candidates.len() {
+            continue;
+        }
+        let profile = std::mem::replace(
+            &mut pool.candidates[hire.candidate],
+            WorkerProfile::random(),
+        );
+
+        let spawn_point = spawn_points
+            .iter()
+            .next()
+            .map_or(WORKER_SPAWN_POINT, |transform| transform.translation);
+
+        info!("Hired {} at ${:.2}/h", profile.name, profile.hourly_wage);
+
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Capsule3d::new(0.3, 1.0)),
+                material: materials.add(Color::rgb(0.95, 0.55, 0.1)),
+                transform: Transform::from_translation(spawn_point),
+                ..default()
+            },
+            Name::new(profile.name.clone()),
+            RigidBody::KinematicPositionBased,
+            Collider::capsule_y(0.5, 0.3),
+            KinematicCharacterController::default(),
+            NavAgent::default(),
+            Worker::new(profile),
+        ));
+    }
+}
+
+fn fire_workers(
+    mut commands: Commands,
+    mut firings: EventReader<FireWorker>,
+    mut queue: ResMut<JobQueue>,
+    worker_query: Query<&Worker>,
+    mut truck_query: Query<&mut OutboundTruck>,
+) {
+    for firing in firings.read() {
+        let Ok(worker) = worker_query.get(firing.worker) else {
+            continue;
+        };
+
+        // Whatever they were holding gets put down where they stand.
+        if let Some(job) = worker.job {
+            if matches!(
+                worker.step,
+                WorkerStep::Delivering { .. } | WorkerStep::Dropping { .. }
+            ) {
+                commands
+                    .entity(job.target)
+                    .remove_parent_in_place()
+                    .insert(RigidBody::Dynamic);
+            }
+            queue.release(job.target);
+            for mut truck in truck_query.iter_mut() {
+                truck.release(job.target);
+            }
+        }
+
+        info!("Let {} go", worker.profile.name);
+        commands.entity(firing.worker).despawn_recursive();
+    }
+}
+
+// Where the job's target should end up, worked out once the worker has it in hand.
+#[allow(clippy::too_many_arguments)]
+fn delivery_point(
+    job: Job,
+    worker: &Worker,
+    picked_up_at: &GlobalTransform,
+    package_query: &Query<&Package>,
+    chute_query: &Query<(&ChuteInput, &GlobalTransform)>,
+    truck_query: &mut Query<(&mut OutboundTruck, &GlobalTransform)>,
+    zone_query: &Query<(&TriggerZone, &GlobalTransform)>,
+    transforms: &Query<&GlobalTransform, Without<Worker>>,
+    container_query: &Query<&ShippingContainer>,
+) -> Option<Transform> {
+    let from = picked_up_at.translation();
+    let closest = |a: &Vec3, b: &Vec3| a.distance(from).total_cmp(&b.distance(from));
+
+    match job.kind {
+        // Off the truck and onto the staging area, or just out of the way if there isn't one.
+        JobKind::UnloadTruck => {
+            let staging = zone_query
+                .iter()
+                .filter(|(zone, _)| zone.name.to_lowercase().contains("staging"))
+                .map(|(_, transform)| transform.translation())
+                .min_by(closest);
+            let mut rng = thread_rng();
+            let jitter = Vec3::new(rng.gen_range(-1.0..1.0), 0.0, rng.gen_range(-1.0..1.0));
+
+            Some(Transform::from_translation(
+                staging.unwrap_or(from) + jitter,
+            ))
+        }
+        JobKind::ScanAndSort => {
+            let package = package_query.get(job.target).ok()?;
+            let mut chutes: Vec<(&str, Vec3)> = chute_query
+                .iter()
+                .map(|(chute, transform)| (chute.name.as_str(), transform.translation()))
+                .collect();
+            chutes.sort_by(|a, b| a.0.cmp(b.0));
+
+            let names: Vec<&str> = chutes.iter().map(|(name, _)| *name).collect();
+            let mut chute = destination_chute(package, &names)?;
+
+            // A missort sends the package down the wrong chute.
+            if worker.profile.fumbles(job.kind) {
+                if let Some(wrong) = names
+                    .iter()
+                    .filter(|name| **name != chute)
+                    .choose(&mut thread_rng())
+                {
+                    chute = wrong;
+                }
+            }
+
+            chutes
+                .iter()
+                .find(|(name, _)| *name == chute)
+                .map(|(_, position)| Transform::from_translation(*position))
+        }
+        // Into the closest truck with room, at a spot that's held for this worker until the
+        // load shows up. Containers need a patch of floor of their own.
+        JobKind::LoadTruck | JobKind::MoveContainer => {
+            let package = package_query.get(job.target).ok();
+            let container = container_query.get(job.target).ok();
+            let mut trucks: Vec<_> = truck_query
+                .iter_mut()
+                .filter(|(truck, _)| {
+                    package.is_none_or(|package| truck.has_room_for(package, package_query))
+                })
+                .collect();
+            trucks.sort_by(|(_, a), (_, b)| closest(&a.translation(), &b.translation()));
+
+            for (mut truck, truck_transform) in trucks {
+                let occupied = truck.occupied_space(truck_transform, transforms, container_query);
+                let spot = match container {
+                    Some(container) => {
+                        free_container_spot(container.kind.base_half_extents(), &occupied)
+                    }
+                    None => free_cargo_slot(&occupied).map(|(slot, spot)| (vec![slot], spot)),
+                };
+                let Some((slots, spot)) = spot else {
+                    continue;
+                };
+
+                truck.reserve(job.target, slots);
+                let (_, rotation, _) = truck_transform.to_scale_rotation_translation();
+                return Some(
+                    Transform::from_translation(truck_transform.transform_point(spot))
+                        .with_rotation(rotation),
+                );
+            }
+
+            None
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_jobs(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<ShiftClock>,
+    mut queue: ResMut<JobQueue>,
+    mut log: ResMut<TrackingLog>,
+    mut scans: EventWriter<PackageScanned>,
+    mut worker_query: Query<(Entity, &mut Worker, &GlobalTransform)>,
+    target_query: Query<&GlobalTransform, Without<Worker>>,
+    package_query: Query<&Package>,
+    chute_query: Query<(&ChuteInput, &GlobalTransform)>,
+    mut truck_query: Query<(&mut OutboundTruck, &GlobalTransform)>,
+    zone_query: Query<(&TriggerZone, &GlobalTransform)>,
+    container_query: Query<&ShippingContainer>,
+) {
+    let dt = time.delta_seconds();
+
+    for (worker_entity, mut worker, worker_transform) in worker_query.iter_mut() {
+        let Some(job) = worker.job else {
+            continue;
+        };
+        let position = worker_transform.translation();
+
+        // The target is gone: shipped, despawned, or taken apart.
+        let Ok(target_transform) = target_query.get(job.target) else {
+            queue.release(job.target);
+            worker.finish();
+            continue;
+        };
+
+        match worker.step {
+            WorkerStep::Idle => {}
+            WorkerStep::Fetching => {
+                let target = target_transform.translation();
+                worker.walk_to = Some(target);
+
+                let reach = match job.kind {
+                    JobKind::MoveContainer => CONTAINER_REACH,
+                    _ => REACH,
+                };
+                if target.xz().distance(position.xz()) < reach {
+                    worker.walk_to = None;
+                    worker.step = WorkerStep::PickingUp {
+                        remaining: worker.profile.handling_time(job.kind),
+                    };
+                }
+            }
+            WorkerStep::PickingUp { remaining } => {
+                if remaining > dt {
+                    worker.step = WorkerStep::PickingUp {
+                        remaining: remaining - dt,
+                    };
+                    continue;
+                }
+
+                let Some(drop_at) = delivery_point(
+                    job,
+                    &worker,
+                    target_transform,
+                    &package_query,
+                    &chute_query,
+                    &mut truck_query,
+                    &zone_query,
+                    &target_query,
+                    &container_query,
+                ) else {
+                    // Nowhere to take it yet. Leave it for later.
+                    queue.release(job.target);
+                    worker.finish();
+                    continue;
+                };
+
+                // Without its own rigid body the target rides along as part of the worker's.
+                commands
+                    .entity(job.target)
+                    .remove::<RigidBody>()
+                    .set_parent(worker_entity)
+                    .insert(Transform::from_translation(CARRY_OFFSET));
+
+                if job.kind == JobKind::ScanAndSort {
+                    scans.send(PackageScanned {
+                        package: job.target,
+                    });
+                }
+
+                worker.step = WorkerStep::Delivering { drop_at };
+            }
+            WorkerStep::Delivering { drop_at } => {
+                worker.walk_to = Some(drop_at.translation);
+
+                if drop_at.translation.xz().distance(position.xz()) < REACH {
+                    worker.walk_to = None;
+                    worker.step = WorkerStep::Dropping {
+                        drop_at,
+                        remaining: worker.profile.handling_time(job.kind),
+                    };
+                }
+            }
+            WorkerStep::Dropping { drop_at, remaining } => {
+                if remaining > dt {
+                    worker.step = WorkerStep::Dropping {
+                        drop_at,
+                        remaining: remaining - dt,
+                    };
+                    continue;
+                }
+
+                let mut target = commands.entity(job.target);
+                target
+                    .remove_parent()
+                    .insert((drop_at, RigidBody::Dynamic, Velocity::zero()));
+
+                if job.kind == JobKind::UnloadTruck {
+                    target.remove::<InboundCargo>();
+                }
+
+                // Packages thrown around on and off the trucks get damaged.
+                let handles_packages =
+                    matches!(job.kind, JobKind::UnloadTruck | JobKind::LoadTruck);
+                if handles_packages && worker.profile.fumbles(job.kind) {
+                    if let Ok(package) = package_query.get(job.target) {
+                        target.insert(Damaged);
+                        log.record(
+                            &package.tracking_id,
+                            TrackingEventKind::Damaged,
+                            clock.now(),
+                        );
+                    }
+                }
+
+                queue.release(job.target);
+                worker.finish();
+            }
+        }
+    }
+}
+
+// Walks toward `walk_to` along the path the navigation grid found.
+fn walk_workers(
+    time: Res<Time>,
+    mut worker_query: Query<(
+        &Worker,
+        &mut NavAgent,
+        &mut Transform,
+        &mut KinematicCharacterController,
+    )>,
+) {
+    let dt = time.delta_seconds();
+
+    for (worker, mut agent, mut transform, mut controller) in worker_query.iter_mut() {
+        let fall = -Vec3::Y * GRAVITY * dt;
+
+        agent.destination = worker.walk_to;
+        let Some(destination) = agent.next_waypoint() else {
+            controller.translation = Some(fall);
+            continue;
+        };
+
+        let to_destination = (destination - transform.translation) * Vec3::new(1.0, 0.0, 1.0);
+        let distance = to_destination.length();
+        if distance < f32::EPSILON {
+            controller.translation = Some(fall);
+            continue;
+        }
+
+        let direction = to_destination / distance;
+        transform.look_to(direction, Vec3::Y);
+        let step = direction * (worker.profile.walk_speed * dt).min(distance);
+        controller.translation = Some(step + fall);
+    }
+}
+
+fn pay_wages(
+    time: Res<Time>,
+    clock: Res<ShiftClock>,
+    worker_query: Query<&Worker>,
+    mut wallet: ResMut<Wallet>,
+) {
+    let hours = time.delta_seconds() * clock.game_hours_per_second;
+    let payroll: f32 = worker_query
+        .iter()
+        .map(|worker| worker.profile.hourly_wage)
+        .sum();
+
+    wallet.debit(payroll * hours);
+}
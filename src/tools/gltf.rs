@@ -14,6 +14,30 @@ impl Plugin for GltfToolsPlugin {
     }
 }
 
+// Every mesh primitive in the level along with its node's transform. Colliders and the navigation
+// grid are both built from this, so the two always agree on what the level looks like.
+pub fn level_meshes<'a>(
+    gltf: &Gltf,
+    gltf_mesh_assets: &'a Assets<GltfMesh>,
+    gltf_node_assets: &'a Assets<GltfNode>,
+    mesh_assets: &'a Assets<Mesh>,
+) -> Vec<(&'a Mesh, Transform)> {
+    let mut meshes = Vec::new();
+
+    for node in &gltf.nodes {
+        let node = gltf_node_assets.get(node).unwrap();
+        if let Some(gltf_mesh) = node.mesh.clone() {
+            let gltf_mesh = gltf_mesh_assets.get(&gltf_mesh).unwrap();
+            for mesh_primitive in &gltf_mesh.primitives {
+                let mesh = mesh_assets.get(&mesh_primitive.mesh).unwrap();
+                meshes.push((mesh, node.transform));
+            }
+        }
+    }
+
+    meshes
+}
+
 pub fn generate_gltf_colliders(
     mut commands: Commands,
     asset_pack: ResMut<MyAssetPack>,
@@ -23,19 +47,18 @@ pub fn generate_gltf_colliders(
     mesh_assets: Res<Assets<Mesh>>,
 ) {
     let gltf = gltf_assets.get(&asset_pack.main_scene);
+    let meshes = level_meshes(
+        gltf.unwrap(),
+        &gltf_mesh_assets,
+        &gltf_node_assets,
+        &mesh_assets,
+    );
 
-    for node in &gltf.unwrap().nodes {
-        let node = gltf_node_assets.get(node).unwrap();
-        if let Some(gltf_mesh) = node.mesh.clone() {
-            let gltf_mesh = gltf_mesh_assets.get(&gltf_mesh).unwrap();
-            for mesh_primitive in &gltf_mesh.primitives {
-                let mesh = mesh_assets.get(&mesh_primitive.mesh).unwrap();
-                commands.spawn((
-                    Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh).unwrap(),
-                    RigidBody::Fixed,
-                    TransformBundle::from_transform(node.transform),
-                ));
-            }
-        }
+    for (mesh, transform) in meshes {
+        commands.spawn((
+            Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh).unwrap(),
+            RigidBody::Fixed,
+            TransformBundle::from_transform(transform),
+        ));
     }
 }
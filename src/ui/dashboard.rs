@@ -0,0 +1,331 @@
+// Shift dashboard. M opens it, X exports every shift so far to CSV and JSON. The summary is plain
+// text; the charts underneath are rebuilt from bars a couple of times a second while it's open.
+
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionBindings, ActionState};
+use crate::analytics::export::ExportMetrics;
+use crate::analytics::metrics::{Equipment, ShiftMetrics, ShiftMetricsLog, Stage};
+use crate::game_state::GameState;
+use crate::logistics::shift::format_timestamp;
+
+pub struct DashboardPlugin;
+
+impl Plugin for DashboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DashboardState>()
+            .add_systems(Startup, spawn_dashboard)
+            .add_systems(
+                Update,
+                (toggle_dashboard, export_action, update_dashboard)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+const REFRESH_SECONDS: f32 = 0.5;
+const BAR_MAX_WIDTH: f32 = 300.0;
+const BAR_COLOR: Color = Color::rgb(0.3, 0.65, 0.95);
+const BOTTLENECK_COLOR: Color = Color::rgb(0.95, 0.45, 0.3);
+// How many finished shifts are listed under the current one.
+const PAST_SHIFTS_SHOWN: usize = 3;
+
+#[derive(Resource, Default, Debug)]
+struct DashboardState {
+    open: bool,
+    refresh_in: f32,
+}
+
+#[derive(Component)]
+struct Dashboard;
+
+#[derive(Component)]
+struct DashboardText;
+
+#[derive(Component)]
+struct DashboardCharts;
+
+fn spawn_dashboard(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.0),
+                    left: Val::Px(20.0),
+                    width: Val::Px(560.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.08, 0.85).into(),
+                ..default()
+            },
+            Dashboard,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                DashboardText,
+            ));
+            panel.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                DashboardCharts,
+            ));
+        });
+}
+
+fn toggle_dashboard(
+    actions: Res<ActionState>,
+    mut state: ResMut<DashboardState>,
+    mut dashboard_query: Query<&mut Style, With<Dashboard>>,
+) {
+    if !actions.just_pressed(Action::Dashboard) {
+        return;
+    }
+
+    state.open = !state.open;
+    state.refresh_in = 0.0;
+
+    for mut style in dashboard_query.iter_mut() {
+        style.display = if state.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn export_action(
+    actions: Res<ActionState>,
+    state: Res<DashboardState>,
+    mut exports: EventWriter<ExportMetrics>,
+) {
+    if state.open && actions.just_pressed(Action::ExportMetrics) {
+        exports.send(ExportMetrics);
+    }
+}
+
+fn percent(value: Option<f32>) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.0}%", value * 100.0))
+}
+
+fn summary(metrics: &ShiftMetrics) -> String {
+    format!(
+        "{:.1} pkg/h   shipped {}   received {}   sort accuracy {}\n\
+         dwell avg {} max {:.1}h   trucks {} ({} late, {} late packages)",
+        metrics.packages_per_hour(),
+        metrics.packages_shipped,
+        metrics.packages_received,
+        percent(metrics.sort_accuracy()),
+        metrics
+            .average_dwell_hours()
+            .map_or("-".to_string(), |hours| format!("{:.1}h", hours)),
+        metrics.max_dwell_hours,
+        metrics.trucks_departed,
+        metrics.late_departures,
+        metrics.late_packages,
+    )
+}
+
+fn spawn_label(parent: &mut ChildBuilder, text: String, width: f32, color: Color) {
+    parent.spawn(TextBundle {
+        text: Text::from_section(
+            text,
+            TextStyle {
+                font_size: 14.0,
+                color,
+                ..default()
+            },
+        ),
+        style: Style {
+            width: Val::Px(width),
+            ..default()
+        },
+        ..default()
+    });
+}
+
+fn spawn_chart_title(parent: &mut ChildBuilder, title: &str) {
+    parent.spawn(
+        TextBundle::from_section(
+            title,
+            TextStyle {
+                font_size: 15.0,
+                color: Color::rgb(0.8, 0.8, 0.85),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::top(Val::Px(6.0)),
+            ..default()
+        }),
+    );
+}
+
+// One row of a horizontal bar chart. `fraction` is the bar's share of the full width.
+fn spawn_bar(parent: &mut ChildBuilder, label: &str, fraction: f32, value: String, color: Color) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_label(row, label.to_string(), 100.0, Color::WHITE);
+            row.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(BAR_MAX_WIDTH * fraction.clamp(0.0, 1.0)),
+                    height: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: color.into(),
+                ..default()
+            });
+            spawn_label(row, value, 80.0, Color::WHITE);
+        });
+}
+
+fn spawn_charts(charts: &mut ChildBuilder, metrics: &ShiftMetrics) {
+    spawn_chart_title(charts, "Shipped per hour");
+    let busiest_hour = metrics
+        .shipped_by_hour
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let current_hour = metrics.hours_elapsed.floor() as usize;
+    for (hour, shipped) in metrics.shipped_by_hour.iter().enumerate() {
+        if hour > current_hour {
+            break;
+        }
+        let hour_of_day = (metrics.started_at + hour as f32).rem_euclid(24.0);
+        spawn_bar(
+            charts,
+            &format!("{:02}:00", hour_of_day.floor()),
+            *shipped as f32 / busiest_hour as f32,
+            shipped.to_string(),
+            BAR_COLOR,
+        );
+    }
+
+    spawn_chart_title(charts, "Equipment utilization");
+    for equipment in Equipment::ALL {
+        let Some(rate) = metrics.utilization_of(equipment) else {
+            continue;
+        };
+        spawn_bar(
+            charts,
+            equipment.label(),
+            rate,
+            percent(Some(rate)),
+            BAR_COLOR,
+        );
+    }
+
+    spawn_chart_title(charts, "Average packages waiting");
+    let bottleneck = metrics.bottleneck();
+    let longest_queue = Stage::ALL
+        .iter()
+        .map(|stage| metrics.average_backlog(*stage))
+        .fold(1.0, f32::max);
+    for stage in Stage::ALL {
+        let backlog = metrics.average_backlog(stage);
+        let color = if bottleneck == Some(stage) {
+            BOTTLENECK_COLOR
+        } else {
+            BAR_COLOR
+        };
+        spawn_bar(
+            charts,
+            stage.label(),
+            backlog / longest_queue,
+            format!("{:.1}", backlog),
+            color,
+        );
+    }
+}
+
+fn update_dashboard(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<DashboardState>,
+    log: Res<ShiftMetricsLog>,
+    bindings: Res<ActionBindings>,
+    mut text_query: Query<&mut Text, With<DashboardText>>,
+    charts_query: Query<Entity, With<DashboardCharts>>,
+) {
+    if !state.open {
+        return;
+    }
+    state.refresh_in -= time.delta_seconds();
+    if state.refresh_in > 0.0 {
+        return;
+    }
+    state.refresh_in = REFRESH_SECONDS;
+
+    let mut contents = format!(
+        "Shift dashboard ({} to export)\n",
+        bindings.label(Action::ExportMetrics)
+    );
+    match log.current() {
+        Some(current) => {
+            contents.push_str(&format!(
+                "Shift {} since {}\n{}\n",
+                current.shift + 1,
+                format_timestamp(current.started_at),
+                summary(current)
+            ));
+            if let Some(bottleneck) = current.bottleneck() {
+                contents.push_str(&format!("Bottleneck: {}\n", bottleneck.label()));
+            }
+        }
+        None => contents.push_str("No shift data yet.\n"),
+    }
+
+    let finished = log.shifts.len().saturating_sub(1);
+    for metrics in log.shifts[..finished].iter().rev().take(PAST_SHIFTS_SHOWN) {
+        contents.push_str(&format!(
+            "\nShift {}: {:.1} pkg/h, sort accuracy {}, {} late departures",
+            metrics.shift + 1,
+            metrics.packages_per_hour(),
+            percent(metrics.sort_accuracy()),
+            metrics.late_departures
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = contents.clone();
+    }
+
+    for charts in charts_query.iter() {
+        commands.entity(charts).despawn_descendants();
+        if let Some(current) = log.current() {
+            commands
+                .entity(charts)
+                .with_children(|charts| spawn_charts(charts, current));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// The hotbar along the bottom of the screen: a numbered slot for each tool, with the one in hand
+// picked out, and how many of each supply are left.
+
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionBindings};
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::player::inventory::{Inventory, Supply};
+use crate::player::viewmodel::HeldTool;
+use crate::ui::theme::{TextTone, ThemedPanel, ThemedText, UiTheme};
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AssetLoaderState::Done), spawn_hotbar)
+            .add_systems(Update, update_hotbar.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// The text in the hotbar slot at this index.
+#[derive(Component, Clone, Copy, Debug)]
+struct HotbarSlot(usize);
+
+#[derive(Component)]
+struct SupplyCounts;
+
+fn spawn_hotbar(mut commands: Commands, theme: Res<UiTheme>, inventory: Res<Inventory>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(16.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|hotbar| {
+            hotbar.spawn((theme.text(TextTone::Muted, 0.9), SupplyCounts));
+            hotbar
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|slots| {
+                    for slot in 0..inventory.hotbar.len() {
+                        slots
+                            .spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                                        ..default()
+                                    },
+                                    background_color: theme.panel_color.into(),
+                                    ..default()
+                                },
+                                ThemedPanel,
+                            ))
+                            .with_children(|panel| {
+                                panel.spawn((theme.text(TextTone::Normal, 1.0), HotbarSlot(slot)));
+                            });
+                    }
+                });
+        });
+}
+
+fn update_hotbar(
+    theme: Res<UiTheme>,
+    held: Res<HeldTool>,
+    inventory: Res<Inventory>,
+    bindings: Res<ActionBindings>,
+    added_query: Query<(), Added<HotbarSlot>>,
+    mut slot_query: Query<(&HotbarSlot, &mut Text, &mut ThemedText), Without<SupplyCounts>>,
+    mut supplies_query: Query<&mut Text, With<SupplyCounts>>,
+) {
+    let changed =
+        held.is_changed() || inventory.is_changed() || theme.is_changed() || bindings.is_changed();
+    if !changed && added_query.is_empty() {
+        return;
+    }
+
+    for (slot, mut text, mut themed) in slot_query.iter_mut() {
+        let Some(tool) = inventory.hotbar.get(slot.0) else {
+            continue;
+        };
+        themed.tone = if *tool == held.0 {
+            TextTone::Accent
+        } else {
+            TextTone::Normal
+        };
+        let key = Action::SLOTS
+            .get(slot.0)
+            .map_or(String::new(), |action| bindings.label(*action));
+        text.sections[0].value = format!("{}  {}", key, tool.label());
+        text.sections[0].style = theme.text_style(themed.tone, themed.size);
+    }
+
+    let supplies = Supply::ALL
+        .iter()
+        .map(|supply| format!("{} {}", supply.label(), inventory.count(*supply)))
+        .collect::<Vec<_>>()
+        .join("   ");
+    for mut text in supplies_query.iter_mut() {
+        text.sections[0].value = supplies.clone();
+    }
+}
@@ -0,0 +1,676 @@
+// The in-game HUD: crosshair, prompts for whatever the player is looking at or standing next to,
+// the last scan, the shift clock and balance, and a short list of what needs doing. Styled by
+// `UiTheme`.
+
+use bevy::prelude::*;
+use bevy_fps_controller::controller::LogicalPlayer;
+use bevy_rapier3d::prelude::*;
+
+use crate::actions::{Action, ActionBindings};
+use crate::building::build_mode::BuildMode;
+use crate::building::light_fixtures::FIXTURE_COST;
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{is_inside_any, ChuteInput, LightZone};
+use crate::levels::package_data::{Damaged, Package};
+use crate::lighting::day_night::Daylight;
+use crate::lighting::zones::{CurrentLightZone, LightSwitch};
+use crate::logistics::address::{AddressException, ExceptionDesk, ExceptionStatus};
+use crate::logistics::containers::{ShippingContainer, CONSOLIDATE_RANGE};
+use crate::logistics::customs::{CustomsDeclaration, CustomsStatus, InspectionStation};
+use crate::logistics::economy::Wallet;
+use crate::logistics::outbound::{OutboundTruck, LOAD_RANGE};
+use crate::logistics::shift::{format_timestamp, ShiftClock};
+use crate::logistics::sorting::{destination_chute, SortedTo};
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::items::carry::Carried;
+use crate::player::items::label_printer::needs_hazmat_label;
+use crate::player::items::scanner::PackageScanned;
+use crate::player::items::tablet::SUPPLY_ORDER_COST;
+use crate::player::stamina::{Stamina, MAX_STAMINA};
+use crate::player::viewmodel::{HeldTool, Tool};
+use crate::staff::jobs::is_blocked;
+use crate::ui::theme::{TextTone, ThemedPanel, UiTheme};
+use crate::vehicles::driving::{Occupied, ENTER_RANGE};
+use crate::vehicles::material_handling::{Vehicle, VehicleKind};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScanReadout>()
+            .add_systems(OnEnter(AssetLoaderState::Done), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_status_line,
+                    update_crosshair,
+                    update_prompts,
+                    update_scan_panel,
+                    update_objectives,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// How long the scan panel stays up after a scan.
+const SCAN_PANEL_SECONDS: f32 = 8.0;
+const CROSSHAIR_SIZE: f32 = 14.0;
+const CROSSHAIR_THICKNESS: f32 = 2.0;
+
+#[derive(Resource, Default, Debug)]
+struct ScanReadout {
+    package: Option<Entity>,
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct StatusLine;
+
+#[derive(Component)]
+struct CrosshairBar;
+
+#[derive(Component)]
+struct Prompts;
+
+#[derive(Component)]
+struct ScanPanel;
+
+#[derive(Component)]
+struct ScanPanelText;
+
+#[derive(Component)]
+struct ScanPanelWarnings;
+
+#[derive(Component)]
+struct Objectives;
+
+fn full_screen() -> Style {
+    Style {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        ..default()
+    }
+}
+
+fn panel(theme: &UiTheme, style: Style) -> (NodeBundle, ThemedPanel) {
+    (
+        NodeBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..style
+            },
+            background_color: theme.panel_color.into(),
+            ..default()
+        },
+        ThemedPanel,
+    )
+}
+
+fn spawn_hud(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: full_screen(),
+            ..default()
+        })
+        .with_children(|hud| {
+            // Shift clock and balance along the top.
+            hud.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::top(Val::Px(12.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|top| {
+                top.spawn(panel(&theme, Style::default()))
+                    .with_children(|status| {
+                        status.spawn((theme.text(TextTone::Normal, 1.1), StatusLine));
+                    });
+            });
+
+            // Crosshair, with the prompts underneath it.
+            hud.spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..full_screen()
+                },
+                ..default()
+            })
+            .with_children(|center| {
+                center
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(CROSSHAIR_SIZE),
+                            height: Val::Px(CROSSHAIR_SIZE),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|crosshair| {
+                        for (width, height) in [
+                            (CROSSHAIR_SIZE, CROSSHAIR_THICKNESS),
+                            (CROSSHAIR_THICKNESS, CROSSHAIR_SIZE),
+                        ] {
+                            crosshair.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        position_type: PositionType::Absolute,
+                                        width: Val::Px(width),
+                                        height: Val::Px(height),
+                                        ..default()
+                                    },
+                                    background_color: theme.text_color.into(),
+                                    ..default()
+                                },
+                                CrosshairBar,
+                            ));
+                        }
+                    });
+
+                let (prompts, themed) = theme.text(TextTone::Accent, 1.0);
+                center.spawn((
+                    prompts.with_style(Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Percent(55.0),
+                        ..default()
+                    }),
+                    themed,
+                    Prompts,
+                ));
+            });
+
+            // Last scan, on the left.
+            hud.spawn((
+                panel(
+                    &theme,
+                    Style {
+                        display: Display::None,
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(20.0),
+                        top: Val::Percent(35.0),
+                        width: Val::Px(360.0),
+                        ..default()
+                    },
+                ),
+                ScanPanel,
+            ))
+            .with_children(|scan| {
+                scan.spawn((theme.text(TextTone::Normal, 1.0), ScanPanelText));
+                scan.spawn((theme.text(TextTone::Warning, 1.0), ScanPanelWarnings));
+            });
+
+            // What needs doing, on the right.
+            hud.spawn(panel(
+                &theme,
+                Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Percent(35.0),
+                    width: Val::Px(320.0),
+                    ..default()
+                },
+            ))
+            .with_children(|objectives| {
+                objectives.spawn((theme.text(TextTone::Normal, 0.95), Objectives));
+            });
+        });
+}
+
+fn update_status_line(
+    clock: Res<ShiftClock>,
+    wallet: Res<Wallet>,
+    build_mode: Res<BuildMode>,
+    stamina_query: Query<&Stamina>,
+    mut text_query: Query<&mut Text, With<StatusLine>>,
+) {
+    let mut status = format!(
+        "{}      ${:.2}",
+        format_timestamp(clock.now()),
+        wallet.balance
+    );
+    if build_mode.active {
+        status.push_str("      BUILD MODE");
+    }
+    // Only while it's not full, so it's out of the way most of the time.
+    for stamina in stamina_query.iter() {
+        if stamina.exhausted {
+            status.push_str("      Out of breath");
+        } else if stamina.current < MAX_STAMINA {
+            status.push_str(&format!(
+                "      Stamina {:.0}%",
+                stamina.current / MAX_STAMINA * 100.0
+            ));
+        }
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = status.clone();
+    }
+}
+
+// Lights up while the player is looking at something they can use.
+fn update_crosshair(
+    theme: Res<UiTheme>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    mut bar_query: Query<&mut BackgroundColor, With<CrosshairBar>>,
+) {
+    let looking_at_item = interaction_query
+        .iter()
+        .any(|interaction| interaction.is_looking_at_item);
+    let color = if looking_at_item {
+        theme.accent_color
+    } else {
+        theme.text_color
+    };
+
+    for mut background in bar_query.iter_mut() {
+        *background = color.into();
+    }
+}
+
+fn vehicle_name(kind: VehicleKind) -> &'static str {
+    match kind {
+        VehicleKind::PalletJack => "pallet jack",
+        VehicleKind::Forklift => "forklift",
+        VehicleKind::Tugger => "tugger",
+    }
+}
+
+type PackageTarget<'a> = (
+    &'a Package,
+    &'a GlobalTransform,
+    Option<&'a AddressException>,
+    Option<&'a CustomsStatus>,
+    Option<&'a CustomsDeclaration>,
+    Option<&'a Damaged>,
+    Option<&'a Parent>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn update_prompts(
+    rapier_context: Res<RapierContext>,
+    bindings: Res<ActionBindings>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    player_query: Query<&GlobalTransform, With<LogicalPlayer>>,
+    package_query: Query<PackageTarget>,
+    truck_query: Query<&GlobalTransform, With<OutboundTruck>>,
+    container_query: Query<&GlobalTransform, With<ShippingContainer>>,
+    vehicle_query: Query<(&Vehicle, &GlobalTransform, Option<&Occupied>)>,
+    desk_query: Query<Entity, With<ExceptionDesk>>,
+    station_query: Query<Entity, With<InspectionStation>>,
+    carried_query: Query<(), With<Carried>>,
+    build_mode: Res<BuildMode>,
+    current_zone: Res<CurrentLightZone>,
+    zone_query: Query<(&LightZone, &LightSwitch)>,
+    held: Res<HeldTool>,
+    mut text_query: Query<&mut Text, With<Prompts>>,
+) {
+    let mut prompts: Vec<String> = Vec::new();
+    let interact = bindings.label(Action::Interact);
+    let use_tool = bindings.label(Action::UseTool);
+    let movement = [
+        Action::MoveForward,
+        Action::MoveLeft,
+        Action::MoveBack,
+        Action::MoveRight,
+    ]
+    .map(|action| bindings.label(action))
+    .join("/");
+
+    let driving = vehicle_query
+        .iter()
+        .find(|(_, _, occupied)| occupied.is_some());
+
+    if let Some((vehicle, _, _)) = driving {
+        prompts.push(format!("{}: get out   {}: drive", interact, movement));
+        let lift_up = bindings.label(Action::LiftUp);
+        let lift_down = bindings.label(Action::LiftDown);
+        prompts.push(match vehicle.kind {
+            VehicleKind::Tugger => format!("{}: hitch cart   {}: drop carts", lift_up, lift_down),
+            _ => format!("{}/{}: raise/lower forks", lift_up, lift_down),
+        });
+    } else if build_mode.active {
+        prompts.push(format!(
+            "{}: hang light (${:.0})   {}: take down light",
+            bindings.label(Action::Throw),
+            FIXTURE_COST,
+            interact
+        ));
+    } else if !carried_query.is_empty() {
+        prompts.push(format!(
+            "{}: put down   {}: throw",
+            interact,
+            bindings.label(Action::Throw)
+        ));
+    } else {
+        let target = interaction_query
+            .iter()
+            .find_map(|interaction| interaction.interactable_entity)
+            .and_then(|target| package_query.get(target).ok().map(|state| (target, state)));
+
+        if let Some((
+            target,
+            (package, transform, exception, customs, declaration, damaged, parent),
+        )) = target
+        {
+            let position = transform.translation();
+            prompts.push(format!(
+                "{}: scan   {}: track",
+                bindings.label(Action::Scan),
+                bindings.label(Action::TrackPackage)
+            ));
+            if parent.is_none() {
+                prompts.push(format!("{}: pick up", interact));
+            }
+
+            let in_container = parent.is_some_and(|parent| container_query.contains(parent.get()));
+            let near_container = container_query
+                .iter()
+                .any(|container| container.translation().distance(position) < CONSOLIDATE_RANGE);
+            let near_truck = truck_query
+                .iter()
+                .any(|truck| truck.translation().distance(position) < LOAD_RANGE);
+            let consolidate = bindings.label(Action::Consolidate);
+            if in_container {
+                prompts.push(format!("{}: take out of container", consolidate));
+            } else if near_container {
+                prompts.push(format!("{}: put in container", consolidate));
+            }
+            if parent.is_none() && near_truck {
+                prompts.push(format!(
+                    "{}: load onto truck",
+                    bindings.label(Action::LoadTruck)
+                ));
+            }
+
+            let exception_open = exception.is_some_and(|exception| {
+                matches!(
+                    exception.status,
+                    ExceptionStatus::Open | ExceptionStatus::Held
+                )
+            });
+            match held.0 {
+                Tool::LabelPrinter if exception_open => {
+                    prompts.push(format!("{}: relabel (bad address)", use_tool));
+                }
+                Tool::LabelPrinter if needs_hazmat_label(package, declaration) => {
+                    prompts.push(format!("{}: relabel (needs hazmat label)", use_tool));
+                }
+                Tool::LabelPrinter => {
+                    prompts.push(format!("{}: relabel", use_tool));
+                }
+                Tool::TapeGun if damaged.is_some() => {
+                    prompts.push(format!("{}: tape up", use_tool));
+                }
+                Tool::BoxCutter if in_container => {
+                    prompts.push(format!("{}: cut container open", use_tool));
+                }
+                _ => {}
+            }
+            let clear = bindings.label(Action::Clear);
+            let return_to_sender = bindings.label(Action::ReturnToSender);
+            let hold = bindings.label(Action::Hold);
+            if exception_open && is_inside_any(&rapier_context, desk_query.iter(), target) {
+                prompts.push(format!(
+                    "{}: correct address   {}: return   {}: hold",
+                    clear, return_to_sender, hold
+                ));
+            }
+            let customs_open = customs.is_some_and(|status| {
+                matches!(status, CustomsStatus::Pending | CustomsStatus::Held)
+            });
+            if customs_open && is_inside_any(&rapier_context, station_query.iter(), target) {
+                prompts.push(format!(
+                    "{}: clear customs   {}: refuse   {}: hold",
+                    clear, return_to_sender, hold
+                ));
+            }
+        }
+
+        let on_container = interaction_query
+            .iter()
+            .filter_map(|interaction| interaction.interactable_entity)
+            .any(|target| container_query.contains(target));
+        if held.0 == Tool::BoxCutter && on_container {
+            prompts.push(format!("{}: cut container open", use_tool));
+        }
+        if held.0 == Tool::Tablet {
+            prompts.push(format!(
+                "{}: order supplies (${:.0})",
+                use_tool, SUPPLY_ORDER_COST
+            ));
+        }
+
+        // Interact picks up whatever is under the crosshair before it gets into a vehicle.
+        let looking_at_item = interaction_query
+            .iter()
+            .any(|interaction| interaction.is_looking_at_item);
+        let player = player_query.iter().next().map(GlobalTransform::translation);
+        let closest_vehicle = vehicle_query
+            .iter()
+            .filter_map(|(vehicle, transform, _)| {
+                let distance = transform.translation().distance(player?);
+                (distance < ENTER_RANGE).then_some((vehicle.kind, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|_| !looking_at_item);
+        if let Some((kind, _)) = closest_vehicle {
+            prompts.push(format!("{}: drive {}", interact, vehicle_name(kind)));
+        }
+    }
+
+    let zone = current_zone.0.and_then(|zone| zone_query.get(zone).ok());
+    if let (None, Some((zone, switch))) = (driving, zone) {
+        let state = if switch.on { "off" } else { "on" };
+        prompts.push(format!(
+            "{}: lights {} ({})",
+            bindings.label(Action::Lights),
+            state,
+            zone.name
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = prompts.join("\n");
+    }
+}
+
+type ScannedPackage<'a> = (
+    &'a Package,
+    Option<&'a AddressException>,
+    Option<&'a CustomsStatus>,
+    Option<&'a SortedTo>,
+    Option<&'a Damaged>,
+);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_scan_panel(
+    time: Res<Time>,
+    clock: Res<ShiftClock>,
+    mut readout: ResMut<ScanReadout>,
+    mut scans: EventReader<PackageScanned>,
+    package_query: Query<ScannedPackage>,
+    chute_query: Query<&ChuteInput>,
+    mut panel_query: Query<&mut Style, With<ScanPanel>>,
+    mut text_query: Query<&mut Text, (With<ScanPanelText>, Without<ScanPanelWarnings>)>,
+    mut warning_query: Query<&mut Text, (With<ScanPanelWarnings>, Without<ScanPanelText>)>,
+) {
+    if let Some(scan) = scans.read().last() {
+        readout.package = Some(scan.package);
+        readout.remaining = SCAN_PANEL_SECONDS;
+    }
+    readout.remaining -= time.delta_seconds();
+
+    let scanned = readout
+        .package
+        .filter(|_| readout.remaining > 0.0)
+        .and_then(|package| package_query.get(package).ok());
+
+    for mut style in panel_query.iter_mut() {
+        style.display = if scanned.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    let Some((package, exception, customs, sorted_to, damaged)) = scanned else {
+        return;
+    };
+
+    let mut chutes: Vec<&str> = chute_query
+        .iter()
+        .map(|chute| chute.name.as_str())
+        .collect();
+    chutes.sort();
+    let zip_code = package
+        .zip_code
+        .map_or("no zip".to_string(), |zip_code| zip_code.to_string());
+
+    let mut details = format!(
+        "{}\n{}\n{}\n{}, {}\n{:.1} kg   {:?}\nSort by {}",
+        package.tracking_id,
+        package.recipient_name,
+        package.street_address,
+        package.country,
+        zip_code,
+        package.weight,
+        package.service_level,
+        format_timestamp(package.sort_deadline()),
+    );
+    if let Some(chute) = destination_chute(package, &chutes) {
+        details.push_str(&format!("\nRoute to {}", chute));
+    }
+    if let Some(sorted_to) = sorted_to {
+        details.push_str(&format!("\nSorted to {}", sorted_to.chute));
+    }
+
+    let mut warnings = Vec::new();
+    if let Some(exception) = exception {
+        if exception.status != ExceptionStatus::Corrected {
+            warnings.push(format!(
+                "Address exception ({:?}): {:?}",
+                exception.status, exception.issues
+            ));
+        }
+    }
+    if let Some(customs) = customs {
+        if *customs != CustomsStatus::Cleared {
+            warnings.push(format!("Customs: {:?}", customs));
+        }
+    }
+    if is_blocked(exception, customs) {
+        warnings.push("Not ready to sort".to_string());
+    }
+    if damaged.is_some() {
+        warnings.push("Damaged".to_string());
+    }
+    if package.hazmat {
+        warnings.push("Hazmat".to_string());
+    }
+    if clock.now() > package.sort_deadline() {
+        warnings.push("Past its sort deadline".to_string());
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = details.clone();
+    }
+    for mut text in warning_query.iter_mut() {
+        text.sections[0].value = warnings.join("\n");
+    }
+}
+
+type PackageProgress<'a> = (
+    Entity,
+    &'a Package,
+    Option<&'a SortedTo>,
+    Option<&'a AddressException>,
+    Option<&'a CustomsStatus>,
+);
+
+fn update_objectives(
+    clock: Res<ShiftClock>,
+    daylight: Res<Daylight>,
+    truck_query: Query<&OutboundTruck>,
+    package_query: Query<PackageProgress>,
+    zone_query: Query<(&LightZone, &LightSwitch)>,
+    mut text_query: Query<&mut Text, With<Objectives>>,
+) {
+    let now = clock.now();
+    let mut objectives = vec!["Objectives".to_string()];
+
+    let next_truck = truck_query
+        .iter()
+        .min_by(|a, b| a.departs_at.total_cmp(&b.departs_at));
+    if let Some(truck) = next_truck {
+        let minutes = ((truck.departs_at - now).max(0.0) * 60.0).round() as u32;
+        objectives.push(format!(
+            "Load {} at {}: leaves in {}:{:02} ({} aboard)",
+            truck.name,
+            truck.dock,
+            minutes / 60,
+            minutes % 60,
+            truck.manifest.len()
+        ));
+    }
+
+    let mut to_sort = 0;
+    let mut exceptions = 0;
+    let mut late = 0;
+    for (entity, package, sorted_to, exception, customs) in package_query.iter() {
+        if truck_query
+            .iter()
+            .any(|truck| truck.manifest.contains(&entity))
+        {
+            continue;
+        }
+        if is_blocked(exception, customs) {
+            exceptions += 1;
+        } else if sorted_to.is_none() {
+            to_sort += 1;
+        }
+        if now > package.sort_deadline() {
+            late += 1;
+        }
+    }
+
+    if to_sort > 0 {
+        objectives.push(format!("Sort {} packages", to_sort));
+    }
+    if exceptions > 0 {
+        objectives.push(format!("Resolve {} held packages", exceptions));
+    }
+    if late > 0 {
+        objectives.push(format!("{} packages are past their sort deadline", late));
+    }
+
+    if daylight.is_dark() {
+        let dark_zones: Vec<&str> = zone_query
+            .iter()
+            .filter(|(_, switch)| !switch.on)
+            .map(|(zone, _)| zone.name.as_str())
+            .collect();
+        if !dark_zones.is_empty() {
+            objectives.push(format!(
+                "It's dark out: turn the lights on in {}",
+                dark_zones.join(", ")
+            ));
+        }
+    }
+    if objectives.len() == 1 {
+        objectives.push("All caught up".to_string());
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = objectives.join("\n");
+    }
+}
@@ -0,0 +1,383 @@
+// Label editor for the label printer. Using the printer on a package opens it with the package's
+// label: Up/Down pick a field, Left/Right change it, digits type the ZIP code and Enter prints
+// the new label. Using the printer again puts it away without printing. Letters aren't typed
+// here, since so much else in the warehouse listens for them; names and streets come from the
+// customer file instead.
+
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use bevy_fps_controller::controller::FpsController;
+
+use crate::actions::{Action, ActionBindings};
+use crate::game_state::GameState;
+use crate::levels::package_data::{Package, ServiceLevel, COUNTRIES, HOME_COUNTRY};
+use crate::logistics::address::AddressOnFile;
+use crate::logistics::customs::CustomsStatus;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::inventory::{Inventory, Supply};
+use crate::player::items::label_printer::{customs_open, PrintLabel, RoutingLabel};
+use crate::player::viewmodel::{use_tools, HeldTool, Tool, ToolUsed};
+use crate::ui::theme::{TextTone, ThemedPanel, UiTheme};
+
+pub struct LabelEditorPlugin;
+
+impl Plugin for LabelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LabelEditorState>()
+            .add_systems(Startup, spawn_label_editor)
+            .add_systems(
+                Update,
+                (open_label_editor, edit_label, update_label_editor)
+                    .chain()
+                    .after(use_tools)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Long enough for any country's postal code.
+const ZIP_CODE_LENGTH: usize = 9;
+
+const DOMESTIC_SERVICES: [ServiceLevel; 3] = [
+    ServiceLevel::Ground,
+    ServiceLevel::TwoDay,
+    ServiceLevel::Overnight,
+];
+const INTERNATIONAL_SERVICES: [ServiceLevel; 1] = [ServiceLevel::InternationalEconomy];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LabelField {
+    Address,
+    Country,
+    ZipCode,
+    Service,
+    Hazmat,
+}
+
+impl LabelField {
+    const ALL: [LabelField; 5] = [
+        LabelField::Address,
+        LabelField::Country,
+        LabelField::ZipCode,
+        LabelField::Service,
+        LabelField::Hazmat,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            LabelField::Address => "Address",
+            LabelField::Country => "Country",
+            LabelField::ZipCode => "ZIP code",
+            LabelField::Service => "Service",
+            LabelField::Hazmat => "Hazmat",
+        }
+    }
+}
+
+// The label being edited, next to the one on the package.
+#[derive(Clone, Debug)]
+struct Draft {
+    package: Entity,
+    tracking_id: String,
+    printed: RoutingLabel,
+    label: RoutingLabel,
+    address_on_file: Option<AddressOnFile>,
+    from_file: bool,
+    zip_code: String,
+    // Waiting on customs, so the country stays as it is (see `print_labels`).
+    customs_open: bool,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct LabelEditorState {
+    draft: Option<Draft>,
+    field: usize,
+}
+
+#[derive(Component)]
+struct LabelEditor;
+
+#[derive(Component)]
+struct LabelEditorText;
+
+// For input that editing a label would otherwise also trigger.
+pub fn label_editor_closed(state: Res<LabelEditorState>) -> bool {
+    state.draft.is_none()
+}
+
+fn spawn_label_editor(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    width: Val::Px(460.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: theme.panel_color.into(),
+                ..default()
+            },
+            ThemedPanel,
+            LabelEditor,
+        ))
+        .with_children(|panel| {
+            panel.spawn((theme.text(TextTone::Normal, 1.1), LabelEditorText));
+        });
+}
+
+type Labeled<'a> = (
+    &'a Package,
+    Option<&'a AddressOnFile>,
+    Option<&'a CustomsStatus>,
+);
+
+// Using the printer opens the editor, or puts it away. So does the printer leaving the player's
+// hand.
+fn open_label_editor(
+    mut uses: EventReader<ToolUsed>,
+    held: Res<HeldTool>,
+    mut state: ResMut<LabelEditorState>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    package_query: Query<Labeled>,
+) {
+    let printer_used = uses
+        .read()
+        .filter(|used| used.tool == Tool::LabelPrinter)
+        .count()
+        > 0;
+    let put_away = state.draft.is_some() && held.0 != Tool::LabelPrinter;
+    if !printer_used && !put_away {
+        return;
+    }
+
+    if state.draft.is_some() {
+        state.draft = None;
+    } else {
+        let looked_at = interaction_query
+            .iter()
+            .filter_map(|interaction| interaction.interactable_entity)
+            .find_map(|entity| package_query.get(entity).ok().map(|state| (entity, state)));
+        let Some((entity, (package, address_on_file, customs))) = looked_at else {
+            return;
+        };
+        let label = RoutingLabel::from_package(package);
+        state.draft = Some(Draft {
+            package: entity,
+            tracking_id: package.tracking_id.clone(),
+            zip_code: label
+                .zip_code
+                .map(|zip| zip.to_string())
+                .unwrap_or_default(),
+            printed: label.clone(),
+            label,
+            address_on_file: address_on_file.cloned(),
+            from_file: false,
+            customs_open: customs_open(customs),
+        });
+        state.field = 0;
+    }
+}
+
+fn services_for(country: &str) -> &'static [ServiceLevel] {
+    if country == HOME_COUNTRY {
+        &DOMESTIC_SERVICES
+    } else {
+        &INTERNATIONAL_SERVICES
+    }
+}
+
+// `step` places along `options` from `current`, wrapping around at either end.
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .map_or(0, |index| index as isize);
+    options[(index + step).rem_euclid(options.len() as isize) as usize]
+}
+
+impl Draft {
+    fn change(&mut self, field: LabelField, step: isize) {
+        match field {
+            LabelField::Address => {
+                // Between the address on the package and the one in the customer file.
+                let Some(address_on_file) = &self.address_on_file else {
+                    return;
+                };
+                self.from_file = !self.from_file;
+                let source = if self.from_file {
+                    self.label.with_address(address_on_file)
+                } else {
+                    RoutingLabel {
+                        service_level: self.label.service_level,
+                        hazmat: self.label.hazmat,
+                        ..self.printed.clone()
+                    }
+                };
+                self.label = source;
+                self.zip_code = self
+                    .label
+                    .zip_code
+                    .map(|zip| zip.to_string())
+                    .unwrap_or_default();
+            }
+            LabelField::Country => {
+                let country = COUNTRIES
+                    .iter()
+                    .position(|country| *country == self.label.country)
+                    .map_or(0, |index| index as isize);
+                let country = (country + step).rem_euclid(COUNTRIES.len() as isize) as usize;
+                self.label.country = COUNTRIES[country].to_string();
+            }
+            LabelField::ZipCode => {}
+            LabelField::Service => {
+                let services = services_for(&self.label.country);
+                self.label.service_level = cycle(services, self.label.service_level, step);
+            }
+            LabelField::Hazmat => self.label.hazmat = !self.label.hazmat,
+        }
+
+        if self.customs_open {
+            self.label.country = self.printed.country.clone();
+        }
+        // Changing where it's going can rule out the service it had.
+        let services = services_for(&self.label.country);
+        if !services.contains(&self.label.service_level) {
+            self.label.service_level = services[0];
+        }
+    }
+}
+
+fn edit_label(
+    input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut state: ResMut<LabelEditorState>,
+    mut prints: EventWriter<PrintLabel>,
+) {
+    if state.draft.is_none() {
+        characters.clear();
+        return;
+    }
+    let state = state.as_mut();
+    let Some(draft) = state.draft.as_mut() else {
+        return;
+    };
+
+    if input.just_pressed(KeyCode::ArrowDown) {
+        state.field = (state.field + 1) % LabelField::ALL.len();
+    }
+    if input.just_pressed(KeyCode::ArrowUp) {
+        state.field = (state.field + LabelField::ALL.len() - 1) % LabelField::ALL.len();
+    }
+    let field = LabelField::ALL[state.field];
+    if input.just_pressed(KeyCode::ArrowRight) {
+        draft.change(field, 1);
+    }
+    if input.just_pressed(KeyCode::ArrowLeft) {
+        draft.change(field, -1);
+    }
+
+    if field == LabelField::ZipCode {
+        for event in characters.read() {
+            for c in event.char.chars().filter(char::is_ascii_digit) {
+                if draft.zip_code.len() < ZIP_CODE_LENGTH {
+                    draft.zip_code.push(c);
+                }
+            }
+        }
+        if input.just_pressed(KeyCode::Backspace) {
+            draft.zip_code.pop();
+        }
+        draft.label.zip_code = draft.zip_code.parse().ok();
+    } else {
+        characters.clear();
+    }
+
+    if input.just_pressed(KeyCode::Enter) {
+        prints.send(PrintLabel {
+            package: draft.package,
+            label: draft.label.clone(),
+        });
+        state.draft = None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_label_editor(
+    state: Res<LabelEditorState>,
+    inventory: Res<Inventory>,
+    bindings: Res<ActionBindings>,
+    mut was_open: Local<bool>,
+    mut panel_query: Query<&mut Style, With<LabelEditor>>,
+    mut text_query: Query<&mut Text, With<LabelEditorText>>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    let open = state.draft.is_some();
+    if open != *was_open {
+        *was_open = open;
+        for mut style in panel_query.iter_mut() {
+            style.display = if open { Display::Flex } else { Display::None };
+        }
+        // Left, right and the number keys shouldn't walk the player around.
+        for mut controller in controller_query.iter_mut() {
+            controller.enable_input = !open;
+        }
+    }
+
+    if !state.is_changed() && !inventory.is_changed() {
+        return;
+    }
+    let Some(draft) = &state.draft else {
+        return;
+    };
+
+    let label = &draft.label;
+    let address = if draft.from_file {
+        "customer file"
+    } else if draft.address_on_file.is_some() {
+        "as labeled"
+    } else {
+        "as labeled (none on file)"
+    };
+    let hazmat = if label.hazmat { "yes" } else { "no" };
+    let values = [
+        format!(
+            "{}\n      {}\n      {}",
+            address, label.recipient_name, label.street_address
+        ),
+        if draft.customs_open {
+            format!("{} (waiting on customs)", label.country)
+        } else {
+            label.country.clone()
+        },
+        draft.zip_code.clone(),
+        format!("{:?}", label.service_level),
+        hazmat.to_string(),
+    ];
+
+    let mut contents = format!("Print a label\n{}\n\n", draft.tracking_id);
+    for (index, (field, value)) in LabelField::ALL.iter().zip(values).enumerate() {
+        let cursor = if index == state.field { ">" } else { " " };
+        contents.push_str(&format!("{} {}: {}\n", cursor, field.label(), value));
+    }
+
+    let changes = draft.printed.changes(label);
+    if !changes.is_empty() {
+        let changed: Vec<&str> = changes.iter().map(|(field, _, _)| *field).collect();
+        contents.push_str(&format!("\nChanged: {}\n", changed.join(", ")));
+    }
+    contents.push_str(&format!(
+        "\n{} labels, {} hazmat stickers left\nUp/Down: field   Left/Right: change   \
+         0-9: ZIP code\nEnter: print   {}: cancel",
+        inventory.count(Supply::Labels),
+        inventory.count(Supply::HazmatStickers),
+        bindings.label(Action::UseTool),
+    ));
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = contents.clone();
+    }
+}
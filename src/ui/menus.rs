@@ -0,0 +1,591 @@
+// Main menu, loading screen, pause menu and settings. Each page is rebuilt from scratch whenever
+// it changes (or the settings it shows do); buttons carry the action they trigger. Settings apply
+// as soon as they're changed and are saved when leaving the settings page.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionBindings, ActionState, Rebinding};
+use crate::game_state::GameState;
+use crate::settings::audio::VolumeBus;
+use crate::settings::graphics::{
+    next_choice, BloomPreset, FrameLimit, GraphicsSettings, QualityPreset, MAX_FOV_DEGREES,
+    MIN_FOV_DEGREES, SHADOW_MAP_SIZES,
+};
+use crate::settings::{
+    Settings, MAX_MOUSE_SENSITIVITY, MAX_STICK_SENSITIVITY, MIN_MOUSE_SENSITIVITY,
+    MIN_STICK_SENSITIVITY,
+};
+use crate::ui::theme::{TextTone, ThemedPanel, ThemedText, UiTheme};
+
+pub struct MenusPlugin;
+
+impl Plugin for MenusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Menu>()
+            .add_systems(OnEnter(GameState::MainMenu), open_main_menu)
+            .add_systems(OnEnter(GameState::Loading), open_loading_screen)
+            .add_systems(OnExit(GameState::Loading), close_menu_after_loading)
+            .add_systems(OnEnter(GameState::Paused), open_pause_menu)
+            .add_systems(OnExit(GameState::Paused), close_menu)
+            .add_systems(
+                Update,
+                (
+                    menu_buttons,
+                    menu_escape.run_if(not(in_state(GameState::InGame))),
+                    highlight_buttons,
+                    build_menu.run_if(
+                        resource_changed::<Menu>
+                            .or_else(resource_changed::<Settings>)
+                            .or_else(resource_changed::<ActionBindings>)
+                            .or_else(resource_changed::<Rebinding>),
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.25, 0.25, 0.32);
+const BUTTON_PRESSED_COLOR: Color = Color::rgb(0.3, 0.55, 0.4);
+const VOLUME_STEP: f32 = 0.1;
+const SENSITIVITY_STEP: f32 = 0.1;
+const FOV_STEP: f32 = 5.0;
+const BINDING_WIDTH: f32 = 340.0;
+// Enough to fit every action on screen at once.
+const BINDING_COLUMNS: f32 = 3.0;
+
+// The controls that can't be rebound.
+const FIXED_CONTROLS: &str = "Mouse look, left click recaptures the cursor\n\
+    Arrows, digits and Enter in the label editor and tracking panel";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuPage {
+    Main,
+    Loading,
+    Pause,
+    // Goes back to the pause menu while paused, otherwise to the main menu.
+    Settings,
+}
+
+#[derive(Resource, Default, Debug)]
+struct Menu {
+    page: Option<MenuPage>,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+enum MenuAction {
+    NewGame,
+    Resume,
+    OpenSettings,
+    Back,
+    Quit,
+    ToggleFullscreen,
+    ToggleVsync,
+    ChangeVolume(VolumeBus, f32),
+    ChangeSensitivity(f32),
+    ChangeStickSensitivity(f32),
+    ChangeFov(f32),
+    CycleFrameLimit,
+    CycleShadows,
+    CycleBloom,
+    CyclePreset,
+    TogglePointLightShadows,
+    ToggleHdr,
+    ToggleAtmosphere,
+    ToggleMsaa,
+    ToggleDynamicResolution,
+    Rebind(Action),
+    ResetBindings,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+// Only while nothing else draws: the game's own camera takes over once the level is loaded.
+#[derive(Component)]
+struct MenuCamera;
+
+fn open_main_menu(mut commands: Commands, mut menu: ResMut<Menu>) {
+    commands.spawn((Camera2dBundle::default(), MenuCamera));
+    menu.page = Some(MenuPage::Main);
+}
+
+fn open_loading_screen(mut menu: ResMut<Menu>) {
+    menu.page = Some(MenuPage::Loading);
+}
+
+fn close_menu_after_loading(
+    mut commands: Commands,
+    mut menu: ResMut<Menu>,
+    camera_query: Query<Entity, With<MenuCamera>>,
+) {
+    for camera in camera_query.iter() {
+        commands.entity(camera).despawn_recursive();
+    }
+    menu.page = None;
+}
+
+fn open_pause_menu(mut menu: ResMut<Menu>) {
+    menu.page = Some(MenuPage::Pause);
+}
+
+fn close_menu(mut menu: ResMut<Menu>) {
+    menu.page = None;
+}
+
+// Where Back (or the Pause action) leads from a page.
+fn previous_page(page: MenuPage, state: &GameState) -> Option<MenuPage> {
+    match (page, state) {
+        (MenuPage::Settings, GameState::Paused) => Some(MenuPage::Pause),
+        (MenuPage::Settings, _) => Some(MenuPage::Main),
+        _ => None,
+    }
+}
+
+fn go_back(
+    menu: &mut Menu,
+    state: &GameState,
+    next_state: &mut NextState<GameState>,
+    settings: &Settings,
+    bindings: &ActionBindings,
+) {
+    let Some(page) = menu.page else {
+        return;
+    };
+    if page == MenuPage::Settings {
+        settings.save();
+        bindings.save();
+    }
+    match previous_page(page, state) {
+        Some(previous) => menu.page = Some(previous),
+        // Backing out of the pause menu resumes the game.
+        None if page == MenuPage::Pause => next_state.set(GameState::InGame),
+        None => {}
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_buttons(
+    mut menu: ResMut<Menu>,
+    mut settings: ResMut<Settings>,
+    mut bindings: ResMut<ActionBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+    button_query: Query<(&Interaction, &MenuAction), Changed<Interaction>>,
+) {
+    for (interaction, action) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *action {
+            MenuAction::NewGame => next_state.set(GameState::Loading),
+            MenuAction::Resume => next_state.set(GameState::InGame),
+            MenuAction::OpenSettings => menu.page = Some(MenuPage::Settings),
+            MenuAction::Back => go_back(
+                &mut menu,
+                state.get(),
+                &mut next_state,
+                &settings,
+                &bindings,
+            ),
+            MenuAction::Quit => {
+                exit.send(AppExit);
+            }
+            MenuAction::ToggleFullscreen => {
+                settings.graphics.fullscreen = !settings.graphics.fullscreen;
+            }
+            MenuAction::ToggleVsync => settings.graphics.vsync = !settings.graphics.vsync,
+            MenuAction::ChangeVolume(bus, step) => {
+                let volume = settings.audio.volume_mut(bus);
+                *volume = (*volume + step).clamp(0.0, 1.0);
+            }
+            MenuAction::ChangeSensitivity(step) => {
+                let sensitivity = settings.controls.mouse_sensitivity + step;
+                settings.controls.mouse_sensitivity =
+                    sensitivity.clamp(MIN_MOUSE_SENSITIVITY, MAX_MOUSE_SENSITIVITY);
+            }
+            MenuAction::ChangeStickSensitivity(step) => {
+                let sensitivity = settings.controls.stick_sensitivity + step;
+                settings.controls.stick_sensitivity =
+                    sensitivity.clamp(MIN_STICK_SENSITIVITY, MAX_STICK_SENSITIVITY);
+            }
+            MenuAction::ChangeFov(step) => {
+                let fov = settings.graphics.fov_degrees + step;
+                settings.graphics.fov_degrees = fov.clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES);
+            }
+            MenuAction::CycleFrameLimit => {
+                let graphics = &mut settings.graphics;
+                graphics.frame_limit = next_choice(&FrameLimit::CHOICES, graphics.frame_limit);
+            }
+            MenuAction::CycleShadows => {
+                let graphics = &mut settings.graphics;
+                graphics.shadow_map_size = next_choice(&SHADOW_MAP_SIZES, graphics.shadow_map_size);
+            }
+            MenuAction::CycleBloom => {
+                settings.graphics.bloom = next_choice(&BloomPreset::ALL, settings.graphics.bloom);
+            }
+            MenuAction::CyclePreset => {
+                // From custom settings, start over at the lowest preset.
+                let preset = match settings.graphics.preset() {
+                    Some(preset) => next_choice(&QualityPreset::ALL, preset),
+                    None => QualityPreset::Low,
+                };
+                settings.graphics.apply_preset(preset);
+            }
+            MenuAction::TogglePointLightShadows => {
+                let graphics = &mut settings.graphics;
+                graphics.point_light_shadows = !graphics.point_light_shadows;
+            }
+            MenuAction::ToggleHdr => settings.graphics.hdr = !settings.graphics.hdr,
+            MenuAction::ToggleAtmosphere => {
+                settings.graphics.atmosphere = !settings.graphics.atmosphere;
+            }
+            MenuAction::ToggleMsaa => settings.graphics.msaa = !settings.graphics.msaa,
+            MenuAction::ToggleDynamicResolution => {
+                let graphics = &mut settings.graphics;
+                graphics.dynamic_resolution = !graphics.dynamic_resolution;
+            }
+            MenuAction::Rebind(action) => rebinding.0 = Some(action),
+            MenuAction::ResetBindings => *bindings = ActionBindings::default(),
+        }
+    }
+}
+
+fn menu_escape(
+    actions: Res<ActionState>,
+    mut menu: ResMut<Menu>,
+    settings: Res<Settings>,
+    bindings: Res<ActionBindings>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        go_back(
+            &mut menu,
+            state.get(),
+            &mut next_state,
+            &settings,
+            &bindings,
+        );
+    }
+}
+
+type ButtonColorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<MenuAction>),
+>;
+
+fn highlight_buttons(mut button_query: ButtonColorQuery) {
+    for (interaction, mut background) in button_query.iter_mut() {
+        *background = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVER_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+fn spawn_text(parent: &mut ChildBuilder, theme: &UiTheme, text: &str, tone: TextTone, size: f32) {
+    parent.spawn((
+        TextBundle::from_section(text, theme.text_style(tone, size)),
+        ThemedText { tone, size },
+    ));
+}
+
+fn spawn_button(parent: &mut ChildBuilder, theme: &UiTheme, label: &str, action: MenuAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    min_width: Val::Px(48.0),
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|button| spawn_text(button, theme, label, TextTone::Normal, 1.25));
+}
+
+// A label, its value and a pair of buttons to step it down and up.
+fn spawn_stepper(
+    parent: &mut ChildBuilder,
+    theme: &UiTheme,
+    label: &str,
+    value: String,
+    down: MenuAction,
+    up: MenuAction,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_text(row, theme, label, TextTone::Normal, 1.25);
+            spawn_button(row, theme, "<", down);
+            spawn_text(row, theme, &value, TextTone::Accent, 1.25);
+            spawn_button(row, theme, ">", up);
+        });
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+// The on/off and multiple choice settings, side by side.
+fn spawn_graphics(parent: &mut ChildBuilder, theme: &UiTheme, graphics: &GraphicsSettings) {
+    let quality = graphics.preset().map_or("custom", |preset| preset.label());
+    let buttons = [
+        (format!("Quality: {}", quality), MenuAction::CyclePreset),
+        (
+            format!("Fullscreen: {}", on_off(graphics.fullscreen)),
+            MenuAction::ToggleFullscreen,
+        ),
+        (
+            format!("Vsync: {}", on_off(graphics.vsync)),
+            MenuAction::ToggleVsync,
+        ),
+        (
+            format!("Frame limit: {}", graphics.frame_limit.label()),
+            MenuAction::CycleFrameLimit,
+        ),
+        (
+            format!("Shadows: {}", graphics.shadow_map_size),
+            MenuAction::CycleShadows,
+        ),
+        (
+            format!(
+                "Point light shadows: {}",
+                on_off(graphics.point_light_shadows)
+            ),
+            MenuAction::TogglePointLightShadows,
+        ),
+        (
+            format!("HDR: {}", on_off(graphics.hdr)),
+            MenuAction::ToggleHdr,
+        ),
+        (
+            format!("Bloom: {}", graphics.bloom.label()),
+            MenuAction::CycleBloom,
+        ),
+        (
+            format!("Sky: {}", on_off(graphics.atmosphere)),
+            MenuAction::ToggleAtmosphere,
+        ),
+        (
+            format!("MSAA: {}", on_off(graphics.msaa)),
+            MenuAction::ToggleMsaa,
+        ),
+        (
+            format!(
+                "Dynamic resolution: {}",
+                on_off(graphics.dynamic_resolution)
+            ),
+            MenuAction::ToggleDynamicResolution,
+        ),
+    ];
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                max_width: Val::Px(BINDING_WIDTH * 2.0),
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            for (label, action) in buttons {
+                spawn_button(row, theme, &label, action);
+            }
+        });
+}
+
+// Columns of actions, each with a button that waits for the next press to rebind it.
+fn spawn_bindings(
+    parent: &mut ChildBuilder,
+    theme: &UiTheme,
+    bindings: &ActionBindings,
+    rebinding: Option<Action>,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(BINDING_WIDTH * BINDING_COLUMNS),
+                flex_wrap: FlexWrap::Wrap,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|grid| {
+            for action in Action::ALL {
+                let binding = if rebinding == Some(action) {
+                    "press a button...".to_string()
+                } else {
+                    bindings.describe(action)
+                };
+                grid.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(BINDING_WIDTH),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::SpaceBetween,
+                        padding: UiRect::horizontal(Val::Px(12.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_text(row, theme, action.label(), TextTone::Normal, 1.0);
+                    spawn_button(row, theme, &binding, MenuAction::Rebind(action));
+                });
+            }
+        });
+}
+
+fn spawn_page(
+    parent: &mut ChildBuilder,
+    theme: &UiTheme,
+    page: MenuPage,
+    settings: &Settings,
+    bindings: &ActionBindings,
+    rebinding: Option<Action>,
+) {
+    match page {
+        MenuPage::Main => {
+            spawn_text(parent, theme, "Courier", TextTone::Accent, 3.0);
+            spawn_button(parent, theme, "New game", MenuAction::NewGame);
+            spawn_button(parent, theme, "Settings", MenuAction::OpenSettings);
+            spawn_button(parent, theme, "Quit", MenuAction::Quit);
+        }
+        MenuPage::Loading => {
+            spawn_text(
+                parent,
+                theme,
+                "Loading the warehouse...",
+                TextTone::Normal,
+                1.5,
+            );
+        }
+        MenuPage::Pause => {
+            spawn_text(parent, theme, "Paused", TextTone::Accent, 2.0);
+            spawn_button(parent, theme, "Resume", MenuAction::Resume);
+            spawn_button(parent, theme, "Settings", MenuAction::OpenSettings);
+            spawn_button(parent, theme, "Quit to desktop", MenuAction::Quit);
+        }
+        MenuPage::Settings => {
+            spawn_text(parent, theme, "Settings", TextTone::Accent, 2.0);
+
+            spawn_text(parent, theme, "Graphics", TextTone::Muted, 1.0);
+            spawn_graphics(parent, theme, &settings.graphics);
+            spawn_stepper(
+                parent,
+                theme,
+                "Field of view",
+                format!("{:.0}°", settings.graphics.fov_degrees),
+                MenuAction::ChangeFov(-FOV_STEP),
+                MenuAction::ChangeFov(FOV_STEP),
+            );
+
+            spawn_text(parent, theme, "Audio", TextTone::Muted, 1.0);
+            for bus in VolumeBus::ALL {
+                spawn_stepper(
+                    parent,
+                    theme,
+                    bus.label(),
+                    format!("{:.0}%", settings.audio.volume(bus) * 100.0),
+                    MenuAction::ChangeVolume(bus, -VOLUME_STEP),
+                    MenuAction::ChangeVolume(bus, VOLUME_STEP),
+                );
+            }
+
+            spawn_text(parent, theme, "Controls", TextTone::Muted, 1.0);
+            spawn_stepper(
+                parent,
+                theme,
+                "Mouse sensitivity",
+                format!("{:.1}x", settings.controls.mouse_sensitivity),
+                MenuAction::ChangeSensitivity(-SENSITIVITY_STEP),
+                MenuAction::ChangeSensitivity(SENSITIVITY_STEP),
+            );
+            spawn_stepper(
+                parent,
+                theme,
+                "Stick sensitivity",
+                format!("{:.1}x", settings.controls.stick_sensitivity),
+                MenuAction::ChangeStickSensitivity(-SENSITIVITY_STEP),
+                MenuAction::ChangeStickSensitivity(SENSITIVITY_STEP),
+            );
+            spawn_bindings(parent, theme, bindings, rebinding);
+            spawn_button(parent, theme, "Reset bindings", MenuAction::ResetBindings);
+            spawn_text(parent, theme, FIXED_CONTROLS, TextTone::Normal, 0.9);
+
+            spawn_button(parent, theme, "Back", MenuAction::Back);
+        }
+    }
+}
+
+fn build_menu(
+    mut commands: Commands,
+    menu: Res<Menu>,
+    settings: Res<Settings>,
+    bindings: Res<ActionBindings>,
+    rebinding: Res<Rebinding>,
+    theme: Res<UiTheme>,
+    root_query: Query<Entity, With<MenuRoot>>,
+) {
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(page) = menu.page else {
+        return;
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: theme.panel_color.into(),
+                // Above the HUD and the other panels.
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ThemedPanel,
+            MenuRoot,
+        ))
+        .with_children(|root| {
+            spawn_page(root, &theme, page, &settings, &bindings, rebinding.0);
+        });
+}
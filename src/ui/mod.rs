@@ -0,0 +1,34 @@
+pub mod dashboard;
+pub mod hotbar;
+pub mod hud;
+pub mod label_editor;
+pub mod menus;
+pub mod staff_panel;
+pub mod theme;
+pub mod tracking_panel;
+
+use bevy::prelude::*;
+
+use self::dashboard::DashboardPlugin;
+use self::hotbar::HotbarPlugin;
+use self::hud::HudPlugin;
+use self::label_editor::LabelEditorPlugin;
+use self::menus::MenusPlugin;
+use self::staff_panel::StaffPanelPlugin;
+use self::theme::UiThemePlugin;
+use self::tracking_panel::TrackingPanelPlugin;
+
+pub struct GameUiPlugin;
+
+impl Plugin for GameUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(UiThemePlugin)
+            .add_plugins(HudPlugin)
+            .add_plugins(HotbarPlugin)
+            .add_plugins(TrackingPanelPlugin)
+            .add_plugins(LabelEditorPlugin)
+            .add_plugins(StaffPanelPlugin)
+            .add_plugins(DashboardPlugin)
+            .add_plugins(MenusPlugin);
+    }
+}
@@ -0,0 +1,273 @@
+// Staff office. O opens it; the slot keys (1-3) hire one of the candidates, and the modifier
+// (Alt) with a slot key lets someone on the roster go. The roster shows what everyone is doing
+// right now and what the payroll costs per hour. U unlocks the robot fleet, and after that buys
+// another robot.
+
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionBindings, ActionState};
+use crate::automation::robots::{
+    BuyRobot, Robot, RobotFleet, RobotTask, UnlockRobotFleet, FLEET_UNLOCK_COST, ROBOT_COST,
+};
+use crate::game_state::GameState;
+use crate::logistics::economy::Wallet;
+use crate::staff::jobs::{JobKind, JobQueue};
+use crate::staff::workers::{FireWorker, HireWorker, HiringPool, Worker, WorkerProfile};
+
+pub struct StaffPanelPlugin;
+
+impl Plugin for StaffPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StaffPanelState>()
+            .add_systems(Startup, spawn_staff_panel)
+            .add_systems(
+                Update,
+                (toggle_staff_panel, staff_panel_actions, update_staff_panel)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct StaffPanelState {
+    open: bool,
+}
+
+#[derive(Component)]
+struct StaffPanel;
+
+#[derive(Component)]
+struct StaffPanelText;
+
+fn spawn_staff_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    left: Val::Px(20.0),
+                    width: Val::Px(520.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.08, 0.85).into(),
+                ..default()
+            },
+            StaffPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                StaffPanelText,
+            ));
+        });
+}
+
+// For input that the panel's slot keys would otherwise also trigger.
+pub fn staff_panel_closed(state: Res<StaffPanelState>) -> bool {
+    !state.open
+}
+
+fn toggle_staff_panel(
+    actions: Res<ActionState>,
+    mut state: ResMut<StaffPanelState>,
+    mut panel_query: Query<&mut Style, With<StaffPanel>>,
+) {
+    if !actions.just_pressed(Action::StaffPanel) {
+        return;
+    }
+
+    state.open = !state.open;
+
+    for mut style in panel_query.iter_mut() {
+        style.display = if state.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+// Roster entries are numbered in hiring order.
+fn roster(worker_query: &Query<(Entity, &Worker)>) -> Vec<(Entity, Worker)> {
+    let mut roster: Vec<(Entity, Worker)> = worker_query
+        .iter()
+        .map(|(entity, worker)| (entity, worker.clone()))
+        .collect();
+    roster.sort_by_key(|(entity, _)| *entity);
+    roster
+}
+
+#[allow(clippy::too_many_arguments)]
+fn staff_panel_actions(
+    actions: Res<ActionState>,
+    state: Res<StaffPanelState>,
+    fleet: Res<RobotFleet>,
+    worker_query: Query<(Entity, &Worker)>,
+    mut hires: EventWriter<HireWorker>,
+    mut firings: EventWriter<FireWorker>,
+    mut unlocks: EventWriter<UnlockRobotFleet>,
+    mut robot_orders: EventWriter<BuyRobot>,
+) {
+    if !state.open {
+        return;
+    }
+
+    if actions.just_pressed(Action::BuyRobot) {
+        if fleet.unlocked {
+            robot_orders.send(BuyRobot);
+        } else {
+            unlocks.send(UnlockRobotFleet);
+        }
+    }
+
+    let Some(index) = actions.just_pressed_slot() else {
+        return;
+    };
+
+    if actions.pressed(Action::Modifier) {
+        if let Some((worker, _)) = roster(&worker_query).get(index) {
+            firings.send(FireWorker { worker: *worker });
+        }
+    } else {
+        hires.send(HireWorker { candidate: index });
+    }
+}
+
+fn describe_job(worker: &Worker) -> &'static str {
+    match worker.job.map(|job| job.kind) {
+        None => "idle",
+        Some(JobKind::UnloadTruck) => "unloading",
+        Some(JobKind::ScanAndSort) => "sorting",
+        Some(JobKind::MoveContainer) => "moving a container",
+        Some(JobKind::LoadTruck) => "loading a truck",
+    }
+}
+
+fn describe_robot(robot: &Robot) -> &'static str {
+    match robot.task {
+        RobotTask::Idle => "idle",
+        RobotTask::Fetching { .. } | RobotTask::Loading { .. } => "picking up",
+        RobotTask::Delivering { .. } | RobotTask::Unloading { .. } => "delivering",
+        RobotTask::GoingToCharge { .. } => "heading to charge",
+        RobotTask::Charging { .. } => "charging",
+    }
+}
+
+fn describe_profile(profile: &WorkerProfile) -> String {
+    let skills = profile.skills;
+    let skill_line = format!(
+        "unload {:.0}  sort {:.0}  load {:.0}",
+        skills.unloading * 100.0,
+        skills.sorting * 100.0,
+        skills.loading * 100.0,
+    );
+
+    format!(
+        "{}  ${:.2}/h\n    {}  speed {:.1} m/s  errors {:.0}%",
+        profile.name,
+        profile.hourly_wage,
+        skill_line,
+        profile.walk_speed,
+        profile.error_rate * 100.0,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_staff_panel(
+    state: Res<StaffPanelState>,
+    bindings: Res<ActionBindings>,
+    pool: Res<HiringPool>,
+    queue: Res<JobQueue>,
+    wallet: Res<Wallet>,
+    fleet: Res<RobotFleet>,
+    worker_query: Query<(Entity, &Worker)>,
+    robot_query: Query<(Entity, &Robot)>,
+    mut text_query: Query<&mut Text, With<StaffPanelText>>,
+) {
+    if !state.open {
+        return;
+    }
+
+    let roster = roster(&worker_query);
+    let payroll: f32 = roster
+        .iter()
+        .map(|(_, worker)| worker.profile.hourly_wage)
+        .sum();
+
+    let mut contents = format!(
+        "Staff office\nBalance ${:.2}   Payroll ${:.2}/h   Open jobs {}\n\nCandidates\n",
+        wallet.balance,
+        payroll,
+        queue.pending.len()
+    );
+    let slot = |index: usize| {
+        Action::SLOTS
+            .get(index)
+            .map_or("-".to_string(), |action| bindings.label(*action))
+    };
+    for (index, profile) in pool.candidates.iter().enumerate() {
+        contents.push_str(&format!(
+            "[{}] {}\n",
+            slot(index),
+            describe_profile(profile)
+        ));
+    }
+
+    contents.push_str(&format!(
+        "\nRoster ({}+slot to let go)\n",
+        bindings.label(Action::Modifier)
+    ));
+    if roster.is_empty() {
+        contents.push_str("Nobody yet. You're doing it all yourself.\n");
+    }
+    for (index, (_, worker)) in roster.iter().enumerate() {
+        contents.push_str(&format!(
+            "[{}] ({}) {}\n",
+            slot(index),
+            describe_job(worker),
+            describe_profile(&worker.profile)
+        ));
+    }
+
+    if fleet.unlocked {
+        contents.push_str(&format!(
+            "\nRobot fleet ({} to buy one, ${:.0})\n",
+            bindings.label(Action::BuyRobot),
+            ROBOT_COST
+        ));
+        let mut robots: Vec<(Entity, &Robot)> = robot_query.iter().collect();
+        robots.sort_by_key(|(entity, _)| *entity);
+        if robots.is_empty() {
+            contents.push_str("No robots yet.\n");
+        }
+        for (index, (_, robot)) in robots.iter().enumerate() {
+            contents.push_str(&format!(
+                "[R{}] {}  battery {:.0}%\n",
+                index + 1,
+                describe_robot(robot),
+                robot.battery * 100.0
+            ));
+        }
+    } else {
+        contents.push_str(&format!(
+            "\n{} to unlock the robot fleet (${:.0})\n",
+            bindings.label(Action::BuyRobot),
+            FLEET_UNLOCK_COST
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = contents.clone();
+    }
+}
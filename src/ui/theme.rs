@@ -0,0 +1,132 @@
+// Colors and fonts shared by the HUD, plus UI scaling. Everything is laid out in pixels for a
+// 1080p window and scaled to the actual window height, so the HUD keeps its proportions at any
+// resolution. Changing `UiTheme` restyles the HUD on the next frame.
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+pub struct UiThemePlugin;
+
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiTheme>().add_systems(
+            Update,
+            (
+                scale_ui_with_window,
+                apply_theme.run_if(resource_changed::<UiTheme>),
+            ),
+        );
+    }
+}
+
+const REFERENCE_HEIGHT: f32 = 1080.0;
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 3.0;
+
+#[derive(Resource, Clone, Debug)]
+pub struct UiTheme {
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub text_color: Color,
+    pub muted_text_color: Color,
+    pub accent_color: Color,
+    pub warning_color: Color,
+    pub panel_color: Color,
+    // On top of the resolution scaling, for players who want everything bigger or smaller.
+    pub scale: f32,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        UiTheme {
+            font: Handle::default(),
+            font_size: 16.0,
+            text_color: Color::WHITE,
+            muted_text_color: Color::rgb(0.75, 0.75, 0.8),
+            accent_color: Color::rgb(0.3, 0.85, 0.55),
+            warning_color: Color::rgb(0.95, 0.55, 0.3),
+            panel_color: Color::rgba(0.05, 0.05, 0.08, 0.75),
+            scale: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextTone {
+    Normal,
+    Muted,
+    Accent,
+    Warning,
+}
+
+// Text that follows the theme. `size` is relative to the theme's font size.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ThemedText {
+    pub tone: TextTone,
+    pub size: f32,
+}
+
+// A node whose background is the theme's panel color.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ThemedPanel;
+
+impl UiTheme {
+    pub fn color(&self, tone: TextTone) -> Color {
+        match tone {
+            TextTone::Normal => self.text_color,
+            TextTone::Muted => self.muted_text_color,
+            TextTone::Accent => self.accent_color,
+            TextTone::Warning => self.warning_color,
+        }
+    }
+
+    pub fn text_style(&self, tone: TextTone, size: f32) -> TextStyle {
+        TextStyle {
+            font: self.font.clone(),
+            font_size: self.font_size * size,
+            color: self.color(tone),
+        }
+    }
+
+    // An empty text node that follows the theme.
+    pub fn text(&self, tone: TextTone, size: f32) -> (TextBundle, ThemedText) {
+        (
+            TextBundle::from_section("", self.text_style(tone, size)),
+            ThemedText { tone, size },
+        )
+    }
+}
+
+fn scale_ui_with_window(
+    mut resized: EventReader<WindowResized>,
+    theme: Res<UiTheme>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if resized.read().count() == 0 && !theme.is_changed() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let scale = window.height() / REFERENCE_HEIGHT * theme.scale;
+    ui_scale.0 = scale.clamp(MIN_SCALE, MAX_SCALE);
+}
+
+fn apply_theme(
+    theme: Res<UiTheme>,
+    mut text_query: Query<(&mut Text, &ThemedText)>,
+    mut panel_query: Query<&mut BackgroundColor, With<ThemedPanel>>,
+) {
+    for (mut text, themed) in text_query.iter_mut() {
+        let style = theme.text_style(themed.tone, themed.size);
+        for section in text.sections.iter_mut() {
+            section.style = style.clone();
+        }
+    }
+
+    for mut background in panel_query.iter_mut() {
+        *background = theme.panel_color.into();
+    }
+}
@@ -0,0 +1,168 @@
+// "Track a package" panel. T opens it pre-filled with the package the player is looking at;
+// digits edit the tracking number and the history updates as it's typed.
+
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use bevy_fps_controller::controller::FpsController;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::levels::package_data::Package;
+use crate::logistics::tracking::TrackingLog;
+use crate::player::controller::PlayerInteractionSystem;
+
+pub struct TrackingPanelPlugin;
+
+impl Plugin for TrackingPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrackingPanelState>()
+            .add_systems(Startup, spawn_tracking_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_tracking_panel,
+                    type_tracking_number,
+                    update_tracking_panel,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+const TRACKING_NUMBER_LENGTH: usize = 12;
+
+#[derive(Resource, Default, Debug)]
+pub struct TrackingPanelState {
+    open: bool,
+    tracking_number: String,
+}
+
+#[derive(Component)]
+struct TrackingPanel;
+
+#[derive(Component)]
+struct TrackingPanelText;
+
+fn spawn_tracking_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    width: Val::Px(420.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.08, 0.85).into(),
+                ..default()
+            },
+            TrackingPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                TrackingPanelText,
+            ));
+        });
+}
+
+// For input that typing a tracking number would otherwise also trigger.
+pub fn tracking_panel_closed(state: Res<TrackingPanelState>) -> bool {
+    !state.open
+}
+
+fn toggle_tracking_panel(
+    actions: Res<ActionState>,
+    mut state: ResMut<TrackingPanelState>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    package_query: Query<&Package>,
+    mut panel_query: Query<&mut Style, With<TrackingPanel>>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    if !actions.just_pressed(Action::TrackPackage) {
+        return;
+    }
+
+    state.open = !state.open;
+
+    if state.open {
+        let looked_at = interaction_query
+            .iter()
+            .filter_map(|interaction| interaction.interactable_entity)
+            .find_map(|entity| package_query.get(entity).ok());
+        if let Some(package) = looked_at {
+            state.tracking_number = package.tracking_id.clone();
+        }
+    }
+
+    for mut style in panel_query.iter_mut() {
+        style.display = if state.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    // Typing shouldn't walk the player around.
+    for mut controller in controller_query.iter_mut() {
+        controller.enable_input = !state.open;
+    }
+}
+
+fn type_tracking_number(
+    input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut state: ResMut<TrackingPanelState>,
+) {
+    if !state.open {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.read() {
+        for c in event.char.chars().filter(char::is_ascii_digit) {
+            if state.tracking_number.len() < TRACKING_NUMBER_LENGTH {
+                state.tracking_number.push(c);
+            }
+        }
+    }
+
+    if input.just_pressed(KeyCode::Backspace) {
+        state.tracking_number.pop();
+    }
+}
+
+fn update_tracking_panel(
+    state: Res<TrackingPanelState>,
+    log: Res<TrackingLog>,
+    mut text_query: Query<&mut Text, With<TrackingPanelText>>,
+) {
+    if !state.open || !(state.is_changed() || log.is_changed()) {
+        return;
+    }
+
+    let mut contents = format!("Track a package\n> {}\n\n", state.tracking_number);
+    match log.history(&state.tracking_number) {
+        Some(history) => {
+            for event in history.iter().rev() {
+                contents.push_str(&format!("{}\n", event));
+            }
+        }
+        None => contents.push_str("No tracking information for this number."),
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = contents.clone();
+    }
+}
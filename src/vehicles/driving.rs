@@ -0,0 +1,204 @@
+// Getting in and out of vehicles. While driving, the camera is handed from the FPS controller's
+// `RenderPlayer` setup to a seat on the vehicle, and the logical player is parked out of the
+// physics world until the driver gets out again.
+
+use bevy::prelude::*;
+use bevy_fps_controller::controller::{FpsController, LogicalPlayer, RenderPlayer};
+use bevy_rapier3d::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::building::build_mode::not_building;
+use crate::game_state::GameState;
+use crate::player::controller::PlayerInteractionSystem;
+use crate::player::items::carry::Carried;
+use crate::vehicles::material_handling::{HitchRequest, Vehicle, VehicleKind};
+
+pub struct DrivingPlugin;
+
+impl Plugin for DrivingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                enter_or_exit_vehicle.run_if(not_building),
+                driver_input,
+                drive_vehicles,
+                keep_driver_seated,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+pub const ENTER_RANGE: f32 = 3.0;
+// Meters per second squared, both speeding up and braking.
+const ACCELERATION: f32 = 3.0;
+const GRAVITY: f32 = 9.81;
+
+// Marks the vehicle the player is driving, and remembers what to hand control back to.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Occupied {
+    pub camera: Entity,
+    pub logical_entity: Entity,
+}
+
+// Interact (E) gets into the closest vehicle in range, or out of the current one. While the player
+// is looking at or carrying a package, Interact is for the package instead, and in build mode it's
+// for taking down fixtures.
+#[allow(clippy::too_many_arguments)]
+fn enter_or_exit_vehicle(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    player_query: Query<(Entity, &Transform), With<LogicalPlayer>>,
+    camera_query: Query<Entity, With<RenderPlayer>>,
+    mut vehicle_query: Query<(Entity, &mut Vehicle, &Transform, Option<&Occupied>)>,
+    mut controller_query: Query<&mut FpsController>,
+    interaction_query: Query<&PlayerInteractionSystem>,
+    carried_query: Query<(), With<Carried>>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+
+    let occupied = vehicle_query
+        .iter_mut()
+        .find(|(_, _, _, occupied)| occupied.is_some());
+
+    if let Some((vehicle_entity, mut vehicle, vehicle_transform, Some(occupied))) = occupied {
+        let occupied = *occupied;
+        let side = Vec3::X * (vehicle.kind.half_extents().x + 1.0);
+        let exit_point = vehicle_transform.transform_point(side);
+
+        commands.entity(vehicle_entity).remove::<Occupied>();
+        vehicle.throttle = 0.0;
+        vehicle.steering = 0.0;
+        vehicle.lift_input = 0.0;
+
+        commands
+            .entity(occupied.camera)
+            .remove_parent()
+            .insert(RenderPlayer {
+                logical_entity: occupied.logical_entity,
+            });
+        commands
+            .entity(occupied.logical_entity)
+            .remove::<ColliderDisabled>()
+            .insert((
+                RigidBody::Dynamic,
+                Velocity::zero(),
+                Transform::from_translation(exit_point),
+            ));
+
+        for mut controller in controller_query.iter_mut() {
+            controller.enable_input = true;
+        }
+        return;
+    }
+
+    let busy_with_package = !carried_query.is_empty()
+        || interaction_query
+            .iter()
+            .any(|interaction| interaction.is_looking_at_item);
+    if busy_with_package {
+        return;
+    }
+
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+
+    let closest = vehicle_query
+        .iter()
+        .map(|(entity, vehicle, transform, _)| {
+            let distance = transform.translation.distance(player_transform.translation);
+            (entity, vehicle.kind, distance)
+        })
+        .filter(|(_, _, distance)| *distance < ENTER_RANGE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    let Some((vehicle_entity, kind, _)) = closest else {
+        return;
+    };
+
+    commands.entity(vehicle_entity).insert(Occupied {
+        camera,
+        logical_entity: player,
+    });
+    commands
+        .entity(camera)
+        .remove::<RenderPlayer>()
+        .set_parent(vehicle_entity)
+        .insert(Transform::from_translation(kind.seat_offset()));
+    commands
+        .entity(player)
+        .insert((RigidBody::KinematicPositionBased, ColliderDisabled));
+
+    for mut controller in controller_query.iter_mut() {
+        controller.enable_input = false;
+    }
+}
+
+// The movement actions (W/A/S/D) drive and steer. Lift up and down (Q and Z) raise and lower the
+// forks, or hitch and drop carts on a tugger.
+fn driver_input(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut vehicle_query: Query<(Entity, &mut Vehicle), With<Occupied>>,
+) {
+    for (entity, mut vehicle) in vehicle_query.iter_mut() {
+        vehicle.throttle = actions.axis(Action::MoveForward, Action::MoveBack);
+        vehicle.steering = actions.axis(Action::MoveRight, Action::MoveLeft);
+
+        if vehicle.kind == VehicleKind::Tugger {
+            if actions.just_pressed(Action::LiftUp) {
+                commands.entity(entity).insert(HitchRequest::Hitch);
+            } else if actions.just_pressed(Action::LiftDown) {
+                commands.entity(entity).insert(HitchRequest::Unhitch);
+            }
+        } else {
+            vehicle.lift_input = actions.axis(Action::LiftUp, Action::LiftDown);
+        }
+    }
+}
+
+fn drive_vehicles(
+    time: Res<Time>,
+    mut vehicle_query: Query<(
+        &mut Vehicle,
+        &mut Transform,
+        &mut KinematicCharacterController,
+    )>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut vehicle, mut transform, mut controller) in vehicle_query.iter_mut() {
+        let max_speed = vehicle.kind.max_speed();
+        let target_speed = vehicle.throttle * max_speed;
+        let max_change = ACCELERATION * dt;
+        vehicle.speed += (target_speed - vehicle.speed).clamp(-max_change, max_change);
+
+        // Steering only turns the vehicle while it's rolling, and reverses when backing up.
+        let rolling = (vehicle.speed / max_speed).clamp(-1.0, 1.0);
+        transform.rotate_y(-vehicle.steering * vehicle.kind.turn_rate() * rolling * dt);
+
+        let forward = *transform.forward();
+        controller.translation = Some(forward * vehicle.speed * dt - Vec3::Y * GRAVITY * dt);
+    }
+}
+
+// Keeps the parked logical player with the vehicle, so anything tracking where the player is still
+// gets a sensible answer while they drive.
+fn keep_driver_seated(
+    vehicle_query: Query<(&Transform, &Occupied), With<Vehicle>>,
+    mut player_query: Query<&mut Transform, (With<LogicalPlayer>, Without<Vehicle>)>,
+) {
+    for (vehicle_transform, occupied) in vehicle_query.iter() {
+        if let Ok(mut player_transform) = player_query.get_mut(occupied.logical_entity) {
+            player_transform.translation = vehicle_transform.translation;
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// Pallet jacks, forklifts and tuggers. Jacks and forklifts pick a container up on their forks when
+// the forks are raised under it; tuggers hitch a train of carts behind them. Carried containers
+// lose their own rigid body and ride along as part of the vehicle's, like packages do inside a
+// container.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_state::GameState;
+use crate::levels::asset_loader_plugin::AssetLoaderState;
+use crate::levels::markers::{normalize_tag, spawn_level_markers, VehicleSpawnPoint};
+use crate::logistics::containers::ShippingContainer;
+use crate::navigation::grid::NavObstacle;
+
+pub struct MaterialHandlingPlugin;
+
+impl Plugin for MaterialHandlingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AssetLoaderState::Done),
+            spawn_vehicles.after(spawn_level_markers),
+        )
+        .add_systems(
+            Update,
+            (update_forks, pick_up_on_forks, hitch_carts)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+// How close a container has to be to the fork tips or the tow hitch to be picked up.
+const PICKUP_RANGE: f32 = 1.2;
+const MAX_CARTS: usize = 3;
+const CART_SPACING: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VehicleKind {
+    PalletJack,
+    Forklift,
+    Tugger,
+}
+
+impl VehicleKind {
+    pub fn from_marker_name(name: &str) -> Option<Self> {
+        let normalized = normalize_tag(name);
+
+        [
+            ("palletjack", VehicleKind::PalletJack),
+            ("forklift", VehicleKind::Forklift),
+            ("tugger", VehicleKind::Tugger),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| normalized.starts_with(prefix))
+        .map(|(_, kind)| kind)
+    }
+
+    pub fn max_speed(&self) -> f32 {
+        match self {
+            VehicleKind::PalletJack => 1.8,
+            VehicleKind::Forklift => 4.0,
+            VehicleKind::Tugger => 5.0,
+        }
+    }
+
+    // Radians per second at full lock.
+    pub fn turn_rate(&self) -> f32 {
+        match self {
+            VehicleKind::PalletJack => 1.5,
+            VehicleKind::Forklift => 1.2,
+            VehicleKind::Tugger => 1.0,
+        }
+    }
+
+    // Tuggers have no forks.
+    pub fn max_lift_height(&self) -> f32 {
+        match self {
+            VehicleKind::PalletJack => 0.2,
+            VehicleKind::Forklift => 3.0,
+            VehicleKind::Tugger => 0.0,
+        }
+    }
+
+    pub fn lift_speed(&self) -> f32 {
+        match self {
+            VehicleKind::PalletJack => 0.2,
+            VehicleKind::Forklift => 0.5,
+            VehicleKind::Tugger => 0.0,
+        }
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        match self {
+            VehicleKind::PalletJack => Vec3::new(0.4, 0.6, 0.5),
+            VehicleKind::Forklift => Vec3::new(0.6, 1.1, 1.2),
+            VehicleKind::Tugger => Vec3::new(0.6, 0.8, 1.0),
+        }
+    }
+
+    // Where the driver's eyes are, relative to the vehicle.
+    pub fn seat_offset(&self) -> Vec3 {
+        match self {
+            VehicleKind::PalletJack => Vec3::new(0.0, 1.6, 1.2),
+            VehicleKind::Forklift => Vec3::new(0.0, 2.0, 0.3),
+            VehicleKind::Tugger => Vec3::new(0.0, 1.7, 0.2),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            VehicleKind::PalletJack => Color::rgb(0.85, 0.2, 0.15),
+            VehicleKind::Forklift => Color::rgb(0.95, 0.7, 0.1),
+            VehicleKind::Tugger => Color::rgb(0.2, 0.45, 0.8),
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Vehicle {
+    pub kind: VehicleKind,
+    pub speed: f32,
+    pub lift_height: f32,
+    // Driver input, in -1.0..=1.0.
+    pub throttle: f32,
+    pub steering: f32,
+    pub lift_input: f32,
+    pub carried: Option<Entity>,
+    pub carts: Vec<Entity>,
+}
+
+impl Vehicle {
+    pub fn new(kind: VehicleKind) -> Self {
+        Vehicle {
+            kind,
+            speed: 0.0,
+            lift_height: 0.0,
+            throttle: 0.0,
+            steering: 0.0,
+            lift_input: 0.0,
+            carried: None,
+            carts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Forks;
+
+// Set for one frame when the tugger's driver asks to hitch or drop a cart.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HitchRequest {
+    Hitch,
+    Unhitch,
+}
+
+fn spawn_vehicles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    spawn_query: Query<(&VehicleSpawnPoint, &Transform)>,
+) {
+    let fork_mesh = meshes.add(Cuboid::new(0.9, 0.08, 1.1));
+    let fork_material = materials.add(Color::DARK_GRAY);
+
+    for (spawn_point, transform) in spawn_query.iter() {
+        let Some(kind) = VehicleKind::from_marker_name(&spawn_point.name) else {
+            continue;
+        };
+        let half_extents = kind.half_extents();
+        let translation = transform.translation + Vec3::Y * half_extents.y;
+
+        let mut vehicle = commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_size(half_extents * 2.0)),
+                material: materials.add(kind.color()),
+                transform: Transform::from_translation(translation)
+                    .with_rotation(transform.rotation),
+                ..default()
+            },
+            Name::new(spawn_point.name.clone()),
+            Vehicle::new(kind),
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+            KinematicCharacterController {
+                apply_impulse_to_dynamic_bodies: true,
+                ..default()
+            },
+            NavObstacle {
+                half_extents: half_extents.xz(),
+            },
+        ));
+
+        if kind.max_lift_height() > 0.0 {
+            vehicle.with_children(|vehicle| {
+                vehicle.spawn((
+                    PbrBundle {
+                        mesh: fork_mesh.clone(),
+                        material: fork_material.clone(),
+                        transform: Transform::from_translation(fork_base(kind)),
+                        ..default()
+                    },
+                    Forks,
+                ));
+            });
+        }
+    }
+}
+
+// Forks sit just above the floor, sticking out of the vehicle's front (-Z).
+fn fork_base(kind: VehicleKind) -> Vec3 {
+    let half_extents = kind.half_extents();
+    Vec3::new(0.0, 0.1 - half_extents.y, -half_extents.z - 0.55)
+}
+
+fn update_forks(
+    time: Res<Time>,
+    mut vehicle_query: Query<(&mut Vehicle, &Children)>,
+    mut fork_query: Query<&mut Transform, With<Forks>>,
+) {
+    for (mut vehicle, children) in vehicle_query.iter_mut() {
+        let kind = vehicle.kind;
+        vehicle.lift_height = (vehicle.lift_height
+            + vehicle.lift_input * kind.lift_speed() * time.delta_seconds())
+        .clamp(0.0, kind.max_lift_height());
+
+        for child in children.iter() {
+            if let Ok(mut fork_transform) = fork_query.get_mut(*child) {
+                fork_transform.translation = fork_base(kind) + Vec3::Y * vehicle.lift_height;
+            }
+        }
+    }
+}
+
+// Raising the forks under a container picks it up; lowering them all the way sets it back down.
+fn pick_up_on_forks(
+    mut commands: Commands,
+    mut vehicle_query: Query<(&mut Vehicle, &Children)>,
+    fork_query: Query<&GlobalTransform, With<Forks>>,
+    container_query: Query<(Entity, &GlobalTransform, Option<&Parent>), With<ShippingContainer>>,
+) {
+    for (mut vehicle, children) in vehicle_query.iter_mut() {
+        let Some((forks, fork_transform)) = children
+            .iter()
+            .find_map(|child| fork_query.get(*child).ok().map(|t| (*child, t)))
+        else {
+            continue;
+        };
+
+        match vehicle.carried {
+            Some(container) if vehicle.lift_height <= 0.0 => {
+                commands
+                    .entity(container)
+                    .remove_parent_in_place()
+                    .insert(RigidBody::Dynamic);
+                vehicle.carried = None;
+            }
+            None if vehicle.lift_input > 0.0 => {
+                let nearby = container_query
+                    .iter()
+                    .filter(|(_, _, parent)| parent.is_none())
+                    .find(|(_, transform, _)| {
+                        transform
+                            .translation()
+                            .distance(fork_transform.translation())
+                            < PICKUP_RANGE
+                    });
+
+                if let Some((container, _, _)) = nearby {
+                    commands
+                        .entity(container)
+                        .remove::<RigidBody>()
+                        .set_parent(forks)
+                        .insert(Transform::from_xyz(0.0, 0.1, 0.0));
+                    vehicle.carried = Some(container);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn hitch_carts(
+    mut commands: Commands,
+    mut vehicle_query: Query<(Entity, &mut Vehicle, &GlobalTransform, &HitchRequest)>,
+    container_query: Query<(Entity, &GlobalTransform, Option<&Parent>), With<ShippingContainer>>,
+) {
+    for (vehicle_entity, mut vehicle, vehicle_transform, request) in vehicle_query.iter_mut() {
+        commands.entity(vehicle_entity).remove::<HitchRequest>();
+
+        if vehicle.kind != VehicleKind::Tugger {
+            continue;
+        }
+
+        match request {
+            HitchRequest::Hitch if vehicle.carts.len() < MAX_CARTS => {
+                let behind = vehicle.kind.half_extents().z
+                    + CART_SPACING * (vehicle.carts.len() as f32 + 0.5);
+                let hitch_point = vehicle_transform.transform_point(Vec3::Z * behind);
+
+                let nearby = container_query
+                    .iter()
+                    .filter(|(_, _, parent)| parent.is_none())
+                    .find(|(_, transform, _)| {
+                        transform.translation().xz().distance(hitch_point.xz()) < CART_SPACING
+                    });
+
+                if let Some((cart, _, _)) = nearby {
+                    let local = Vec3::new(0.0, -vehicle.kind.half_extents().y, behind);
+                    commands
+                        .entity(cart)
+                        .remove::<RigidBody>()
+                        .set_parent(vehicle_entity)
+                        .insert(Transform::from_translation(local));
+                    vehicle.carts.push(cart);
+                }
+            }
+            HitchRequest::Unhitch => {
+                if let Some(cart) = vehicle.carts.pop() {
+                    commands
+                        .entity(cart)
+                        .remove_parent_in_place()
+                        .insert(RigidBody::Dynamic);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,16 @@
+pub mod driving;
+pub mod material_handling;
+
+use bevy::prelude::*;
+
+use self::driving::DrivingPlugin;
+use self::material_handling::MaterialHandlingPlugin;
+
+pub struct VehiclesPlugin;
+
+impl Plugin for VehiclesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialHandlingPlugin)
+            .add_plugins(DrivingPlugin);
+    }
+}